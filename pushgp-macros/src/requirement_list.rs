@@ -3,14 +3,37 @@ use syn::*;
 
 pub struct RequirementList {
     pub idents: Vec<Ident>,
+
+    // Replaces the `.` between the stack and the function in the instruction name, as in `separator = "_"`
+    pub separator: Option<String>,
+
+    // Replaces the stack part of the instruction name, as in `prefix = "int"`
+    pub prefix: Option<String>,
 }
 
 impl Parse for RequirementList {
     fn parse(input: ParseStream) -> Result<Self> {
         let mut idents = vec![];
+        let mut separator = None;
+        let mut prefix = None;
         while !input.is_empty() {
             let ident: Ident = input.parse()?;
-            idents.push(ident);
+            if input.peek(Token![=]) {
+                let _eq: Token![=] = input.parse()?;
+                let value: LitStr = input.parse()?;
+                if ident == "separator" {
+                    separator = Some(value.value());
+                } else if ident == "prefix" {
+                    prefix = Some(value.value());
+                } else {
+                    return Err(Error::new(
+                        ident.span(),
+                        "the only named options are `separator` and `prefix`",
+                    ));
+                }
+            } else {
+                idents.push(ident);
+            }
 
             if input.is_empty() {
                 break;
@@ -18,6 +41,14 @@ impl Parse for RequirementList {
             let _comma: Token![,] = input.parse()?;
         }
 
-        Ok(RequirementList { idents })
+        if idents.is_empty() {
+            return Err(input.error("the first parameter must be the stack of the instruction"));
+        }
+
+        Ok(RequirementList {
+            idents,
+            separator,
+            prefix,
+        })
     }
 }
@@ -44,13 +44,26 @@ pub fn handle_macro(requirements: &RequirementList, inner_fn: &mut ItemFn) -> Re
         function_name.to_case(Case::Pascal)
     ))?;
 
-    // Use the base stack name plus the name of the function to generate the name of the instruction
+    // Use the base stack name plus the name of the function to generate the name of the instruction. Both the stack
+    // part and the separator can be replaced through the attribute to match the names used by other Push dialects.
     let instruction_name_str = format!(
-        "{}.{}",
-        stack_name.to_case(Case::UpperFlat),
+        "{}{}{}",
+        requirements
+            .prefix
+            .clone()
+            .unwrap_or_else(|| stack_name.to_case(Case::UpperFlat)),
+        requirements.separator.as_deref().unwrap_or("."),
         function_name.to_case(Case::UpperFlat)
     );
 
+    // The category is the base stack when it is one of the stacks defined by 'pushgp'
+    let category: Ident = match stack_name.as_str() {
+        "Bool" | "Code" | "Exec" | "Float" | "Integer" | "Name" => {
+            syn::parse_str::<Ident>(&stack_name)?
+        }
+        _ => syn::parse_str::<Ident>("Other")?,
+    };
+
     // Only keep the 'doc' attributes from what's supplied for the function
     inner_fn.attrs.retain(|attr| attr.path.is_ident("doc"));
     let docs = inner_fn.attrs.iter();
@@ -90,6 +103,8 @@ pub fn handle_macro(requirements: &RequirementList, inner_fn: &mut ItemFn) -> Re
         where
             Vm: #(#bound_types)+*,
         {
+            const CATEGORY: #pushgp::InstructionCategory = #pushgp::InstructionCategory::#category;
+
             fn parse<'a>(input: &'a str, opcode: #pushgp::Opcode) -> nom::IResult<&'a str, #pushgp::Code> {
                 let (rest, _) = nom::bytes::complete::tag(#struct_name::static_name())(input)?;
                 let (rest, _) = #pushgp::space_or_end(rest)?;
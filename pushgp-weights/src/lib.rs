@@ -1,5 +1,6 @@
 extern crate pushgp_macros;
 
+mod plateau;
 mod set_instruction_weight;
 mod target;
 mod vm;
@@ -7,8 +8,9 @@ mod weight;
 mod weight_finding_island;
 
 use fnv::FnvHashMap;
-use pushgp::{RunResult, VirtualMachine, World, WorldConfiguration};
+use pushgp::{sorted_by_name, RunResult, VirtualMachine, World, WorldConfiguration};
 
+pub use plateau::*;
 pub use set_instruction_weight::*;
 pub use target::*;
 pub use vm::*;
@@ -35,24 +37,23 @@ pub fn find_best_weights<TargetRunResult: RunResult, TargetVm: VirtualMachine>(
         score: 0,
         weights: FnvHashMap::default(),
     };
-    let mut generations_since_new_best = 0;
+    let mut plateau = Plateau::new(10, 0.0);
+    plateau.update(best_result.score as f64);
     weight_finding_world.run_generations_while(|w| {
         generations_complete += 1;
-        generations_since_new_best += 1;
 
         let island = w.get_island(0).unwrap();
         let best_run_result = island.most_fit_individual().unwrap().get_run_result().unwrap();
         println!("WeightFinder: Generation {}, best score is {}", generations_complete, best_run_result.score);
-        if best_run_result.score > best_result.score {
+        if plateau.update(best_run_result.score as f64) {
             best_result = best_run_result.clone();
-            generations_since_new_best = 0;
         }
 
-        generations_since_new_best < 10
+        !plateau.has_plateaued()
     });
 
     println!("WeightFinder: best weights are:");
-    for (name, weight) in best_result.weights.iter() {
+    for (name, weight) in sorted_by_name(&best_result.weights) {
         println!("  {}: {}", weight, name);
     }
     best_result.weights
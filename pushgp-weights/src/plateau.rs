@@ -0,0 +1,79 @@
+/// Tracks the best score seen over a series of generations so that a run can stop once the score has plateaued. A new
+/// score only counts as an improvement if it beats the best score by more than `improvement_epsilon`, so that noise in
+/// a floating point fitness is not mistaken for progress.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Plateau {
+    patience: usize,
+    improvement_epsilon: f64,
+    best_score: Option<f64>,
+    generations_since_improvement: usize,
+}
+
+impl Plateau {
+    /// Creates a tracker that has plateaued once `patience` generations in a row have not improved the best score by
+    /// more than `improvement_epsilon`. Use an epsilon of zero to count any increase as an improvement.
+    pub fn new(patience: usize, improvement_epsilon: f64) -> Plateau {
+        Plateau { patience, improvement_epsilon, best_score: None, generations_since_improvement: 0 }
+    }
+
+    /// Records the best score of the latest generation. Returns true if it was an improvement, in which case the
+    /// patience counter is reset.
+    pub fn update(&mut self, score: f64) -> bool {
+        let improved = match self.best_score {
+            Some(best) => score > best + self.improvement_epsilon,
+            None => true,
+        };
+
+        if improved {
+            self.best_score = Some(score);
+            self.generations_since_improvement = 0;
+        } else {
+            self.generations_since_improvement += 1;
+        }
+
+        improved
+    }
+
+    /// Returns the best score recorded so far, or None if no scores have been recorded
+    pub fn best_score(&self) -> Option<f64> {
+        self.best_score
+    }
+
+    /// Returns the number of generations since the last improvement
+    pub fn generations_since_improvement(&self) -> usize {
+        self.generations_since_improvement
+    }
+
+    /// Returns true once the patience has run out without an improvement
+    pub fn has_plateaued(&self) -> bool {
+        self.generations_since_improvement >= self.patience
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Plateau;
+
+    #[test]
+    fn sub_epsilon_improvements_do_not_reset_patience() {
+        let mut plateau = Plateau::new(3, 0.01);
+        assert!(plateau.update(1.0));
+
+        // Tiny improvements are noise
+        assert!(!plateau.update(1.005));
+        assert!(!plateau.update(1.009));
+        assert_eq!(2, plateau.generations_since_improvement());
+        assert_eq!(Some(1.0), plateau.best_score());
+
+        // A real improvement resets the counter
+        assert!(plateau.update(1.5));
+        assert_eq!(0, plateau.generations_since_improvement());
+        assert_eq!(Some(1.5), plateau.best_score());
+
+        for _ in 0..3 {
+            assert!(!plateau.has_plateaued());
+            plateau.update(1.505);
+        }
+        assert!(plateau.has_plateaued());
+    }
+}
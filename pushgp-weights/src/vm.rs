@@ -65,6 +65,19 @@ impl<TargetRunResult: RunResult, TargetVm: VirtualMachine> VirtualMachine
         self.integer_stack.clear();
         self.weight_stack.clear();
     }
+
+    fn peek_stack(&mut self, stack: &str) -> Option<Data> {
+        match stack {
+            "EXEC" => self.engine.exec().peek().map(|code| Data::CodeList(vec![code])),
+            "INTEGER" => self.integer_stack.peek().map(Data::Integer),
+            "WEIGHT" => self.weight_stack.peek().map(|weight| weight.into()),
+            _ => None,
+        }
+    }
+
+    fn stack_names(&self) -> &'static [&'static str] {
+        &["EXEC", "INTEGER", "WEIGHT"]
+    }
 }
 
 impl<TargetRunResult: RunResult, TargetVm: VirtualMachine>
@@ -0,0 +1,161 @@
+use crate::*;
+use fnv::{FnvHashMap, FnvHashSet};
+
+// The stacks that Clojush instruction names start with, and the name of the same stack in this crate
+const CLOJUSH_STACKS: [(&str, &str); 6] = [
+    ("boolean", "BOOL"),
+    ("code", "CODE"),
+    ("exec", "EXEC"),
+    ("float", "FLOAT"),
+    ("integer", "INTEGER"),
+    ("name", "NAME"),
+];
+
+/// Returns the default table used by `from_clojush` to translate Clojush instruction names into the names used by this
+/// crate. Only the names that cannot be translated by the general rule need to be in the table: the general rule turns
+/// `integer_yankdup` into `INTEGER.YANKDUP` by mapping the stack and uppercasing the rest of the name. Both the
+/// Clojush names (`integer_add`) and the older Push3 symbols (`integer_+`) are included.
+pub fn default_clojush_aliases() -> FnvHashMap<String, String> {
+    let mut aliases = FnvHashMap::default();
+    let numeric_operations = [
+        ("add", "+", "SUM"),
+        ("sub", "-", "DIFFERENCE"),
+        ("mult", "*", "PRODUCT"),
+        ("div", "/", "QUOTIENT"),
+        ("mod", "%", "MODULO"),
+        ("lt", "<", "LESS"),
+        ("gt", ">", "GREATER"),
+    ];
+    for (clojush_stack, stack) in [("integer", "INTEGER"), ("float", "FLOAT")] {
+        for (name, symbol, instruction) in numeric_operations {
+            let instruction = format!("{}.{}", stack, instruction);
+            aliases.insert(format!("{}_{}", clojush_stack, name), instruction.clone());
+            aliases.insert(format!("{}_{}", clojush_stack, symbol), instruction);
+        }
+    }
+    for (clojush_stack, stack) in CLOJUSH_STACKS {
+        aliases.insert(format!("{}_eq", clojush_stack), format!("{}.EQUAL", stack));
+        aliases.insert(format!("{}_=", clojush_stack), format!("{}.EQUAL", stack));
+    }
+    for (clojush_stack, stack) in [("code", "CODE"), ("exec", "EXEC")] {
+        aliases.insert(format!("{}_do*count", clojush_stack), format!("{}.DONCOUNT", stack));
+        aliases.insert(format!("{}_do*range", clojush_stack), format!("{}.DONRANGE", stack));
+        aliases.insert(format!("{}_do*times", clojush_stack), format!("{}.DONTIMES", stack));
+    }
+    aliases.insert("boolean_frominteger".to_owned(), "BOOL.FROMINT".to_owned());
+
+    aliases
+}
+
+/// Parses a program exported from Clojush (or another Push implementation that uses the same format) using the
+/// default alias table. See `from_clojush_with_aliases`.
+pub fn from_clojush<Vm: VirtualMachine>(vm: &Vm, src: &str) -> Result<Code, ParseError> {
+    from_clojush_with_aliases(vm, src, &default_clojush_aliases())
+}
+
+/// Parses a program exported from Clojush, such as `(1 2 integer_add exec_if (true) (false))`. Parentheses do not need
+/// to be separated from their contents by white-space, `true` and `false` are the boolean literals, and instructions
+/// are named like `integer_add`. Each instruction is translated using the alias table or else the general rule
+/// described by `default_clojush_aliases`.
+///
+/// Returns an error if the program uses an instruction that does not exist in the virtual machine, rather than treating
+/// it as a name.
+pub fn from_clojush_with_aliases<Vm: VirtualMachine>(
+    vm: &Vm,
+    src: &str,
+    aliases: &FnvHashMap<String, String>,
+) -> Result<Code, ParseError> {
+    let mut instructions = FnvHashSet::default();
+    let mut opcode = 0;
+    while let Some(name) = vm.engine().name_for_opcode(opcode) {
+        instructions.insert(name);
+        opcode += 1;
+    }
+
+    let spaced = src.replace('(', " ( ").replace(')', " ) ");
+    let mut tokens = vec![];
+    for token in spaced.split_whitespace() {
+        let translated = match token {
+            "true" => "TRUE".to_owned(),
+            "false" => "FALSE".to_owned(),
+            _ => match translate_instruction(token, aliases) {
+                Some(instruction) if instructions.contains(instruction.as_str()) => instruction,
+                Some(instruction) => {
+                    return Err(ParseError::new_with_message(format!(
+                        "the Clojush instruction {} ({}) does not exist",
+                        token, instruction
+                    )))
+                }
+                None => token.to_owned(),
+            },
+        };
+        tokens.push(translated);
+    }
+
+    let translated = tokens.join(" ");
+    let (rest, code) = vm.engine().parse(&translated).map_err(ParseError::new)?;
+    if rest.trim().is_empty() {
+        Ok(code)
+    } else {
+        Err(ParseError::new_with_message("the code did not finish parsing"))
+    }
+}
+
+// Returns the name of the instruction for the token, or None if the token does not look like a Clojush instruction
+fn translate_instruction(token: &str, aliases: &FnvHashMap<String, String>) -> Option<String> {
+    if let Some(instruction) = aliases.get(token) {
+        return Some(instruction.clone());
+    }
+    let (clojush_stack, name) = token.split_once('_')?;
+    let (_, stack) = CLOJUSH_STACKS.iter().find(|(clojush, _)| *clojush == clojush_stack)?;
+    Some(format!("{}.{}", stack, name.to_uppercase()))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    fn new_base_vm() -> BaseVm {
+        let mut vm = BaseVm::new(Some(1), Configuration::new_simple());
+        add_base_instructions(&mut vm);
+        add_base_literals(&mut vm);
+        vm
+    }
+
+    #[test]
+    fn clojush_snippets() {
+        let vm = new_base_vm();
+        let translations = [
+            ("(1 2 integer_add)", "( 1 2 INTEGER.SUM )"),
+            (
+                "(3 4 integer_* integer_dup integer_stackdepth)",
+                "( 3 4 INTEGER.PRODUCT INTEGER.DUP INTEGER.STACKDEPTH )",
+            ),
+            (
+                "(true boolean_not exec_if (1.5 float_sin) (false))",
+                "( TRUE BOOL.NOT EXEC.IF ( 1.5 FLOAT.SIN ) ( FALSE ) )",
+            ),
+            (
+                "(5 exec_do*times (2 integer_yankdup) boolean_frominteger)",
+                "( 5 EXEC.DONTIMES ( 2 INTEGER.YANKDUP ) BOOL.FROMINT )",
+            ),
+            ("(code_quote (x) x code_eq)", "( CODE.QUOTE ( x ) x CODE.EQUAL )"),
+        ];
+        for (clojush, expected) in translations {
+            assert_eq!(vm.engine().must_parse(expected), from_clojush(&vm, clojush).unwrap(), "{}", clojush);
+        }
+    }
+
+    #[test]
+    fn clojush_aliases() {
+        let vm = new_base_vm();
+        assert!(from_clojush(&vm, "(1 integer_inc)").is_err());
+
+        let mut aliases = default_clojush_aliases();
+        aliases.insert("integer_inc".to_owned(), "INTEGER.DUP".to_owned());
+        assert_eq!(
+            vm.engine().must_parse("( 1 INTEGER.DUP )"),
+            from_clojush_with_aliases(&vm, "(1 integer_inc)", &aliases).unwrap()
+        );
+    }
+}
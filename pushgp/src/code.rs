@@ -1,7 +1,9 @@
-use fnv::FnvHashMap;
+use fnv::{FnvHashMap, FnvHasher};
+use rust_decimal::Decimal;
+use std::hash::Hasher;
 
 use crate::{
-    Data, ExecutionError, Name, VirtualMachine, VirtualMachineMustHaveBool, VirtualMachineMustHaveCode,
+    Data, ExecutionError, Name, ParseError, VirtualMachine, VirtualMachineMustHaveBool, VirtualMachineMustHaveCode,
     VirtualMachineMustHaveExec, VirtualMachineMustHaveFloat, VirtualMachineMustHaveInteger, VirtualMachineMustHaveName,
 };
 
@@ -69,25 +71,13 @@ impl Code {
 
     /// Returns true if the specified code is equal to this item or any child
     pub fn contains(&self, look_for: &Code) -> bool {
-        self == look_for || (self.is_list() && self.data.code_iter().unwrap().any(|i| i.contains(look_for)))
+        self.subtrees().any(|item| item == look_for)
     }
 
     /// Returns the smallest sub-list that contains the specified code
     pub fn container(&self, look_for: &Code) -> Option<Code> {
-        // If this is an atom it cannot be a container
-        if self.is_list() {
-            for i in self.data.code_iter().unwrap() {
-                if i == look_for {
-                    return Some(self.clone());
-                }
-            }
-            for i in self.data.code_iter().unwrap() {
-                if let Some(code) = i.container(look_for) {
-                    return Some(code);
-                }
-            }
-        }
-        None
+        // Atoms cannot be a container, and the first list in depth-first order to hold the code is the smallest
+        self.subtrees().find(|item| item.is_list() && item.data.code_iter().unwrap().any(|i| i == look_for)).cloned()
     }
 
     /// Similar to `contains` but does not recurse into Lists
@@ -103,18 +93,155 @@ impl Code {
         items
     }
 
-    /// Appends this item to an already-existing discrepancy items HashMap
-    fn append_discrepancy_items(&self, items: &mut FnvHashMap<Code, i64>) {
-        // Append 'self' whether it is an atom or a list
-        let counter = items.entry(self.clone()).or_insert(0);
-        *counter += 1;
+    /// Returns an iterator over every sub-list and atom in the code (including the code itself) in depth-first order.
+    /// This visits the same items that `discrepancy_items` counts, but borrows them rather than cloning them.
+    pub fn subtrees(&self) -> impl Iterator<Item = &Code> {
+        Subtrees { pending: vec![self] }
+    }
 
-        // If this is a list, also append all the items in the list
-        if self.is_list() {
-            for i in self.data.code_iter().unwrap() {
-                i.append_discrepancy_items(items);
+    /// Returns a 64-bit hash of the code that is the same on every run and every platform, unlike the `Hash` trait which
+    /// does not promise a stable value. Equal code always has an equal hash.
+    ///
+    /// The hash is 64-bit FNV-1a over the items returned by `subtrees`. Each item contributes its opcode as four
+    /// little-endian bytes, then one byte for the kind of data it holds, then the data: integers as eight little-endian
+    /// bytes, decimals as the 16 bytes of `Decimal::serialize` after normalizing, strings and byte data as an eight byte
+    /// little-endian length followed by the bytes, and lists as their eight byte little-endian length. Opcodes depend on
+    /// the order in which instructions are added, so the hash is only comparable between virtual machines with the same
+    /// `instruction_fingerprint`.
+    pub fn stable_hash(&self) -> u64 {
+        let mut hasher = FnvHasher::default();
+        let write_len_and_bytes = |hasher: &mut FnvHasher, bytes: &[u8]| {
+            hasher.write(&(bytes.len() as u64).to_le_bytes());
+            hasher.write(bytes);
+        };
+        for item in self.subtrees() {
+            hasher.write(&item.opcode.to_le_bytes());
+            match &item.data {
+                Data::None => hasher.write(&[0]),
+                Data::Integer(x) => {
+                    hasher.write(&[1]);
+                    hasher.write(&x.to_le_bytes());
+                }
+                Data::UnsignedInteger(x) => {
+                    hasher.write(&[2]);
+                    hasher.write(&x.to_le_bytes());
+                }
+                Data::Decimal(x) => {
+                    hasher.write(&[3]);
+                    hasher.write(&x.normalize().serialize());
+                }
+                Data::Name(x) => {
+                    hasher.write(&[4]);
+                    write_len_and_bytes(&mut hasher, x.as_bytes());
+                }
+                Data::String(x) => {
+                    hasher.write(&[5]);
+                    write_len_and_bytes(&mut hasher, x.as_bytes());
+                }
+                Data::StaticString(x) => {
+                    hasher.write(&[6]);
+                    write_len_and_bytes(&mut hasher, x.as_bytes());
+                }
+                Data::StackBytes(x) => {
+                    hasher.write(&[7]);
+                    hasher.write(x);
+                }
+                Data::Bytes(x) => {
+                    hasher.write(&[8]);
+                    write_len_and_bytes(&mut hasher, x);
+                }
+                Data::CodeList(x) => {
+                    hasher.write(&[9]);
+                    hasher.write(&(x.len() as u64).to_le_bytes());
+                }
+            }
+        }
+        hasher.finish()
+    }
+
+    /// Encodes the code in a compact binary format for high-volume storage. Items are written by opcode rather than by
+    /// instruction name, which makes the encoding smaller and faster to read than source text, but it also means that
+    /// it can only be decoded by a VM with exactly the same instruction set. The encoding starts with the VM's
+    /// `instruction_fingerprint` so that `decode_binary` can reject it everywhere else.
+    ///
+    /// Each item is its opcode, a tag for the kind of data and then the data, with numbers and lengths written as
+    /// variable-length integers. A `Data::StaticString` is written the same way as a `Data::String`, and is decoded as
+    /// one.
+    pub fn encode_binary<Vm: VirtualMachine>(&self, vm: &Vm) -> Vec<u8> {
+        let mut bytes = vm.engine().instruction_fingerprint().to_le_bytes().to_vec();
+        let mut pending = vec![self];
+        while let Some(item) = pending.pop() {
+            write_varint(&mut bytes, item.opcode as u64);
+            match &item.data {
+                Data::None => bytes.push(0),
+                Data::Integer(x) => {
+                    bytes.push(1);
+                    // Zig-zag encoding keeps small negative numbers small
+                    write_varint(&mut bytes, ((x << 1) ^ (x >> 63)) as u64);
+                }
+                Data::UnsignedInteger(x) => {
+                    bytes.push(2);
+                    write_varint(&mut bytes, *x);
+                }
+                Data::Decimal(x) => {
+                    bytes.push(3);
+                    bytes.extend_from_slice(&x.serialize());
+                }
+                Data::Name(x) => {
+                    bytes.push(4);
+                    write_varint_and_bytes(&mut bytes, x.as_bytes());
+                }
+                Data::String(x) => {
+                    bytes.push(5);
+                    write_varint_and_bytes(&mut bytes, x.as_bytes());
+                }
+                Data::StaticString(x) => {
+                    bytes.push(5);
+                    write_varint_and_bytes(&mut bytes, x.as_bytes());
+                }
+                Data::StackBytes(x) => {
+                    bytes.push(7);
+                    bytes.extend_from_slice(x);
+                }
+                Data::Bytes(x) => {
+                    bytes.push(8);
+                    write_varint_and_bytes(&mut bytes, x);
+                }
+                Data::CodeList(x) => {
+                    bytes.push(9);
+                    write_varint(&mut bytes, x.len() as u64);
+                    pending.extend(x.iter().rev());
+                }
             }
         }
+        bytes
+    }
+
+    /// Decodes code that was encoded by `encode_binary`. Returns an error if the code was encoded by a VM with a
+    /// different instruction set, if the bytes are not a complete encoding of one piece of code, or if the code would
+    /// break the VM's limits on size and nesting depth.
+    pub fn decode_binary<Vm: VirtualMachine>(vm: &Vm, bytes: &[u8]) -> Result<Code, ParseError> {
+        let fingerprint = vm.engine().instruction_fingerprint().to_le_bytes();
+        if bytes.len() < fingerprint.len() || bytes[..fingerprint.len()] != fingerprint {
+            return Err(ParseError::new_with_message("the code was encoded with an incompatible instruction set"));
+        }
+
+        let mut reader = BinaryReader { bytes: &bytes[fingerprint.len()..] };
+        let code = reader.read_code(vm.engine().get_configuration().get_max_nesting_depth())?;
+        if !reader.bytes.is_empty() {
+            return Err(ParseError::new_with_message("the code did not finish decoding"));
+        }
+        vm.engine()
+            .validate_code(&code)
+            .map_err(|opcode| ParseError::new_with_message(format!("the opcode {} is not an instruction", opcode)))?;
+        Ok(code)
+    }
+
+    /// Appends this item and every sub-list and atom in it to an already-existing discrepancy items HashMap
+    fn append_discrepancy_items(&self, items: &mut FnvHashMap<Code, i64>) {
+        for item in self.subtrees() {
+            *items.entry(item.clone()).or_insert(0) += 1;
+        }
     }
 
     /// Coerces the item to a list
@@ -132,58 +259,58 @@ impl Code {
 
     /// Returns the number of 'points' of the entire code. Each atom and list is considered one point.
     pub fn points(&self) -> i64 {
-        if self.is_list() {
-            let sub_points: i64 = self.data.code_iter().unwrap().map(|c| c.points()).sum();
-            1 + sub_points
-        } else {
-            1
+        self.subtrees().count() as i64
+    }
+
+    /// Returns how deeply lists are nested in this code. An atom has a depth of zero, a list of atoms a depth of one, a
+    /// list containing a list a depth of two, and so on. This does not recurse, so it is safe to call on code of any
+    /// depth.
+    pub fn depth(&self) -> usize {
+        let mut max_depth = 0;
+        let mut pending = vec![(self, 0)];
+        while let Some((code, depth)) = pending.pop() {
+            if let Data::CodeList(list) = code.get_data() {
+                max_depth = max_depth.max(depth + 1);
+                pending.extend(list.iter().map(|item| (item, depth + 1)));
+            }
         }
+
+        max_depth
     }
 
     /// Returns the item of code at the specified 'point' in the code tree if `point` is less than the number of points
     /// in the code. Returns the number of points used otherwise.
     pub fn extract_point(&self, point: i64) -> Extraction {
-        if 0 == point {
-            return Extraction::Extracted(self.clone());
-        }
-        let mut used = 1;
-        if self.is_list() {
-            for item in self.data.code_iter().unwrap() {
-                match item.extract_point(point - used) {
-                    Extraction::Extracted(code) => return Extraction::Extracted(code),
-                    Extraction::Used(u) => used += u,
-                }
-            }
+        let found = if point < 0 { None } else { self.subtrees().nth(point as usize) };
+        match found {
+            Some(code) => Extraction::Extracted(code.clone()),
+            None => Extraction::Used(self.points()),
         }
-        Extraction::Used(used)
     }
 
     /// Descends to the specified point in the code tree and swaps the list or atom there with the specified replacement
-    /// code. If the replacement point is greater than the number of points in the Code, this has no effect.
-    pub fn replace_point(&self, mut point: i64, replace_with: &Code) -> Result<(Code, i64), ExecutionError> {
-        // If this is the replacement point, return the replacement
-        if 0 == point {
-            Ok((replace_with.clone(), 1))
-        } else if self.is_atom() || point < 1 {
-            // If this is an atom or we've performed the replacement, everything gets returned as-is
-            Ok((self.clone(), 1))
-        } else {
-            // We need to track both the number of points used and the points remaining until replacement.
-            let mut next_list = vec![];
-            let mut total_used = 1;
-            point -= 1;
-            for item in self.data.code_iter().unwrap() {
-                let (next, used) = item.replace_point(point, replace_with)?;
-                point -= used;
-                total_used += used;
-                next_list.push(next);
-
-                if total_used > MAX_POINTS_IN_CODE {
-                    return Err(ExecutionError::OutOfMemory);
-                }
-            }
-            Ok((Code::new(0, Data::CodeList(next_list)), total_used))
+    /// code. If the replacement point is greater than the number of points in the Code, this has no effect. Returns the
+    /// new code and the number of points in it, or OutOfMemory if the new code would have too many points.
+    pub fn replace_point(&self, point: i64, replace_with: &Code) -> Result<(Code, i64), ExecutionError> {
+        let replaced = if point < 0 { None } else { self.subtrees().nth(point as usize) };
+        let total_points = match replaced {
+            Some(replaced) => self.points() - replaced.points() + replace_with.points(),
+            None => self.points(),
+        };
+        if total_points > MAX_POINTS_IN_CODE {
+            return Err(ExecutionError::OutOfMemory);
         }
+
+        let mut next_point = 0;
+        let code = self.rebuild(|_| {
+            next_point += 1;
+            if next_point - 1 == point {
+                Some(replace_with.clone())
+            } else {
+                None
+            }
+        });
+        Ok((code, total_points))
     }
 
     /// Similar to `extract_point` but does not recurse into lists
@@ -204,6 +331,20 @@ impl Code {
         }
     }
 
+    /// Similar to `position_of`, but returns the index of every top-level item that matches rather than just the first.
+    /// An atom is treated as a list of one item.
+    pub fn positions_of(&self, look_for: &Code) -> Vec<usize> {
+        if self.is_atom() {
+            if self == look_for {
+                vec![0]
+            } else {
+                vec![]
+            }
+        } else {
+            self.data.code_iter().unwrap().enumerate().filter(|(_, item)| *item == look_for).map(|(i, _)| i).collect()
+        }
+    }
+
     /// Returns a list of all names found in the instruction.
     pub fn extract_names(&self) -> Vec<Name> {
         let mut names = vec![];
@@ -212,15 +353,58 @@ impl Code {
     }
 
     fn append_names(&self, names: &mut Vec<Name>) {
-        match self.get_data() {
-            Data::Name(name) => names.push(name.clone()),
-            Data::CodeList(list) => {
-                for item in list.iter() {
-                    item.append_names(names);
+        for item in self.subtrees() {
+            if let Data::Name(name) = item.get_data() {
+                names.push(name.clone());
+            }
+        }
+    }
+
+    /// Returns a copy of the code with every name consistently renamed to a canonical name. The first name to appear is
+    /// renamed N0, the second distinct name N1, and so on. Two programs that differ only by a consistent renaming of
+    /// their names normalize to equal code, which is useful for removing duplicates and measuring diversity.
+    ///
+    /// Every name is treated as local to the code, so this should only be used to compare programs that define all of
+    /// the names they use. Names that refer to an Individual's defined names are renamed too, but the definitions are
+    /// not, so the normalized code should not be run with those definitions.
+    pub fn alpha_normalize(&self) -> Code {
+        let mut renamed: FnvHashMap<Name, Name> = FnvHashMap::default();
+        self.rebuild(|item| match item.get_data() {
+            Data::Name(name) => {
+                let next = renamed.len();
+                let canonical = renamed.entry(name.clone()).or_insert_with(|| format!("N{}", next).into());
+                Some(Code::new(item.opcode, Data::Name(canonical.clone())))
+            }
+            _ => None,
+        })
+    }
+
+    /// Returns true if the code has the same structure and data as the other code, except that float literals only need
+    /// to be within `float_eps` of each other. Code that is `==` is always `approx_eq`, but float-heavy programs that
+    /// differ only by tiny amounts can also be treated as the same when removing duplicates or measuring diversity.
+    pub fn approx_eq(&self, other: &Code, float_eps: Decimal) -> bool {
+        let mut pending = vec![(self, other)];
+        while let Some((a, b)) = pending.pop() {
+            if a.opcode != b.opcode {
+                return false;
+            }
+            let equal = match (a.get_data(), b.get_data()) {
+                // The difference is only too large to represent when the floats are far apart
+                (Data::Decimal(a), Data::Decimal(b)) => {
+                    a.checked_sub(*b).map(|d| d.abs() <= float_eps).unwrap_or(false)
                 }
+                (Data::CodeList(a), Data::CodeList(b)) => {
+                    pending.extend(a.iter().zip(b.iter()));
+                    a.len() == b.len()
+                }
+                (a, b) => a == b,
+            };
+            if !equal {
+                return false;
             }
-            _ => {}
         }
+
+        true
     }
 
     /// Returns a list of clones of all the atoms found in the instruction.
@@ -232,13 +416,7 @@ impl Code {
     }
 
     fn append_atoms(&self, atoms: &mut Vec<Code>) {
-        if self.is_atom() {
-            atoms.push(self.clone());
-        } else {
-            for item in self.data.code_iter().unwrap() {
-                item.append_atoms(atoms);
-            }
-        }
+        atoms.extend(self.subtrees().filter(|item| item.is_atom()).cloned());
     }
 
     /// Returns the number of items in this list. Unlike 'points' it does not recurse into sub-lists
@@ -258,20 +436,152 @@ impl Code {
     }
 
     fn inner_replace(&self, look_for: &Code, replace_with: &Code) -> Code {
-        if self == look_for {
-            replace_with.clone()
-        } else if self.is_atom() {
-            self.clone()
-        } else {
-            let mut next_list = vec![];
-            for item in self.data.code_iter().unwrap() {
-                next_list.push(item.inner_replace(look_for, replace_with));
+        self.rebuild(|item| if item == look_for { Some(replace_with.clone()) } else { None })
+    }
+
+    // Copies the code, visiting every item in the same depth-first order as `subtrees`. When `replace` returns some
+    // code for an item, that code is used in place of the item and the item's own children are not visited. This does
+    // not recurse, so it is safe to call on code of any depth.
+    fn rebuild<F: FnMut(&Code) -> Option<Code>>(&self, mut replace: F) -> Code {
+        if let Some(code) = replace(self) {
+            return code;
+        }
+        if !self.is_list() {
+            return self.clone();
+        }
+
+        // Each pending list holds the list being copied, the index of its next item and the items copied so far
+        let mut pending: Vec<(&Code, usize, Vec<Code>)> = vec![(self, 0, vec![])];
+        loop {
+            let (list, index, copied) = pending.last_mut().unwrap();
+            match list.data.code_iter().unwrap().nth(*index) {
+                Some(item) => {
+                    *index += 1;
+                    if let Some(code) = replace(item) {
+                        copied.push(code);
+                    } else if item.is_list() {
+                        pending.push((item, 0, Vec::with_capacity(item.len())));
+                    } else {
+                        copied.push(item.clone());
+                    }
+                }
+                None => {
+                    let (list, _, copied) = pending.pop().unwrap();
+                    let code = Code::new(list.opcode, Data::CodeList(copied));
+                    match pending.last_mut() {
+                        Some((_, _, parent)) => parent.push(code),
+                        None => return code,
+                    }
+                }
             }
-            Code::new(0, Data::CodeList(next_list))
         }
     }
 }
 
+// Iterates the sub-trees of a piece of code in depth-first order. The pending items are stored in reverse order so that
+// the next item to visit is always on the end of the Vec.
+struct Subtrees<'a> {
+    pending: Vec<&'a Code>,
+}
+
+impl<'a> Iterator for Subtrees<'a> {
+    type Item = &'a Code;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let next = self.pending.pop()?;
+        if let Some(iter) = next.data.code_iter() {
+            self.pending.extend(iter.rev());
+        }
+        Some(next)
+    }
+}
+
+// Writes the number in as few bytes as possible, seven bits at a time with the high bit set on all but the last byte
+fn write_varint(bytes: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        bytes.push((value as u8) | 0x80);
+        value >>= 7;
+    }
+    bytes.push(value as u8);
+}
+
+fn write_varint_and_bytes(bytes: &mut Vec<u8>, data: &[u8]) {
+    write_varint(bytes, data.len() as u64);
+    bytes.extend_from_slice(data);
+}
+
+// Reads the binary encoding written by `Code::encode_binary`, consuming the bytes as it goes
+struct BinaryReader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> BinaryReader<'a> {
+    fn read_code(&mut self, depth_remaining: usize) -> Result<Code, ParseError> {
+        let opcode = Opcode::try_from(self.read_varint()?)
+            .map_err(|_| ParseError::new_with_message("the opcode is out of range"))?;
+        let data = match self.read_bytes(1)?[0] {
+            0 => Data::None,
+            1 => {
+                let zigzag = self.read_varint()?;
+                Data::Integer((zigzag >> 1) as i64 ^ -((zigzag & 1) as i64))
+            }
+            2 => Data::UnsignedInteger(self.read_varint()?),
+            3 => Data::Decimal(Decimal::deserialize(self.read_bytes(16)?.try_into().unwrap())),
+            4 => Data::Name(self.read_string()?.into()),
+            5 => Data::String(self.read_string()?.into()),
+            7 => Data::StackBytes(self.read_bytes(30)?.try_into().unwrap()),
+            8 => {
+                let len = self.read_len()?;
+                Data::Bytes(self.read_bytes(len)?.to_vec())
+            }
+            9 => {
+                if depth_remaining == 0 {
+                    return Err(ParseError::new_from_execution_error(ExecutionError::MaxDepthExceeded));
+                }
+                let len = self.read_len()?;
+                let mut items = vec![];
+                for _ in 0..len {
+                    items.push(self.read_code(depth_remaining - 1)?);
+                }
+                return Code::new_list(items).map_err(ParseError::new_from_execution_error);
+            }
+            tag => return Err(ParseError::new_with_message(format!("the data tag {} is not valid", tag))),
+        };
+        Ok(Code::new(opcode, data))
+    }
+
+    fn read_varint(&mut self) -> Result<u64, ParseError> {
+        let mut value: u64 = 0;
+        for shift in (0..64).step_by(7) {
+            let byte = self.read_bytes(1)?[0];
+            value |= ((byte & 0x7f) as u64) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err(ParseError::new_with_message("a number is too large"))
+    }
+
+    fn read_len(&mut self) -> Result<usize, ParseError> {
+        usize::try_from(self.read_varint()?).map_err(|_| ParseError::new_with_message("a length is too large"))
+    }
+
+    fn read_string(&mut self) -> Result<String, ParseError> {
+        let len = self.read_len()?;
+        String::from_utf8(self.read_bytes(len)?.to_vec())
+            .map_err(|_| ParseError::new_with_message("a string is not valid UTF-8"))
+    }
+
+    fn read_bytes(&mut self, len: usize) -> Result<&'a [u8], ParseError> {
+        if self.bytes.len() < len {
+            return Err(ParseError::new_with_message("the code ended unexpectedly"));
+        }
+        let (read, rest) = self.bytes.split_at(len);
+        self.bytes = rest;
+        Ok(read)
+    }
+}
+
 pub struct CodeWithVirtualMachine<'a, Vm: VirtualMachine> {
     code: &'a Code,
     vm: &'a Vm,
@@ -306,8 +616,12 @@ pub fn add_base_instructions<
     vm.engine_mut().add_instruction::<crate::execute_bool::BoolDup>();
     vm.engine_mut().add_instruction::<crate::execute_bool::BoolEqual>();
     vm.engine_mut().add_instruction::<crate::execute_bool::BoolFlush>();
+    vm.engine_mut().add_instruction::<crate::execute_bool::BoolFromCode>();
     vm.engine_mut().add_instruction::<crate::execute_bool::BoolFromFloat>();
     vm.engine_mut().add_instruction::<crate::execute_bool::BoolFromInt>();
+    vm.engine_mut().add_instruction::<crate::execute_bool::BoolImplies>();
+    vm.engine_mut().add_instruction::<crate::execute_bool::BoolIsEmpty>();
+    vm.engine_mut().add_instruction::<crate::execute_bool::BoolNand>();
     vm.engine_mut().add_instruction::<crate::execute_bool::BoolNot>();
     vm.engine_mut().add_instruction::<crate::execute_bool::BoolOr>();
     vm.engine_mut().add_instruction::<crate::execute_bool::BoolPop>();
@@ -316,6 +630,7 @@ pub fn add_base_instructions<
     vm.engine_mut().add_instruction::<crate::execute_bool::BoolShove>();
     vm.engine_mut().add_instruction::<crate::execute_bool::BoolStackDepth>();
     vm.engine_mut().add_instruction::<crate::execute_bool::BoolSwap>();
+    vm.engine_mut().add_instruction::<crate::execute_bool::BoolXor>();
     vm.engine_mut().add_instruction::<crate::execute_bool::BoolYankDup>();
     vm.engine_mut().add_instruction::<crate::execute_bool::BoolYank>();
     vm.engine_mut().add_instruction::<crate::execute_code::CodeAppend>();
@@ -325,6 +640,7 @@ pub fn add_base_instructions<
     vm.engine_mut().add_instruction::<crate::execute_code::CodeCons>();
     vm.engine_mut().add_instruction::<crate::execute_code::CodeContainer>();
     vm.engine_mut().add_instruction::<crate::execute_code::CodeContains>();
+    vm.engine_mut().add_instruction::<crate::execute_code::CodeCount>();
     vm.engine_mut().add_instruction::<crate::execute_code::CodeDefine>();
     vm.engine_mut().add_instruction::<crate::execute_code::CodeDefinition>();
     vm.engine_mut().add_instruction::<crate::execute_code::CodeDiscrepancy>();
@@ -333,16 +649,21 @@ pub fn add_base_instructions<
     vm.engine_mut().add_instruction::<crate::execute_code::CodeDoNTimes>();
     vm.engine_mut().add_instruction::<crate::execute_code::CodeDoN>();
     vm.engine_mut().add_instruction::<crate::execute_code::CodeDo>();
+    vm.engine_mut().add_instruction::<crate::execute_code::CodeDropWhile>();
     vm.engine_mut().add_instruction::<crate::execute_code::CodeDup>();
     vm.engine_mut().add_instruction::<crate::execute_code::CodeEqual>();
     vm.engine_mut().add_instruction::<crate::execute_code::CodeExtract>();
+    vm.engine_mut().add_instruction::<crate::execute_code::CodeFilterByStack>();
     vm.engine_mut().add_instruction::<crate::execute_code::CodeFlush>();
     vm.engine_mut().add_instruction::<crate::execute_code::CodeFromBoolean>();
     vm.engine_mut().add_instruction::<crate::execute_code::CodeFromFloat>();
     vm.engine_mut().add_instruction::<crate::execute_code::CodeFromInteger>();
     vm.engine_mut().add_instruction::<crate::execute_code::CodeFromName>();
+    vm.engine_mut().add_instruction::<crate::execute_code::CodeHash>();
     vm.engine_mut().add_instruction::<crate::execute_code::CodeIf>();
+    vm.engine_mut().add_instruction::<crate::execute_code::CodeIfEmpty>();
     vm.engine_mut().add_instruction::<crate::execute_code::CodeInsert>();
+    vm.engine_mut().add_instruction::<crate::execute_code::CodeIsEmpty>();
     vm.engine_mut().add_instruction::<crate::execute_code::CodeLength>();
     vm.engine_mut().add_instruction::<crate::execute_code::CodeList>();
     vm.engine_mut().add_instruction::<crate::execute_code::CodeMember>();
@@ -352,14 +673,19 @@ pub fn add_base_instructions<
     vm.engine_mut().add_instruction::<crate::execute_code::CodeNull>();
     vm.engine_mut().add_instruction::<crate::execute_code::CodePop>();
     vm.engine_mut().add_instruction::<crate::execute_code::CodePosition>();
+    vm.engine_mut().add_instruction::<crate::execute_code::CodePositionAll>();
     vm.engine_mut().add_instruction::<crate::execute_code::CodeQuote>();
     vm.engine_mut().add_instruction::<crate::execute_code::CodeRand>();
+    vm.engine_mut().add_instruction::<crate::execute_code::CodeReplaceAtPoint>();
     vm.engine_mut().add_instruction::<crate::execute_code::CodeRot>();
     vm.engine_mut().add_instruction::<crate::execute_code::CodeShove>();
     vm.engine_mut().add_instruction::<crate::execute_code::CodeSize>();
     vm.engine_mut().add_instruction::<crate::execute_code::CodeStackDepth>();
+    vm.engine_mut().add_instruction::<crate::execute_code::CodeSublist>();
     vm.engine_mut().add_instruction::<crate::execute_code::CodeSubstitute>();
     vm.engine_mut().add_instruction::<crate::execute_code::CodeSwap>();
+    vm.engine_mut().add_instruction::<crate::execute_code::CodeTakeWhile>();
+    vm.engine_mut().add_instruction::<crate::execute_code::CodeTranspose>();
     vm.engine_mut().add_instruction::<crate::execute_code::CodeYankDup>();
     vm.engine_mut().add_instruction::<crate::execute_code::CodeYank>();
     vm.engine_mut().add_instruction::<crate::execute_exec::ExecDefine>();
@@ -367,29 +693,37 @@ pub fn add_base_instructions<
     vm.engine_mut().add_instruction::<crate::execute_exec::ExecDoNRange>();
     vm.engine_mut().add_instruction::<crate::execute_exec::ExecDoNTimes>();
     vm.engine_mut().add_instruction::<crate::execute_exec::ExecDup>();
+    vm.engine_mut().add_instruction::<crate::execute_exec::ExecDupItems>();
     vm.engine_mut().add_instruction::<crate::execute_exec::ExecEqual>();
     vm.engine_mut().add_instruction::<crate::execute_exec::ExecFlush>();
+    vm.engine_mut().add_instruction::<crate::execute_exec::ExecFlushIf>();
     vm.engine_mut().add_instruction::<crate::execute_exec::ExecIf>();
     vm.engine_mut().add_instruction::<crate::execute_exec::ExecK>();
     vm.engine_mut().add_instruction::<crate::execute_exec::ExecPop>();
+    vm.engine_mut().add_instruction::<crate::execute_exec::ExecRand>();
     vm.engine_mut().add_instruction::<crate::execute_exec::ExecRot>();
     vm.engine_mut().add_instruction::<crate::execute_exec::ExecShove>();
     vm.engine_mut().add_instruction::<crate::execute_exec::ExecStackDepth>();
+    vm.engine_mut().add_instruction::<crate::execute_exec::ExecStepsRemaining>();
     vm.engine_mut().add_instruction::<crate::execute_exec::ExecSwap>();
     vm.engine_mut().add_instruction::<crate::execute_exec::ExecS>();
     vm.engine_mut().add_instruction::<crate::execute_exec::ExecYankDup>();
     vm.engine_mut().add_instruction::<crate::execute_exec::ExecYank>();
     vm.engine_mut().add_instruction::<crate::execute_exec::ExecY>();
+    vm.engine_mut().add_instruction::<crate::execute_float::FloatAbs>();
     vm.engine_mut().add_instruction::<crate::execute_float::FloatCos>();
     vm.engine_mut().add_instruction::<crate::execute_float::FloatDefine>();
     vm.engine_mut().add_instruction::<crate::execute_float::FloatDifference>();
     vm.engine_mut().add_instruction::<crate::execute_float::FloatDup>();
     vm.engine_mut().add_instruction::<crate::execute_float::FloatEqual>();
+    vm.engine_mut().add_instruction::<crate::execute_float::FloatExp>();
     vm.engine_mut().add_instruction::<crate::execute_float::FloatFlush>();
     vm.engine_mut().add_instruction::<crate::execute_float::FloatFromBoolean>();
     vm.engine_mut().add_instruction::<crate::execute_float::FloatFromInteger>();
     vm.engine_mut().add_instruction::<crate::execute_float::FloatGreater>();
+    vm.engine_mut().add_instruction::<crate::execute_float::FloatIsEmpty>();
     vm.engine_mut().add_instruction::<crate::execute_float::FloatLess>();
+    vm.engine_mut().add_instruction::<crate::execute_float::FloatLog>();
     vm.engine_mut().add_instruction::<crate::execute_float::FloatMax>();
     vm.engine_mut().add_instruction::<crate::execute_float::FloatMin>();
     vm.engine_mut().add_instruction::<crate::execute_float::FloatModulo>();
@@ -399,13 +733,18 @@ pub fn add_base_instructions<
     vm.engine_mut().add_instruction::<crate::execute_float::FloatRand>();
     vm.engine_mut().add_instruction::<crate::execute_float::FloatRot>();
     vm.engine_mut().add_instruction::<crate::execute_float::FloatShove>();
+    vm.engine_mut().add_instruction::<crate::execute_float::FloatSign>();
     vm.engine_mut().add_instruction::<crate::execute_float::FloatSin>();
+    vm.engine_mut().add_instruction::<crate::execute_float::FloatSqrt>();
     vm.engine_mut().add_instruction::<crate::execute_float::FloatStackDepth>();
     vm.engine_mut().add_instruction::<crate::execute_float::FloatSum>();
     vm.engine_mut().add_instruction::<crate::execute_float::FloatSwap>();
     vm.engine_mut().add_instruction::<crate::execute_float::FloatTan>();
     vm.engine_mut().add_instruction::<crate::execute_float::FloatYankDup>();
     vm.engine_mut().add_instruction::<crate::execute_float::FloatYank>();
+    vm.engine_mut().add_instruction::<crate::execute_integer::IntegerAbs>();
+    vm.engine_mut().add_instruction::<crate::execute_integer::IntegerAllStackDepths>();
+    vm.engine_mut().add_instruction::<crate::execute_integer::IntegerContextId>();
     vm.engine_mut().add_instruction::<crate::execute_integer::IntegerDefine>();
     vm.engine_mut().add_instruction::<crate::execute_integer::IntegerDifference>();
     vm.engine_mut().add_instruction::<crate::execute_integer::IntegerDup>();
@@ -414,11 +753,14 @@ pub fn add_base_instructions<
     vm.engine_mut().add_instruction::<crate::execute_integer::IntegerFromBoolean>();
     vm.engine_mut().add_instruction::<crate::execute_integer::IntegerFromFloat>();
     vm.engine_mut().add_instruction::<crate::execute_integer::IntegerGreater>();
+    vm.engine_mut().add_instruction::<crate::execute_integer::IntegerIsEmpty>();
     vm.engine_mut().add_instruction::<crate::execute_integer::IntegerLess>();
     vm.engine_mut().add_instruction::<crate::execute_integer::IntegerMax>();
     vm.engine_mut().add_instruction::<crate::execute_integer::IntegerMin>();
     vm.engine_mut().add_instruction::<crate::execute_integer::IntegerModulo>();
+    vm.engine_mut().add_instruction::<crate::execute_integer::IntegerNegate>();
     vm.engine_mut().add_instruction::<crate::execute_integer::IntegerPop>();
+    vm.engine_mut().add_instruction::<crate::execute_integer::IntegerPow>();
     vm.engine_mut().add_instruction::<crate::execute_integer::IntegerProduct>();
     vm.engine_mut().add_instruction::<crate::execute_integer::IntegerQuotient>();
     vm.engine_mut().add_instruction::<crate::execute_integer::IntegerRand>();
@@ -426,12 +768,15 @@ pub fn add_base_instructions<
     vm.engine_mut().add_instruction::<crate::execute_integer::IntegerShove>();
     vm.engine_mut().add_instruction::<crate::execute_integer::IntegerStackDepth>();
     vm.engine_mut().add_instruction::<crate::execute_integer::IntegerSum>();
+    vm.engine_mut().add_instruction::<crate::execute_integer::IntegerSumAll>();
     vm.engine_mut().add_instruction::<crate::execute_integer::IntegerSwap>();
     vm.engine_mut().add_instruction::<crate::execute_integer::IntegerYankDup>();
     vm.engine_mut().add_instruction::<crate::execute_integer::IntegerYank>();
+    vm.engine_mut().add_instruction::<crate::execute_name::NameAll>();
     vm.engine_mut().add_instruction::<crate::execute_name::NameDup>();
     vm.engine_mut().add_instruction::<crate::execute_name::NameEqual>();
     vm.engine_mut().add_instruction::<crate::execute_name::NameFlush>();
+    vm.engine_mut().add_instruction::<crate::execute_name::NameIsEmpty>();
     vm.engine_mut().add_instruction::<crate::execute_name::NamePop>();
     vm.engine_mut().add_instruction::<crate::execute_name::NameQuote>();
     vm.engine_mut().add_instruction::<crate::execute_name::NameRandBoundName>();
@@ -467,6 +812,7 @@ pub fn add_base_literals<
 mod tests {
     use super::Extraction;
     use crate::*;
+    use rust_decimal::Decimal;
 
     fn new_base_vm() -> BaseVm {
         let mut vm = BaseVm::new(None, Configuration::new_simple());
@@ -506,6 +852,24 @@ mod tests {
         assert_eq!("( ( TRUE 0.012345 -12784 a_name ) BOOL.AND )", format!("{}", code.for_display(&vm)));
     }
 
+    #[test]
+    fn code_display_float_precision() {
+        let mut config = Configuration::new_simple();
+        config.set_float_display_precision(Some(3));
+        let mut vm = BaseVm::new(None, config);
+        add_base_instructions(&mut vm);
+        add_base_literals(&mut vm);
+
+        let code = vm.engine().must_parse("( 0.841470984807897 2.0001 )");
+        assert_eq!("( 0.841 2.0 )", format!("{}", code.for_display(&vm)));
+
+        // The stored value is unchanged, but the displayed text parses to the rounded value
+        assert_eq!(code, new_base_vm().engine().must_parse("( 0.841470984807897 2.0001 )"));
+        let reparsed = vm.engine().must_parse(&format!("{}", code.for_display(&vm)));
+        assert_eq!(reparsed, vm.engine().must_parse("( 0.841 2.0 )"));
+        assert_ne!(reparsed, code);
+    }
+
     #[test]
     fn code_points() {
         let vm = new_base_vm();
@@ -524,6 +888,46 @@ mod tests {
         assert_eq!(code.extract_point(3), Extraction::Extracted(vm.engine().must_parse("B")));
     }
 
+    #[test]
+    fn deep_code_built_at_runtime() {
+        let mut vm = new_base_vm();
+
+        // Each pass wraps the top CODE item in another list. Code built at runtime is only limited by its points, so it
+        // can nest far more deeply than parsed or generated code.
+        vm.engine_mut().parse_and_set_code("( CODE.QUOTE A 450 EXEC.DONTIMES ( CODE.QUOTE 1 CODE.LIST ) )").unwrap();
+        vm.run(10_000);
+        let deep = vm.code().pop().unwrap();
+        assert_eq!(450, deep.depth());
+        assert_eq!(901, deep.points());
+        let one = vm.engine().must_parse("1");
+        let name = vm.engine().must_parse("A");
+
+        // None of the walkers recurse, so they work on a thread with a stack far too small for one frame per list
+        let (replaced, normalized, substituted) = std::thread::scope(|scope| {
+            std::thread::Builder::new()
+                .stack_size(64 * 1024)
+                .spawn_scoped(scope, || {
+                    assert!(deep.contains(&name));
+                    assert_eq!(Some(2), deep.container(&name).map(|list| list.len()));
+                    assert_eq!(Extraction::Extracted(one.clone()), deep.extract_point(900));
+                    assert_eq!(vec![Name::from("A")], deep.extract_names());
+                    assert_eq!(451, deep.extract_atoms().len());
+                    assert!(deep.approx_eq(&deep, Decimal::ZERO));
+                    (
+                        deep.replace_point(900, &name).unwrap().0,
+                        deep.alpha_normalize(),
+                        deep.replace(&one, &name).unwrap(),
+                    )
+                })
+                .unwrap()
+                .join()
+                .unwrap()
+        });
+        assert_eq!(450, replaced.depth());
+        assert_eq!(450, normalized.depth());
+        assert_eq!(451, substituted.extract_atoms().iter().filter(|atom| **atom == name).count());
+    }
+
     #[test]
     fn replace_point() {
         let vm = new_base_vm();
@@ -577,6 +981,31 @@ mod tests {
         assert_eq!(6, items.len());
     }
 
+    #[test]
+    fn code_subtrees() {
+        let vm = new_base_vm();
+        let code = vm.engine().must_parse("( ANAME ( 3 ( 1 ) ) 1 )");
+        let subtrees: Vec<&Code> = code.subtrees().collect();
+        assert_eq!(7, subtrees.len());
+        assert_eq!(&code, subtrees[0]);
+        assert_eq!(&vm.engine().must_parse("ANAME"), subtrees[1]);
+        assert_eq!(&vm.engine().must_parse("( 3 ( 1 ) )"), subtrees[2]);
+        assert_eq!(&vm.engine().must_parse("3"), subtrees[3]);
+        assert_eq!(&vm.engine().must_parse("( 1 )"), subtrees[4]);
+        assert_eq!(&vm.engine().must_parse("1"), subtrees[5]);
+        assert_eq!(&vm.engine().must_parse("1"), subtrees[6]);
+        assert_eq!(code.points() as usize, subtrees.len());
+    }
+
+    #[test]
+    fn code_depth() {
+        let vm = new_base_vm();
+        assert_eq!(0, vm.engine().must_parse("1").depth());
+        assert_eq!(1, vm.engine().must_parse("( )").depth());
+        assert_eq!(1, vm.engine().must_parse("( 1 2 )").depth());
+        assert_eq!(3, vm.engine().must_parse("( ANAME ( 3 ( 1 ) ) ( 1 ) )").depth());
+    }
+
     #[test]
     fn code_len() {
         let vm = new_base_vm();
@@ -633,4 +1062,89 @@ mod tests {
                 .unwrap()
         );
     }
+
+    #[test]
+    fn code_stable_hash() {
+        // The hash is calculated from the opcodes and data alone, so it can be checked against a known value
+        let code =
+            Code::new(0, Data::CodeList(vec![Code::new(3, Data::Integer(7)), Code::new(5, Data::Name("A".into()))]));
+        assert_eq!(2194217682260904762, code.stable_hash());
+
+        let vm = new_base_vm();
+        let parse = |src| vm.engine().must_parse(src);
+        assert_eq!(parse("( A ( 1 2.0 ) TRUE )").stable_hash(), parse("( A ( 1 2.0 ) TRUE )").stable_hash());
+        assert_eq!(parse("1.5").stable_hash(), parse("1.50").stable_hash());
+        assert_ne!(parse("( A ( 1 2.0 ) TRUE )").stable_hash(), parse("( A ( 1 2.0 TRUE ) )").stable_hash());
+        assert_ne!(parse("( ( ) )").stable_hash(), parse("( ( ) ( ) )").stable_hash());
+        assert_ne!(parse("1").stable_hash(), parse("2").stable_hash());
+    }
+
+    #[test]
+    fn code_alpha_normalize() {
+        let vm = new_base_vm();
+        let parse = |src| vm.engine().must_parse(src);
+        assert_eq!(
+            parse("( A CODE.QUOTE 1 CODE.DEFINE A )").alpha_normalize(),
+            parse("( B CODE.QUOTE 1 CODE.DEFINE B )").alpha_normalize()
+        );
+        assert_eq!(parse("( N0 ( N1 N0 ) )"), parse("( X ( Y X ) )").alpha_normalize());
+        assert_eq!(parse("( N0 ( N1 N0 ) )"), parse("( N1 ( N0 N1 ) )").alpha_normalize());
+
+        // The renaming must be consistent, so using one name twice is not the same as using two names
+        assert_ne!(parse("( A ( B A ) )").alpha_normalize(), parse("( A ( B B ) )").alpha_normalize());
+    }
+
+    #[test]
+    fn binary_round_trip() {
+        let vm = new_base_vm();
+        let src = "( TRUE -3 9000000000 ( 1.25 -0.000001 ( a_name ) ) ( ) INTEGER.SUM CODE.QUOTE ( b_name EXEC.IF ) )";
+        let code = vm.engine().must_parse(src);
+        let bytes = code.encode_binary(&vm);
+        assert_eq!(code, Code::decode_binary(&vm, &bytes).unwrap());
+
+        // Anything other than one complete piece of code is rejected
+        assert!(Code::decode_binary(&vm, &bytes[..bytes.len() - 1]).is_err());
+        let mut extra = bytes.clone();
+        extra.push(0);
+        assert!(Code::decode_binary(&vm, &extra).is_err());
+    }
+
+    #[test]
+    fn binary_fingerprint_mismatch() {
+        let vm = new_base_vm();
+        let code = vm.engine().must_parse("( 1 2 INTEGER.SUM )");
+        let bytes = code.encode_binary(&vm);
+
+        let mut other = BaseVm::new(None, Configuration::new_simple());
+        add_base_literals(&mut other);
+        assert!(Code::decode_binary(&other, &bytes).is_err());
+
+        let mut corrupt = bytes.clone();
+        corrupt[0] ^= 1;
+        assert!(Code::decode_binary(&vm, &corrupt).is_err());
+    }
+
+    #[test]
+    fn code_approx_eq() {
+        let vm = new_base_vm();
+        let parse = |src| vm.engine().must_parse(src);
+        let eps = Decimal::new(1, 6);
+        let a = parse("( 1 ( 1.0 FLOAT.SUM ) A )");
+        let b = parse("( 1 ( 1.0000000001 FLOAT.SUM ) A )");
+        assert_ne!(a, b);
+        assert!(a.approx_eq(&b, eps));
+        assert!(a.approx_eq(&a, Decimal::ZERO));
+
+        // Only the floats are compared with the tolerance
+        assert!(!a.approx_eq(&parse("( 1 ( 1.1 FLOAT.SUM ) A )"), eps));
+        assert!(!a.approx_eq(&parse("( 2 ( 1.0 FLOAT.SUM ) A )"), eps));
+        assert!(!a.approx_eq(&parse("( 1 ( 1.0 FLOAT.SUM ) )"), eps));
+        assert!(!parse("1").approx_eq(&parse("1.0"), eps));
+
+        // Floats too far apart for their difference to be represented are not equal
+        let max = parse("79000000000000000000000000000.0");
+        let min = parse("-79000000000000000000000000000.0");
+        assert!(!max.approx_eq(&min, eps));
+        assert!(max.approx_eq(&max, eps));
+    }
 }
@@ -1,7 +1,8 @@
-use crate::GeneticOperation;
+use crate::{Code, DeepIndexPolicy, ExpansionCost, GeneticOperation, NameInheritance, RandomNameEncoding};
 use fnv::FnvHashMap;
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Configuration {
     // A random program running long enough can use more memory than the real hardware has. The virtual machine will
     // stop processing a program when it exceeds this number.
@@ -14,7 +15,70 @@ pub struct Configuration {
 
     defined_name_weight: u8,
 
+    // The most weight that all of the defined names together are given when picking a random atom. Without a cap the
+    // chance of picking a defined name grows with every definition until names dominate the random code. Defaults to
+    // None, which does not cap the weight.
+    defined_name_weight_cap: Option<usize>,
+
+    #[cfg_attr(feature = "serde", serde(deserialize_with = "deserialize_instruction_weights"))]
     instruction_weights: FnvHashMap<&'static str, u8>,
+
+    // How many steps are counted when a list on the Exec stack is expanded. Defaults to a flat cost of one step.
+    list_expansion_cost: ExpansionCost,
+
+    // How SHOVE, YANK and YANKDUP treat an index that is larger than the stack. Defaults to wrapping the index around.
+    deep_index_policy: DeepIndexPolicy,
+
+    // Which of the parents' defined names are copied to a child. Defaults to the names referenced by either parent,
+    // with the left parent taking priority.
+    name_inheritance: NameInheritance,
+
+    // Random names are made from this prefix followed by random bits in the specified encoding. The prefix should not
+    // be empty, otherwise a random name could look like a literal of some other type. Defaults to "RND." and base64.
+    random_name_prefix: String,
+    random_name_encoding: RandomNameEncoding,
+
+    // The chance (0.0 to 1.0) that NAME.RAND pushes one of the names that already has a definition instead of a newly
+    // generated name. Reusing names helps evolve modular programs. Defaults to 0.0, which always generates a new name.
+    name_rand_reuse_probability: f64,
+
+    // A name is parsed from all of the characters up to the next white-space, so malformed input could produce names of
+    // any length. Code containing a name longer than this is rejected by the parser rather than truncated, so that a
+    // name never silently changes meaning. Defaults to 1024.
+    max_name_length: usize,
+
+    // The deepest that lists may be nested inside one another. Deeply nested code is expensive to process with the
+    // recursive Code operations, so the parser rejects it and genetic operations will not produce it. Defaults to 100.
+    max_nesting_depth: usize,
+
+    // The most names that a program may define while it runs. Once this many names are defined, attempts to define a
+    // new name are a NOOP, although names that are already defined may still be redefined. Defaults to None, which
+    // does not limit the number of names.
+    max_defined_names: Option<usize>,
+
+    // The number of decimal places used when displaying float literals, or None to display them at full precision.
+    // This only affects display; float values are always stored and calculated at full precision.
+    float_display_precision: Option<usize>,
+
+    // FLOAT.EQUAL treats two floats as equal when they differ by no more than this amount. Evolved floats are rarely
+    // exactly equal, so a small epsilon makes the comparison more useful. Defaults to 0.0, which requires exact equality.
+    float_equality_epsilon: f64,
+
+    // When a template is set, this fraction of the initial population is generated as mutations of the template rather
+    // than as completely random code. The fraction defaults to one half. The template is not serialized, because its
+    // opcodes only have meaning for the VM that parsed it.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    init_template: Option<Code>,
+    init_template_fraction: f64,
+
+    // Every program generated for an initial population (or as a random immigrant) contains at least one of each of
+    // these instructions. Any that the random code is missing are injected at random places. Defaults to empty.
+    #[cfg_attr(feature = "serde", serde(deserialize_with = "deserialize_instruction_names"))]
+    required_instructions: Vec<&'static str>,
+
+    // The name of the stack (such as "FLOAT") whose top item is the program's answer, as returned by
+    // `VirtualMachine::answer`. Defaults to None, which reads the INTEGER stack.
+    answer_stack: Option<String>,
 }
 
 impl Configuration {
@@ -32,7 +96,23 @@ impl Configuration {
             crossover_rate,
             mutation_rate,
             defined_name_weight,
+            defined_name_weight_cap: None,
             instruction_weights,
+            list_expansion_cost: ExpansionCost::Flat,
+            deep_index_policy: DeepIndexPolicy::Modulo,
+            name_inheritance: NameInheritance::LeftPriority,
+            random_name_prefix: "RND.".to_owned(),
+            random_name_encoding: RandomNameEncoding::Base64,
+            name_rand_reuse_probability: 0.0,
+            max_name_length: 1024,
+            max_nesting_depth: 100,
+            max_defined_names: None,
+            float_display_precision: None,
+            float_equality_epsilon: 0.0,
+            init_template: None,
+            init_template_fraction: 0.5,
+            required_instructions: vec![],
+            answer_stack: None,
         }
     }
 
@@ -43,7 +123,23 @@ impl Configuration {
             crossover_rate: 99,
             mutation_rate: 1,
             defined_name_weight: 1,
+            defined_name_weight_cap: None,
             instruction_weights: FnvHashMap::default(),
+            list_expansion_cost: ExpansionCost::Flat,
+            deep_index_policy: DeepIndexPolicy::Modulo,
+            name_inheritance: NameInheritance::LeftPriority,
+            random_name_prefix: "RND.".to_owned(),
+            random_name_encoding: RandomNameEncoding::Base64,
+            name_rand_reuse_probability: 0.0,
+            max_name_length: 1024,
+            max_nesting_depth: 100,
+            max_defined_names: None,
+            float_display_precision: None,
+            float_equality_epsilon: 0.0,
+            init_template: None,
+            init_template_fraction: 0.5,
+            required_instructions: vec![],
+            answer_stack: None,
         }
     }
 
@@ -55,6 +151,14 @@ impl Configuration {
         self.max_points_in_random_expressions
     }
 
+    /// Returns the mean number of points in code produced by `VirtualMachineEngine::rand_code(None)`. The size of random
+    /// code is picked uniformly from one to MAX-POINTS-IN-RANDOM-EXPRESSIONS, so this is calculated exactly rather than
+    /// sampled. When defined names are picked as atoms, their definitions are used in place of a single point, so a VM
+    /// with many large definitions will produce somewhat larger code than this.
+    pub fn expected_random_points(&self) -> f64 {
+        (self.max_points_in_random_expressions as f64 + 1.0) / 2.0
+    }
+
     pub fn get_crossover_rate(&self) -> u8 {
         self.crossover_rate
     }
@@ -63,6 +167,18 @@ impl Configuration {
         self.mutation_rate
     }
 
+    /// Sets the relative chance that a child is created by crossover. At least one of the crossover and mutation rates
+    /// must be non-zero before any children are created
+    pub fn set_crossover_rate(&mut self, crossover_rate: u8) {
+        self.crossover_rate = crossover_rate;
+    }
+
+    /// Sets the relative chance that a child is created by mutation. At least one of the crossover and mutation rates
+    /// must be non-zero before any children are created
+    pub fn set_mutation_rate(&mut self, mutation_rate: u8) {
+        self.mutation_rate = mutation_rate;
+    }
+
     pub fn get_defined_name_weight(&self) -> u8 {
         self.defined_name_weight
     }
@@ -92,6 +208,155 @@ impl Configuration {
         self.instruction_weights.insert(instruction_name, weight)
     }
 
+    pub fn get_list_expansion_cost(&self) -> ExpansionCost {
+        self.list_expansion_cost
+    }
+
+    /// Sets how many steps are counted when a list on the Exec stack is expanded
+    pub fn set_list_expansion_cost(&mut self, list_expansion_cost: ExpansionCost) {
+        self.list_expansion_cost = list_expansion_cost;
+    }
+
+    pub fn get_deep_index_policy(&self) -> DeepIndexPolicy {
+        self.deep_index_policy
+    }
+
+    /// Sets how SHOVE, YANK and YANKDUP treat an index that is larger than the stack
+    pub fn set_deep_index_policy(&mut self, deep_index_policy: DeepIndexPolicy) {
+        self.deep_index_policy = deep_index_policy;
+    }
+
+    pub fn get_name_inheritance(&self) -> NameInheritance {
+        self.name_inheritance
+    }
+
+    /// Sets which of the parents' defined names are copied to a child
+    pub fn set_name_inheritance(&mut self, name_inheritance: NameInheritance) {
+        self.name_inheritance = name_inheritance;
+    }
+
+    pub fn get_random_name_prefix(&self) -> &str {
+        &self.random_name_prefix
+    }
+
+    pub fn get_random_name_encoding(&self) -> RandomNameEncoding {
+        self.random_name_encoding
+    }
+
+    /// Sets the prefix and encoding used when generating random names. The random bits always come from the virtual
+    /// machine's random number generator, so the same seed always produces the same sequence of names. An empty prefix
+    /// falls back to "RND.", because a random name without a prefix could look like a literal of some other type.
+    pub fn set_random_name_format(&mut self, prefix: &str, encoding: RandomNameEncoding) {
+        self.random_name_prefix = if prefix.is_empty() { "RND." } else { prefix }.to_owned();
+        self.random_name_encoding = encoding;
+    }
+
+    pub fn get_name_rand_reuse_probability(&self) -> f64 {
+        self.name_rand_reuse_probability
+    }
+
+    /// Sets the chance (0.0 to 1.0) that NAME.RAND pushes an already defined name rather than a new random name
+    pub fn set_name_rand_reuse_probability(&mut self, name_rand_reuse_probability: f64) {
+        self.name_rand_reuse_probability = name_rand_reuse_probability.clamp(0.0, 1.0);
+    }
+
+    pub fn get_max_name_length(&self) -> usize {
+        self.max_name_length
+    }
+
+    /// Sets the maximum length of a name that will be accepted by the parser
+    pub fn set_max_name_length(&mut self, max_name_length: usize) {
+        self.max_name_length = max_name_length;
+    }
+
+    pub fn get_max_nesting_depth(&self) -> usize {
+        self.max_nesting_depth
+    }
+
+    /// Sets the maximum depth to which lists may be nested
+    pub fn set_max_nesting_depth(&mut self, max_nesting_depth: usize) {
+        self.max_nesting_depth = max_nesting_depth;
+    }
+
+    pub fn get_max_defined_names(&self) -> Option<usize> {
+        self.max_defined_names
+    }
+
+    /// Sets the maximum number of names that a program may define, or None to allow any number of names
+    pub fn set_max_defined_names(&mut self, max_defined_names: Option<usize>) {
+        self.max_defined_names = max_defined_names;
+    }
+
+    pub fn get_float_display_precision(&self) -> Option<usize> {
+        self.float_display_precision
+    }
+
+    /// Sets the number of decimal places used when displaying float literals
+    pub fn set_float_display_precision(&mut self, float_display_precision: Option<usize>) {
+        self.float_display_precision = float_display_precision;
+    }
+
+    pub fn get_float_equality_epsilon(&self) -> f64 {
+        self.float_equality_epsilon
+    }
+
+    /// Sets the largest difference between two floats that FLOAT.EQUAL still considers equal. Negative values are
+    /// treated as zero.
+    pub fn set_float_equality_epsilon(&mut self, float_equality_epsilon: f64) {
+        self.float_equality_epsilon = float_equality_epsilon.max(0.0);
+    }
+
+    pub fn get_init_template(&self) -> Option<&Code> {
+        self.init_template.as_ref()
+    }
+
+    /// Sets the template from which part of the initial population is generated, or None to generate the entire initial
+    /// population randomly
+    pub fn set_init_template(&mut self, init_template: Option<Code>) {
+        self.init_template = init_template;
+    }
+
+    pub fn get_init_template_fraction(&self) -> f64 {
+        self.init_template_fraction
+    }
+
+    /// Sets the fraction (0.0 to 1.0) of the initial population that is generated from the template. A fraction of NaN
+    /// is treated as 0.0.
+    pub fn set_init_template_fraction(&mut self, init_template_fraction: f64) {
+        self.init_template_fraction =
+            if init_template_fraction.is_nan() { 0.0 } else { init_template_fraction.clamp(0.0, 1.0) };
+    }
+
+    pub fn get_required_instructions(&self) -> &[&'static str] {
+        &self.required_instructions
+    }
+
+    /// Sets the instructions that every randomly generated program must contain. The names are checked against the
+    /// instructions of the VM when the code is generated. See `VirtualMachineEngine::validate_required_instructions`.
+    pub fn set_required_instructions(&mut self, required_instructions: Vec<&'static str>) {
+        self.required_instructions = required_instructions;
+    }
+
+    pub fn get_answer_stack(&self) -> Option<&str> {
+        self.answer_stack.as_deref()
+    }
+
+    /// Sets the name of the stack (such as "FLOAT") whose top item is read by `VirtualMachine::answer`, or None to read
+    /// the INTEGER stack. The name is checked against the stacks of the VM when the islands are filled. See
+    /// `VirtualMachine::validate_answer_stack`.
+    pub fn set_answer_stack(&mut self, answer_stack: Option<&str>) {
+        self.answer_stack = answer_stack.map(|stack| stack.to_owned());
+    }
+
+    pub fn get_defined_name_weight_cap(&self) -> Option<usize> {
+        self.defined_name_weight_cap
+    }
+
+    /// Sets the most weight that all of the defined names together are given when picking a random atom
+    pub fn set_defined_name_weight_cap(&mut self, defined_name_weight_cap: Option<usize>) {
+        self.defined_name_weight_cap = defined_name_weight_cap;
+    }
+
     /// Returns a random genetic operation
     pub fn random_genetic_operation<R: rand::Rng>(&self, rng: &mut R) -> GeneticOperation {
         let total: usize = self.mutation_rate as usize + self.crossover_rate as usize;
@@ -103,4 +368,124 @@ impl Configuration {
             GeneticOperation::Crossover
         }
     }
+
+    /// Lists every setting that differs between this configuration and the other, as (field, this value, other value).
+    /// Experiment harnesses can log this to record exactly what changed between runs. Instruction weights are compared
+    /// one instruction at a time and reported as `instruction_weights.NAME`, using the default weight of 1 for an
+    /// instruction that only one of the configurations mentions.
+    pub fn diff(&self, other: &Configuration) -> Vec<(String, String, String)> {
+        let mut differences = vec![];
+        let mut compare = |field: &str, this: &dyn std::fmt::Debug, other: &dyn std::fmt::Debug| {
+            let (this, other) = (format!("{:?}", this), format!("{:?}", other));
+            if this != other {
+                differences.push((field.to_owned(), this, other));
+            }
+        };
+        compare("max_memory_size", &self.max_memory_size, &other.max_memory_size);
+        compare(
+            "max_points_in_random_expressions",
+            &self.max_points_in_random_expressions,
+            &other.max_points_in_random_expressions,
+        );
+        compare("crossover_rate", &self.crossover_rate, &other.crossover_rate);
+        compare("mutation_rate", &self.mutation_rate, &other.mutation_rate);
+        compare("defined_name_weight", &self.defined_name_weight, &other.defined_name_weight);
+        compare("defined_name_weight_cap", &self.defined_name_weight_cap, &other.defined_name_weight_cap);
+        compare("list_expansion_cost", &self.list_expansion_cost, &other.list_expansion_cost);
+        compare("deep_index_policy", &self.deep_index_policy, &other.deep_index_policy);
+        compare("name_inheritance", &self.name_inheritance, &other.name_inheritance);
+        compare("random_name_prefix", &self.random_name_prefix, &other.random_name_prefix);
+        compare("random_name_encoding", &self.random_name_encoding, &other.random_name_encoding);
+        compare("name_rand_reuse_probability", &self.name_rand_reuse_probability, &other.name_rand_reuse_probability);
+        compare("max_name_length", &self.max_name_length, &other.max_name_length);
+        compare("max_nesting_depth", &self.max_nesting_depth, &other.max_nesting_depth);
+        compare("max_defined_names", &self.max_defined_names, &other.max_defined_names);
+        compare("float_display_precision", &self.float_display_precision, &other.float_display_precision);
+        compare("float_equality_epsilon", &self.float_equality_epsilon, &other.float_equality_epsilon);
+        compare("init_template", &self.init_template, &other.init_template);
+        compare("init_template_fraction", &self.init_template_fraction, &other.init_template_fraction);
+        compare("required_instructions", &self.required_instructions, &other.required_instructions);
+        compare("answer_stack", &self.answer_stack, &other.answer_stack);
+
+        let mut names: Vec<&'static str> =
+            self.instruction_weights.keys().chain(other.instruction_weights.keys()).copied().collect();
+        names.sort();
+        names.dedup();
+        for name in names {
+            compare(
+                &format!("instruction_weights.{}", name),
+                &self.get_instruction_weight(name),
+                &other.get_instruction_weight(name),
+            );
+        }
+
+        differences
+    }
+}
+
+// The instruction names in the weights are static because they come from the instructions themselves. Each name that
+// is deserialized is leaked to make it static, which is fine for a configuration that is loaded once per experiment.
+#[cfg(feature = "serde")]
+fn deserialize_instruction_weights<'de, D: serde::Deserializer<'de>>(
+    deserializer: D,
+) -> Result<FnvHashMap<&'static str, u8>, D::Error> {
+    let weights: FnvHashMap<String, u8> = serde::Deserialize::deserialize(deserializer)?;
+    Ok(weights.into_iter().map(|(name, weight)| (&*Box::leak(name.into_boxed_str()), weight)).collect())
+}
+
+// Leaks each deserialized instruction name in the same way as `deserialize_instruction_weights`
+#[cfg(feature = "serde")]
+fn deserialize_instruction_names<'de, D: serde::Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Vec<&'static str>, D::Error> {
+    let names: Vec<String> = serde::Deserialize::deserialize(deserializer)?;
+    Ok(names.into_iter().map(|name| &*Box::leak(name.into_boxed_str())).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn diff() {
+        let mut config = Configuration::new_simple();
+        config.set_instruction_weight("BOOL.AND", 3);
+        assert!(config.diff(&config.clone()).is_empty());
+
+        let mut other = config.clone();
+        other.set_mutation_rate(10);
+        other.set_instruction_weight("INTEGER.SUM", 0);
+        other.set_instruction_weight("BOOL.AND", 3);
+        other.set_instruction_weight("FLOAT.SUM", 1);
+        assert_eq!(
+            vec![
+                ("mutation_rate".to_owned(), "1".to_owned(), "10".to_owned()),
+                ("instruction_weights.INTEGER.SUM".to_owned(), "1".to_owned(), "0".to_owned()),
+            ],
+            config.diff(&other)
+        );
+    }
+
+    #[test]
+    fn init_template_fraction() {
+        let mut config = Configuration::new_simple();
+        config.set_init_template_fraction(0.25);
+        assert_eq!(0.25, config.get_init_template_fraction());
+        config.set_init_template_fraction(2.0);
+        assert_eq!(1.0, config.get_init_template_fraction());
+        config.set_init_template_fraction(-1.0);
+        assert_eq!(0.0, config.get_init_template_fraction());
+        config.set_init_template_fraction(f64::NAN);
+        assert_eq!(0.0, config.get_init_template_fraction());
+    }
+
+    #[test]
+    fn random_name_format() {
+        let mut config = Configuration::new_simple();
+        config.set_random_name_format("GEN_", RandomNameEncoding::Hex);
+        assert_eq!("GEN_", config.get_random_name_prefix());
+        assert_eq!(RandomNameEncoding::Hex, config.get_random_name_encoding());
+        config.set_random_name_format("", RandomNameEncoding::Hex);
+        assert_eq!("RND.", config.get_random_name_prefix());
+    }
 }
@@ -3,7 +3,11 @@ mod tests {
     use crate::*;
 
     fn load_and_run(src: &str) -> BaseVm {
-        let mut vm = BaseVm::new(Some(1), Configuration::new_simple());
+        load_and_run_with_config(src, Configuration::new_simple())
+    }
+
+    fn load_and_run_with_config(src: &str, config: Configuration) -> BaseVm {
+        let mut vm = BaseVm::new(Some(1), config);
         add_base_instructions(&mut vm);
         add_base_literals(&mut vm);
         vm.engine_mut().parse_and_set_code(src).unwrap();
@@ -42,8 +46,13 @@ mod tests {
         test_bool_dup: ("( TRUE BOOL.DUP )", "( TRUE TRUE )", vec![]),
         test_bool_equal: ("( TRUE FALSE BOOL.EQUAL )", "( FALSE )", vec![]),
         test_bool_flush: ("( TRUE FALSE BOOL.FLUSH )", "( )", vec![]),
+        test_bool_fromcode_atom: ("( CODE.QUOTE 1 BOOL.FROMCODE )", "( FALSE )", vec![]),
+        test_bool_fromcode_list: ("( CODE.QUOTE ( 1 2 ) BOOL.FROMCODE )", "( TRUE )", vec![]),
         test_bool_fromfloat: ("( 0.0 0.00001 BOOL.FROMFLOAT BOOL.FROMFLOAT )", "( TRUE FALSE )", vec![]),
         test_bool_fromint: ("( 0 1 BOOL.FROMINT BOOL.FROMINT )", "( TRUE FALSE )", vec![]),
+        test_bool_implies: ("( TRUE TRUE BOOL.IMPLIES TRUE FALSE BOOL.IMPLIES FALSE TRUE BOOL.IMPLIES FALSE FALSE BOOL.IMPLIES )", "( TRUE FALSE TRUE TRUE )", vec![]),
+        test_bool_is_empty: ("( BOOL.ISEMPTY BOOL.ISEMPTY )", "( TRUE FALSE )", vec![]),
+        test_bool_nand: ("( TRUE TRUE BOOL.NAND TRUE FALSE BOOL.NAND FALSE TRUE BOOL.NAND FALSE FALSE BOOL.NAND )", "( FALSE TRUE TRUE TRUE )", vec![]),
         test_bool_not: ("( TRUE BOOL.NOT )", "( FALSE )", vec![]),
         test_bool_or: ("( TRUE FALSE BOOL.OR )", "( TRUE )", vec![]),
         test_bool_pop: ("( TRUE FALSE BOOL.POP )", "( TRUE )", vec![]),
@@ -55,6 +64,7 @@ mod tests {
         test_bool_stack_depth: ("( TRUE FALSE BOOL.STACKDEPTH )", "( TRUE FALSE 2 )", vec![]),
         test_bool_swap: ("( FALSE TRUE FALSE BOOL.SWAP )", "( FALSE FALSE TRUE )", vec![]),
         test_bool_swap_not_enough: ("( FALSE BOOL.SWAP )", "( FALSE )", vec![]),
+        test_bool_xor: ("( TRUE TRUE BOOL.XOR TRUE FALSE BOOL.XOR FALSE TRUE BOOL.XOR FALSE FALSE BOOL.XOR )", "( FALSE TRUE TRUE FALSE )", vec![]),
         test_bool_yank: ("( FALSE TRUE FALSE FALSE 2 BOOL.YANK )", "( FALSE FALSE FALSE TRUE )", vec![]),
         test_bool_yank_dup: ("( FALSE TRUE FALSE FALSE 2 BOOL.YANKDUP )", "( FALSE TRUE FALSE FALSE TRUE )", vec![]),
         test_code_append: ("( CODE.QUOTE 1 CODE.QUOTE 2 CODE.APPEND )", "( CODE.QUOTE ( 1 2 ) )", vec![]),
@@ -69,6 +79,9 @@ mod tests {
         test_code_contains_true: ("( CODE.QUOTE ( 4 ( 3 ( 2 ) ) ) CODE.QUOTE 3 CODE.CONTAINS )", "( TRUE )", vec![]),
         test_code_contains_false: ("( CODE.QUOTE ( 4 ( 3 ( 2 ) ) ) CODE.QUOTE 1 CODE.CONTAINS )", "( FALSE )", vec![]),
         test_code_contains_list: ("( CODE.QUOTE ( 4 ( 3 ( 2 ) ) ) CODE.QUOTE ( 2 ) CODE.CONTAINS )", "( TRUE )", vec![]),
+        test_code_count: ("( CODE.QUOTE ( 1 ( 1 2 ) 1 ) CODE.QUOTE 1 CODE.COUNT )", "( 3 )", vec![]),
+        test_code_count_list: ("( CODE.QUOTE ( ( 2 ) 1 ( 2 ) ) CODE.QUOTE ( 2 ) CODE.COUNT )", "( 2 )", vec![]),
+        test_code_count_none: ("( CODE.QUOTE ( 4 ( 3 ) ) CODE.QUOTE 1 CODE.COUNT )", "( 0 )", vec![]),
         test_code_define: ("( SOMENAME CODE.QUOTE TRUE CODE.DEFINE )", "( )", vec![("SOMENAME", "TRUE")]),
         test_code_definition: ("( CODE.QUOTE TRUE ANAME ANAME CODE.DEFINE CODE.DEFINITION )", "( CODE.QUOTE TRUE )", vec![("ANAME", "TRUE")]),
         test_code_discrepancy_zero: ("( CODE.QUOTE ( ANAME ( 3 ( 1 ) ) 1 ( 1 ) ) CODE.QUOTE ( ANAME ( 3 ( 1 ) ) 1 ( 1 ) ) CODE.DISCREPANCY )", "( 0 )", vec![]),
@@ -79,6 +92,9 @@ mod tests {
         test_code_do_n_range_countup: ("( 0 3 CODE.QUOTE BOOL.FROMINT CODE.DONRANGE )", "( FALSE TRUE TRUE TRUE )", vec![]),
         test_code_do_n_range_countdown: ("( 3 0 CODE.QUOTE BOOL.FROMINT CODE.DONRANGE )", "( TRUE TRUE TRUE FALSE )", vec![]),
         test_code_do_n_times: ("( FALSE TRUE TRUE 2 CODE.QUOTE BOOL.ROT CODE.DONTIMES )", "( TRUE FALSE TRUE )", vec![]),
+        test_code_drop_while: ("( CODE.QUOTE ( 1 2 3 4 ) CODE.QUOTE ( CODE.DON 3 INTEGER.LESS ) CODE.DROPWHILE )", "( CODE.QUOTE ( 3 4 ) )", vec![]),
+        test_code_drop_while_all: ("( CODE.QUOTE ( 1 2 ) CODE.QUOTE ( CODE.DON 3 INTEGER.LESS ) CODE.DROPWHILE )", "( CODE.QUOTE ( ) )", vec![]),
+        test_code_drop_while_atom: ("( CODE.QUOTE 1 CODE.QUOTE ( CODE.DON 3 INTEGER.LESS ) CODE.DROPWHILE )", "( CODE.QUOTE 1 )", vec![]),
         test_code_dup: ("( CODE.QUOTE BOOL.FROMINT CODE.DUP )", "( CODE.QUOTE BOOL.FROMINT CODE.QUOTE BOOL.FROMINT )", vec![]),
         test_code_equal_true: ("( CODE.QUOTE BOOL.FROMINT CODE.QUOTE BOOL.FROMINT CODE.EQUAL )", "( TRUE )", vec![]),
         test_code_equal_false: ("( CODE.QUOTE BOOL.FROMINT CODE.QUOTE BOOL.FROMFLOAT CODE.EQUAL )", "( FALSE )", vec![]),
@@ -87,14 +103,23 @@ mod tests {
         test_code_extract_2: ("( CODE.QUOTE ( 1 ( 2 ) ) 2 CODE.EXTRACT )", "( CODE.QUOTE ( 2 ) )", vec![]),
         test_code_extract_3: ("( CODE.QUOTE ( 1 ( 2 ) ) 3 CODE.EXTRACT )", "( CODE.QUOTE 2 )", vec![]),
         test_code_extract_modulo: ("( CODE.QUOTE ( 1 ( 2 ) ) 4 CODE.EXTRACT )", "( CODE.QUOTE ( 1 ( 2 ) ) )", vec![]),
+        test_code_filter_by_stack: ("( CODE.QUOTE ( 1 INTEGER.SUM TRUE ( 2.5 INTEGER.DUP ( BOOL.AND 3 ) ) FLOAT.SUM ) INTEGER CODE.FILTERBYSTACK )", "( CODE.QUOTE ( 1 INTEGER.SUM INTEGER.DUP 3 ) )", vec![]),
+        test_code_filter_by_stack_unknown: ("( CODE.QUOTE ( 1 TRUE ) UNKNOWN CODE.FILTERBYSTACK )", "( )", vec![]),
         test_code_flush: ("( CODE.QUOTE ( 1 ( 2 ) ) CODE.FLUSH )", "( )", vec![]),
         test_code_from_boolean: ("( TRUE CODE.FROMBOOLEAN )", "( CODE.QUOTE TRUE )", vec![]),
         test_code_from_float: ("( 1.5 CODE.FROMFLOAT )", "( CODE.QUOTE 1.5 )", vec![]),
         test_code_from_integer: ("( 42 CODE.FROMINTEGER )", "( CODE.QUOTE 42 )", vec![]),
         test_code_from_name: ("( KmU7 CODE.FROMNAME )", "( CODE.QUOTE KmU7 )", vec![]),
+        test_code_hash_equal: ("( CODE.QUOTE ( A ( 1 2.5 ) ) CODE.QUOTE ( A ( 1 2.5 ) ) CODE.HASH CODE.HASH INTEGER.EQUAL )", "( TRUE )", vec![]),
+        test_code_hash_different: ("( CODE.QUOTE ( A ( 1 2.5 ) ) CODE.QUOTE ( A ( 1 2.6 ) ) CODE.HASH CODE.HASH INTEGER.EQUAL )", "( FALSE )", vec![]),
         test_code_if_true: ("( TRUE CODE.QUOTE TRUENAME CODE.QUOTE FALSENAME CODE.IF )", "( TRUENAME )", vec![]),
         test_code_if_false: ("( FALSE CODE.QUOTE TRUENAME CODE.QUOTE FALSENAME CODE.IF )", "( FALSENAME )", vec![]),
+        test_code_if_empty_true: ("( CODE.QUOTE ( ) CODE.QUOTE EMPTYNAME CODE.QUOTE FULLNAME CODE.IFEMPTY )", "( EMPTYNAME )", vec![]),
+        test_code_if_empty_false: ("( CODE.QUOTE ( A ) CODE.QUOTE EMPTYNAME CODE.QUOTE FULLNAME CODE.IFEMPTY )", "( FULLNAME )", vec![]),
+        test_code_if_empty_atom: ("( CODE.QUOTE A CODE.QUOTE EMPTYNAME CODE.QUOTE FULLNAME CODE.IFEMPTY )", "( FULLNAME )", vec![]),
         test_code_insert: ("( CODE.QUOTE C CODE.QUOTE ( A ( B ) ) 2 CODE.INSERT )", "( CODE.QUOTE ( A C ) )", vec![]),
+        test_code_is_empty_true: ("( CODE.ISEMPTY )", "( TRUE )", vec![]),
+        test_code_is_empty_false: ("( CODE.QUOTE ( ) CODE.ISEMPTY )", "( CODE.QUOTE ( ) FALSE )", vec![]),
         test_code_length: ("( CODE.QUOTE ( A B ( C 1 2 3 ) ) CODE.LENGTH )", "( 3 )", vec![]),
         test_code_list: ("( CODE.QUOTE A CODE.QUOTE ( B ) CODE.LIST )", "( CODE.QUOTE ( A ( B ) ) )", vec![]),
         test_code_member_true: ("( CODE.QUOTE A CODE.QUOTE ( A ( B ) ) CODE.MEMBER )", "( TRUE )", vec![]),
@@ -113,9 +138,14 @@ mod tests {
         test_code_pop: ("( CODE.QUOTE TRUE CODE.POP )", "( )", vec![]),
         test_code_position: ("( CODE.QUOTE ( B ) CODE.QUOTE ( A ( B ) ) CODE.POSITION )", "( 1 )", vec![]),
         test_code_position_not_found: ("( CODE.QUOTE B CODE.QUOTE ( A ( B ) ) CODE.POSITION )", "( -1 )", vec![]),
+        test_code_position_all: ("( CODE.QUOTE A CODE.QUOTE ( A B A C A ) CODE.POSITIONALL )", "( CODE.QUOTE ( 0 2 4 ) )", vec![]),
+        test_code_position_all_not_found: ("( CODE.QUOTE D CODE.QUOTE ( A B A C A ) CODE.POSITIONALL )", "( CODE.QUOTE ( ) )", vec![]),
         test_code_position_self: ("( CODE.QUOTE B CODE.QUOTE B CODE.POSITION )", "( 0 )", vec![]),
         test_code_rand_no_points: ("( CODE.RAND )", "( )", vec![]),
-        test_code_rand_points: ("( 5 CODE.RAND )", "( CODE.QUOTE ( FLOAT.FLUSH EXEC.K INTEGER.LESS ) )", vec![]),
+        test_code_rand_points: ("( 5 CODE.RAND )", "( CODE.QUOTE ( CODE.ISEMPTY FLOAT.EQUAL FLOAT.EXP ) )", vec![]),
+        test_code_replace_at_point_zero: ("( CODE.QUOTE ( A ( B ) ) CODE.QUOTE C 0 CODE.REPLACEATPOINT )", "( CODE.QUOTE C )", vec![]),
+        test_code_replace_at_point_one: ("( CODE.QUOTE ( A ( B ) ) CODE.QUOTE C 1 CODE.REPLACEATPOINT )", "( CODE.QUOTE ( C ( B ) ) )", vec![]),
+        test_code_replace_at_point_wrap: ("( CODE.QUOTE ( A ( B ) ) CODE.QUOTE C 7 CODE.REPLACEATPOINT )", "( CODE.QUOTE ( A ( C ) ) )", vec![]),
         test_code_rot: ("( CODE.QUOTE A CODE.QUOTE B CODE.QUOTE C CODE.ROT )", "( CODE.QUOTE B CODE.QUOTE C CODE.QUOTE A )", vec![]),
         test_code_shove: ("( CODE.QUOTE A CODE.QUOTE B CODE.QUOTE C 2 CODE.SHOVE )", "( CODE.QUOTE C CODE.QUOTE A CODE.QUOTE B )", vec![]),
         test_code_shove_zero: ("( CODE.QUOTE A CODE.QUOTE B CODE.QUOTE C 0 CODE.SHOVE )", "( CODE.QUOTE A CODE.QUOTE B CODE.QUOTE C )", vec![]),
@@ -123,7 +153,19 @@ mod tests {
         test_code_size: ("( CODE.QUOTE ( A ( B ) C ) CODE.SIZE )", "( 5 )", vec![]),
         test_code_stack_depth: ("( CODE.QUOTE A CODE.QUOTE B CODE.STACKDEPTH )", "( CODE.QUOTE A CODE.QUOTE B 2 )", vec![]),
         test_code_substitute: ("( CODE.QUOTE A CODE.QUOTE ( B ) CODE.QUOTE ( A ( B ) ( A ( B ) ) ) CODE.SUBSTITUTE )", "( CODE.QUOTE ( A A ( A A ) ) )", vec![]),
+        test_code_sublist: ("( CODE.QUOTE ( A B C D ) 1 3 CODE.SUBLIST )", "( CODE.QUOTE ( B C ) )", vec![]),
+        test_code_sublist_wrapped: ("( CODE.QUOTE ( A B C D ) 6 8 CODE.SUBLIST )", "( CODE.QUOTE ( B C ) )", vec![]),
+        test_code_sublist_reversed: ("( CODE.QUOTE ( A B C D ) 4 2 CODE.SUBLIST )", "( CODE.QUOTE ( C D ) )", vec![]),
+        test_code_sublist_coerce: ("( CODE.QUOTE A 0 1 CODE.SUBLIST )", "( CODE.QUOTE ( A ) )", vec![]),
         test_code_swap: ("( CODE.QUOTE A CODE.QUOTE B CODE.SWAP )", "( CODE.QUOTE B CODE.QUOTE A )", vec![]),
+        test_code_take_while: ("( CODE.QUOTE ( 1 2 3 4 ) CODE.QUOTE ( CODE.DON 3 INTEGER.LESS ) CODE.TAKEWHILE )", "( CODE.QUOTE ( 1 2 ) )", vec![]),
+        test_code_take_while_all: ("( CODE.QUOTE ( 1 2 ) CODE.QUOTE ( CODE.DON 3 INTEGER.LESS ) CODE.TAKEWHILE )", "( CODE.QUOTE ( 1 2 ) )", vec![]),
+        test_code_take_while_empty: ("( CODE.QUOTE ( ) CODE.QUOTE ( CODE.DON 3 INTEGER.LESS ) CODE.TAKEWHILE )", "( CODE.QUOTE ( ) )", vec![]),
+        test_code_transpose: ("( CODE.QUOTE ( ( 1 2 ) ( 3 4 ) ) CODE.TRANSPOSE )", "( CODE.QUOTE ( ( 1 3 ) ( 2 4 ) ) )", vec![]),
+        test_code_transpose_rectangle: ("( CODE.QUOTE ( ( A B C ) ( D E F ) ) CODE.TRANSPOSE )", "( CODE.QUOTE ( ( A D ) ( B E ) ( C F ) ) )", vec![]),
+        test_code_transpose_empty: ("( CODE.QUOTE ( ) CODE.TRANSPOSE )", "( CODE.QUOTE ( ) )", vec![]),
+        test_code_transpose_ragged: ("( CODE.QUOTE ( ( 1 2 ) ( 3 ) ) CODE.TRANSPOSE )", "( )", vec![]),
+        test_code_transpose_atom_row: ("( CODE.QUOTE ( ( 1 2 ) 3 ) CODE.TRANSPOSE )", "( )", vec![]),
         test_code_yank: ("( CODE.QUOTE A CODE.QUOTE B CODE.QUOTE C CODE.QUOTE D 2 CODE.YANK )", "( CODE.QUOTE A CODE.QUOTE C CODE.QUOTE D CODE.QUOTE B )", vec![]),
         test_code_yank_dup: ("( CODE.QUOTE A CODE.QUOTE B CODE.QUOTE C CODE.QUOTE D 2 CODE.YANKDUP )", "( CODE.QUOTE A CODE.QUOTE B CODE.QUOTE C CODE.QUOTE D CODE.QUOTE B )", vec![]),
         test_exec_define: ("( A EXEC.DEFINE TRUE A )", "( TRUE )", vec![("A", "TRUE")]),
@@ -132,38 +174,57 @@ mod tests {
         test_exec_do_n_range_countdown: ("( 3 0 EXEC.DONRANGE BOOL.FROMINT )", "( TRUE TRUE TRUE FALSE )", vec![]),
         test_exec_do_n_times: ("( FALSE TRUE TRUE 2 EXEC.DONTIMES BOOL.ROT )", "( TRUE FALSE TRUE )", vec![]),
         test_exec_dup: ("( EXEC.DUP 5 )", "( 5 5 )", vec![]),
+        test_exec_dup_items: ("( 2 EXEC.DUPITEMS A B C )", "( A B A B C )", vec![]),
+        test_exec_dup_items_too_many: ("( 5 EXEC.DUPITEMS A B )", "( A B A B )", vec![]),
         test_exec_equal: ("( EXEC.EQUAL 5 5 )", "( TRUE )", vec![]),
         test_exec_flush: ("( EXEC.FLUSH 5 5 )", "( )", vec![]),
+        test_exec_flush_if_true: ("( 1 TRUE EXEC.FLUSHIF 2 3 )", "( 1 )", vec![]),
+        test_exec_flush_if_false: ("( 1 FALSE EXEC.FLUSHIF 2 3 )", "( 1 2 3 )", vec![]),
         test_exec_if_true: ("( TRUE EXEC.IF TRUENAME FALSENAME )", "( TRUENAME )", vec![]),
         test_exec_if_false: ("( FALSE EXEC.IF TRUENAME FALSENAME )", "( FALSENAME )", vec![]),
         test_exec_k: ("( EXEC.K TRUENAME FALSENAME )", "( TRUENAME )", vec![]),
         test_exec_pop: ("( EXEC.POP 5 )", "( )", vec![]),
+        test_exec_rand: ("( 1.0 2.0 3.0 5 EXEC.RAND )", "( 1.0 2.0 3.0 5 CODE.RAND CODE.DON )", vec![]),
         test_exec_rot: ("( EXEC.ROT A B C )", "( C A B )", vec![]),
         test_exec_shove: ("( 2 EXEC.SHOVE A B C )", "( B C A )", vec![]),
         test_exec_shove_zero: ("( 0 EXEC.SHOVE A B C )", "( A B C )", vec![]),
         test_exec_shove_wrap: ("( 3 EXEC.SHOVE A B C )", "( A B C )", vec![]),
         test_exec_stack_depth: ("( EXEC.STACKDEPTH A B )", "( A B 2 )", vec![]),
+        test_exec_steps_remaining: ("( EXEC.STEPSREMAINING )", "( 998 )", vec![]),
+        test_exec_steps_remaining_branch_true: ("( EXEC.STEPSREMAINING 990 INTEGER.GREATER EXEC.IF 1 2 )", "( 1 )", vec![]),
+        test_exec_steps_remaining_branch_false: ("( EXEC.STEPSREMAINING 999 INTEGER.GREATER EXEC.IF 1 2 )", "( 2 )", vec![]),
         test_exec_swap: ("( EXEC.SWAP A B )", "( B A )", vec![]),
         test_exec_s: ("( EXEC.S A B C )", "( A C ( B C ) )", vec![]),
         test_exec_yank: ("( 2 EXEC.YANK A B C D )", "( C A B D )", vec![]),
         test_exec_yank_dup: ("( 2 EXEC.YANKDUP A B C D )", "( C A B C D )", vec![]),
         test_exec_y: ("( 0 EXEC.Y ( INTEGER.DUP 2 INTEGER.EQUAL EXEC.IF EXEC.POP ( INTEGER.DUP 1 INTEGER.SUM ) ) )", "( 0 1 2 )", vec![]),
+        test_float_abs: ("( -2.5 FLOAT.ABS 1.5 FLOAT.ABS )", "( 2.5 1.5 )", vec![]),
         test_float_cos: ("( 1.0 FLOAT.COS )", "( 0.54030230586814 )", vec![]),
         test_float_define: ("( A 1.0 FLOAT.DEFINE A )", "( 1.0 )", vec![("A", "1.0")]),
         test_float_difference: ("( 3.0 1.0 FLOAT.DIFFERENCE )", "( 2.0 )", vec![]),
+        test_float_difference_overflow: ("( 1.0 100.0 FLOAT.EXP FLOAT.DUP -1.0 FLOAT.PRODUCT FLOAT.DIFFERENCE )", "( 1.0 )", vec![]),
         test_float_dup: ("( 1.0 FLOAT.DUP )", "( 1.0 1.0 )", vec![]),
         test_float_equal: ("( 1.0 1.0 FLOAT.EQUAL )", "( TRUE )", vec![]),
+        test_float_equal_close: ("( 1.0 1.0005 FLOAT.EQUAL )", "( FALSE )", vec![]),
+        test_float_exp: ("( 1.0 FLOAT.EXP )", "( 2.718281828459045 )", vec![]),
+        test_float_exp_overflow: ("( 100.0 FLOAT.EXP 1000.0 FLOAT.EXP FLOAT.EQUAL )", "( TRUE )", vec![]),
         test_float_flush: ("( 1.0 1.0 FLOAT.FLUSH )", "( )", vec![]),
         test_float_fromboolean: ("( TRUE FLOAT.FROMBOOLEAN FALSE FLOAT.FROMBOOLEAN )", "( 1.0 0.0 )", vec![]),
         test_float_frominteger: ("( 5 FLOAT.FROMINTEGER )", "( 5.0 )", vec![]),
         test_float_greater: ("( 5.0 3.0 FLOAT.GREATER )", "( TRUE )", vec![]),
+        test_float_is_empty_true: ("( FLOAT.ISEMPTY )", "( TRUE )", vec![]),
+        test_float_is_empty_false: ("( 1.0 FLOAT.ISEMPTY )", "( 1.0 FALSE )", vec![]),
         test_float_less: ("( 5.0 3.0 FLOAT.LESS )", "( FALSE )", vec![]),
+        test_float_log: ("( 2.718281828459045 FLOAT.LOG )", "( 1.0 )", vec![]),
+        test_float_log_zero: ("( 1.0 0.0 FLOAT.LOG )", "( 1.0 )", vec![]),
+        test_float_log_negative: ("( 1.0 -2.0 FLOAT.LOG )", "( 1.0 )", vec![]),
         test_float_max: ("( 5.0 3.0 FLOAT.MAX )", "( 5.0 )", vec![]),
         test_float_min: ("( -5.0 3.0 FLOAT.MIN )", "( -5.0 )", vec![]),
         test_float_modulo: ("( -5.0 3.0 FLOAT.MODULO )", "( -2.0 )", vec![]),
         test_float_modulo_zero: ("( -5.0 0.0 FLOAT.MODULO )", "( )", vec![]),
         test_float_pop: ("( 5.0 FLOAT.POP )", "( )", vec![]),
         test_float_product: ("( -5.0 3.0 FLOAT.PRODUCT )", "( -15.0 )", vec![]),
+        test_float_product_overflow: ("( 1.0 100000000000000000000.0 FLOAT.DUP FLOAT.PRODUCT )", "( 1.0 )", vec![]),
         test_float_quotient: ("( 15.0 3.0 FLOAT.QUOTIENT )", "( 5.0 )", vec![]),
         test_float_quotient_zero: ("( 15.0 0.0 FLOAT.QUOTIENT )", "( )", vec![]),
         test_float_rand: ("( FLOAT.RAND )", "( 0.426738773909753 )", vec![]),
@@ -171,13 +232,21 @@ mod tests {
         test_float_shove: ("( 1.0 2.0 3.0 2 FLOAT.SHOVE )", "( 3.0 1.0 2.0 )", vec![]),
         test_float_shove_zero: ("( 1.0 2.0 3.0 0 FLOAT.SHOVE )", "( 1.0 2.0 3.0 )", vec![]),
         test_float_shove_wrap: ("( 1.0 2.0 3.0 3 FLOAT.SHOVE )", "( 1.0 2.0 3.0 )", vec![]),
+        test_float_sign: ("( -2.5 FLOAT.SIGN 0.0 FLOAT.SIGN 1.5 FLOAT.SIGN )", "( -1.0 0.0 1.0 )", vec![]),
         test_float_sin: ("( 1.0 FLOAT.SIN )", "( 0.841470984807897 )", vec![]),
+        test_float_sqrt: ("( 6.25 FLOAT.SQRT )", "( 2.5 )", vec![]),
+        test_float_sqrt_negative: ("( 1.0 -4.0 FLOAT.SQRT )", "( 1.0 )", vec![]),
         test_float_stack_depth: ("( 1.0 2.0 FLOAT.STACKDEPTH )", "( 1.0 2.0 2 )", vec![]),
         test_float_sum: ("( 1.5 2.5 FLOAT.SUM )", "( 4.0 )", vec![]),
+        test_float_sum_overflow: ("( 1.0 100.0 FLOAT.EXP FLOAT.DUP FLOAT.SUM )", "( 1.0 )", vec![]),
         test_float_swap: ("( 1.0 2.0 3.0 FLOAT.SWAP )", "( 1.0 3.0 2.0 )", vec![]),
         test_float_tan: ("( 1.0 FLOAT.TAN )", "( 1.557407724654902 )", vec![]),
         test_float_yank: ("( 1.0 2.0 3.0 4.0 2 FLOAT.YANK )", "( 1.0 3.0 4.0 2.0 )", vec![]),
         test_float_yank_dup: ("( 1.0 2.0 3.0 4.0 2 FLOAT.YANKDUP )", "( 1.0 2.0 3.0 4.0 2.0 )", vec![]),
+        test_integer_abs: ("( -5 INTEGER.ABS 3 INTEGER.ABS )", "( 5 3 )", vec![]),
+        test_integer_abs_min: ("( -9223372036854775808 INTEGER.ABS )", "( 9223372036854775807 )", vec![]),
+        test_integer_all_stack_depths: ("( TRUE 1.5 2 3 A B CODE.QUOTE 1 INTEGER.ALLSTACKDEPTHS 4 )", "( TRUE 1.5 A B CODE.QUOTE 1 2 3 1 1 1 1 2 2 4 )", vec![]),
+        test_integer_context_id: ("( INTEGER.CONTEXTID )", "( 0 )", vec![]),
         test_integer_define: ("( A 1 INTEGER.DEFINE A )", "( 1 )", vec![("A", "1")]),
         test_integer_difference: ("( 3 1 INTEGER.DIFFERENCE )", "( 2 )", vec![]),
         test_integer_difference_above_max: ("( 9223372036854775807 -5 INTEGER.DIFFERENCE )", "( 9223372036854775807 )", vec![]),
@@ -187,13 +256,25 @@ mod tests {
         test_integer_flush: ("( 1 1 INTEGER.FLUSH )", "( )", vec![]),
         test_integer_fromboolean: ("( TRUE INTEGER.FROMBOOLEAN FALSE INTEGER.FROMBOOLEAN )", "( 1 0 )", vec![]),
         test_integer_fromfloat: ("( 5.0 INTEGER.FROMFLOAT )", "( 5 )", vec![]),
+        test_integer_fromfloat_above_max: ("( 100000000000000000000.0 INTEGER.FROMFLOAT )", "( 9223372036854775807 )", vec![]),
+        test_integer_fromfloat_below_min: ("( -100000000000000000000.0 INTEGER.FROMFLOAT )", "( -9223372036854775808 )", vec![]),
         test_integer_greater: ("( 5 3 INTEGER.GREATER )", "( TRUE )", vec![]),
+        test_integer_is_empty_true: ("( INTEGER.ISEMPTY )", "( TRUE )", vec![]),
+        test_integer_is_empty_false: ("( 1 INTEGER.ISEMPTY )", "( 1 FALSE )", vec![]),
         test_integer_less: ("( 5 3 INTEGER.LESS )", "( FALSE )", vec![]),
         test_integer_max: ("( 5 3 INTEGER.MAX )", "( 5 )", vec![]),
         test_integer_min: ("( -5 3 INTEGER.MIN )", "( -5 )", vec![]),
         test_integer_modulo: ("( -5 3 INTEGER.MODULO )", "( -2 )", vec![]),
         test_integer_modulo_zero: ("( -5 0 INTEGER.MODULO )", "( )", vec![]),
+        test_integer_negate: ("( -5 INTEGER.NEGATE 3 INTEGER.NEGATE )", "( 5 -3 )", vec![]),
+        test_integer_negate_min: ("( -9223372036854775808 INTEGER.NEGATE )", "( 9223372036854775807 )", vec![]),
         test_integer_pop: ("( 42 INTEGER.POP )", "( )", vec![]),
+        test_integer_pow: ("( 2 10 INTEGER.POW )", "( 1024 )", vec![]),
+        test_integer_pow_zero: ("( 7 0 INTEGER.POW )", "( 1 )", vec![]),
+        test_integer_pow_above_max: ("( 3 100 INTEGER.POW )", "( 9223372036854775807 )", vec![]),
+        test_integer_pow_below_min: ("( -3 101 INTEGER.POW )", "( -9223372036854775808 )", vec![]),
+        test_integer_pow_large_exponent: ("( -1 9223372036854775807 INTEGER.POW )", "( -1 )", vec![]),
+        test_integer_pow_negative: ("( 2 -1 INTEGER.POW )", "( )", vec![]),
         test_integer_product: ("( -5 3 INTEGER.PRODUCT )", "( -15 )", vec![]),
         test_integer_product_above_max: ("( 9223372036854775807 3 INTEGER.PRODUCT )", "( 9223372036854775807 )", vec![]),
         test_integer_product_below_min: ("( -9223372036854775808 3 INTEGER.PRODUCT )", "( -9223372036854775808 )", vec![]),
@@ -205,16 +286,23 @@ mod tests {
         test_integer_shove: ("( 1 2 3 2 INTEGER.SHOVE )", "( 3 1 2 )", vec![]),
         test_integer_shove_zero: ("( 1 2 3 0 INTEGER.SHOVE )", "( 1 2 3 )", vec![]),
         test_integer_shove_wrap: ("( 1 2 3 3 INTEGER.SHOVE )", "( 1 2 3 )", vec![]),
+        test_integer_shove_large_modulo: ("( 1 2 3 4 5 INTEGER.SHOVE )", "( 1 2 4 3 )", vec![]),
         test_integer_stack_depth: ("( 1 2 INTEGER.STACKDEPTH )", "( 1 2 2 )", vec![]),
         test_integer_sum: ("( 42 7 INTEGER.SUM )", "( 49 )", vec![]),
         test_integer_sum_above_max: ("( 9223372036854775807 1 INTEGER.SUM )", "( 9223372036854775807 )", vec![]),
         test_integer_sum_below_min: ("( -9223372036854775808 -1 INTEGER.SUM )", "( -9223372036854775808 )", vec![]),
+        test_integer_sum_all: ("( 1 2 3 INTEGER.SUMALL )", "( 6 )", vec![]),
+        test_integer_sum_all_empty: ("( INTEGER.SUMALL )", "( 0 )", vec![]),
+        test_integer_sum_all_above_max: ("( 9223372036854775807 1 -2 INTEGER.SUMALL )", "( 9223372036854775805 )", vec![]),
         test_integer_swap: ("( 1 2 3 INTEGER.SWAP )", "( 1 3 2 )", vec![]),
         test_integer_yank: ("( 1 2 3 4 2 INTEGER.YANK )", "( 1 3 4 2 )", vec![]),
         test_integer_yank_dup: ("( 1 2 3 4 2 INTEGER.YANKDUP )", "( 1 2 3 4 2 )", vec![]),
+        test_name_all: ("( B 2.0 FLOAT.DEFINE A 1.0 FLOAT.DEFINE NAME.ALL )", "( A B )", vec![("A", "1.0"), ("B", "2.0")]),
         test_name_dup: ("( A NAME.DUP )", "( A A )", vec![]),
         test_name_equal: ("( A B NAME.EQUAL )", "( FALSE )", vec![]),
         test_name_flush: ("( A B NAME.FLUSH )", "( )", vec![]),
+        test_name_is_empty_true: ("( NAME.ISEMPTY )", "( TRUE )", vec![]),
+        test_name_is_empty_false: ("( A NAME.ISEMPTY )", "( A FALSE )", vec![]),
         test_name_pop: ("( A NAME.POP )", "( )", vec![]),
         test_name_quote: ("( A 1.0 FLOAT.DEFINE NAME.QUOTE A )", "( A )", vec![("A", "1.0")]),
         test_name_rand: ("( NAME.RAND )", "( RND.sN5S8Epgn7Y= )", vec![]),
@@ -237,4 +325,23 @@ mod tests {
         assert_eq!(0, to_run.bool().len());
         assert_eq!(Some(expected), to_run.code().pop());
     }
+
+    #[test]
+    fn deep_index_clamp() {
+        let mut config = Configuration::new_simple();
+        config.set_deep_index_policy(DeepIndexPolicy::Clamp);
+
+        // An index past the bottom of the stack moves the item to the bottom rather than wrapping around
+        let clamped = load_and_run_with_config("( 1 2 3 4 5 INTEGER.SHOVE )", config.clone());
+        let expected = load_and_run_with_config("( 4 1 2 3 )", config.clone());
+        assert_eq!(expected, clamped);
+
+        let clamped = load_and_run_with_config("( 1 2 3 4 -2 INTEGER.YANK )", config.clone());
+        let expected = load_and_run_with_config("( 1 2 3 4 )", config.clone());
+        assert_eq!(expected, clamped);
+
+        let clamped = load_and_run_with_config("( A B C 7 NAME.YANKDUP )", config.clone());
+        let expected = load_and_run_with_config("( A B C A )", config);
+        assert_eq!(expected, clamped);
+    }
 }
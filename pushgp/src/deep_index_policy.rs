@@ -0,0 +1,25 @@
+/// Determines how the instructions that index "deep" into a stack (SHOVE, YANK and YANKDUP) treat an index that is
+/// larger than the stack. The Stack itself always takes the index modulo its length; the policy is applied to the index
+/// before it is passed to the Stack.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum DeepIndexPolicy {
+    /// The index wraps around, so an index that is one past the bottom of the stack refers to the top item. This is the
+    /// default.
+    Modulo,
+
+    /// The index is clamped to the stack, so any index past the bottom of the stack refers to the bottom item and any
+    /// negative index refers to the top item.
+    Clamp,
+}
+
+impl DeepIndexPolicy {
+    /// Returns the index to pass to the Stack for a stack with `len` items
+    pub fn apply(&self, position: i64, len: usize) -> i64 {
+        match self {
+            DeepIndexPolicy::Modulo => position,
+            DeepIndexPolicy::Clamp if len > 0 => position.clamp(0, len as i64 - 1),
+            DeepIndexPolicy::Clamp => position,
+        }
+    }
+}
@@ -0,0 +1,218 @@
+use rand::{rngs::SmallRng, RngCore, SeedableRng};
+use std::any::Any;
+
+/// The random number generators that can be used by a VirtualMachineEngine. This is implemented for every `RngCore`
+/// that can also be seeded, cloned, compared and debug-printed, so it does not need to be implemented by hand.
+pub trait EngineRngCore: RngCore + std::fmt::Debug + Send {
+    fn clone_box(&self) -> Box<dyn EngineRngCore>;
+    fn eq_box(&self, other: &dyn EngineRngCore) -> bool;
+    fn as_any(&self) -> &dyn Any;
+
+    /// Returns a new generator of the same kind, seeded with the seed
+    fn reseeded(&self, seed: u64) -> Box<dyn EngineRngCore>;
+}
+
+impl<T: RngCore + SeedableRng + Clone + PartialEq + std::fmt::Debug + Send + 'static> EngineRngCore for T {
+    fn clone_box(&self) -> Box<dyn EngineRngCore> {
+        Box::new(self.clone())
+    }
+
+    fn reseeded(&self, seed: u64) -> Box<dyn EngineRngCore> {
+        Box::new(T::seed_from_u64(seed))
+    }
+
+    fn eq_box(&self, other: &dyn EngineRngCore) -> bool {
+        other.as_any().downcast_ref::<T>().map(|other| self == other).unwrap_or(false)
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+/// The random number generator used by a VirtualMachineEngine. By default this is a `SmallRng`, which is fast but does
+/// not promise to produce the same values on every platform or in every version of the rand crate. Any other generator
+/// can be plugged in with `EngineRng::new`, such as a cryptographic generator or a counter-based generator that
+/// produces exactly the same values everywhere.
+///
+/// Every value is drawn from the generator's `next_u64`, with `next_u32` taking the upper 32 bits, which is what
+/// `SmallRng` does itself. This means that the position of the generator in its stream is simply the number of words it
+/// has drawn, so a generator that was created from a seed can be recreated anywhere from the seed and that count.
+#[derive(Debug)]
+pub struct EngineRng {
+    inner: Box<dyn EngineRngCore>,
+    seed: Option<u64>,
+    words_drawn: u64,
+}
+
+impl EngineRng {
+    /// Plugs in a generator. Its seed is not known, so its position cannot be saved; use `reseeded` on the result to
+    /// get a generator of the same kind whose position can be saved.
+    pub fn new<T: EngineRngCore + 'static>(rng: T) -> EngineRng {
+        EngineRng { inner: Box::new(rng), seed: None, words_drawn: 0 }
+    }
+
+    /// Creates a `SmallRng` seeded with the seed, or with a seed from the operating system if there is no seed
+    pub fn from_optional_seed(seed: Option<u64>) -> EngineRng {
+        let seed = seed.unwrap_or_else(rand::random);
+        EngineRng { inner: Box::new(SmallRng::seed_from_u64(seed)), seed: Some(seed), words_drawn: 0 }
+    }
+
+    /// Returns a new generator of the same kind as this one, seeded with the seed. This lets code that needs a
+    /// reproducible stream of its own, such as `VirtualMachineEngine::rand_initial_individual`, keep using the kind of
+    /// generator that was plugged in with `EngineRng::new`.
+    pub fn reseeded(&self, seed: u64) -> EngineRng {
+        EngineRng { inner: self.inner.reseeded(seed), seed: Some(seed), words_drawn: 0 }
+    }
+
+    /// Returns the seed the generator was created from, or None if it was plugged in with `EngineRng::new`
+    pub fn get_seed(&self) -> Option<u64> {
+        self.seed
+    }
+
+    /// Returns the number of 64-bit words the generator has drawn since it was created
+    pub fn get_words_drawn(&self) -> u64 {
+        self.words_drawn
+    }
+
+    /// Draws and discards the specified number of words, such as to move a newly seeded generator to a position
+    /// returned by `get_words_drawn`. This takes time in proportion to the number of words.
+    pub fn skip_words(&mut self, words: u64) {
+        for _ in 0..words {
+            self.next_u64();
+        }
+    }
+}
+
+impl Clone for EngineRng {
+    fn clone(&self) -> Self {
+        EngineRng { inner: self.inner.clone_box(), seed: self.seed, words_drawn: self.words_drawn }
+    }
+}
+
+impl PartialEq for EngineRng {
+    fn eq(&self, other: &Self) -> bool {
+        self.inner.eq_box(other.inner.as_ref())
+    }
+}
+
+impl RngCore for EngineRng {
+    fn next_u32(&mut self) -> u32 {
+        (self.next_u64() >> 32) as u32
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.words_drawn += 1;
+        self.inner.next_u64()
+    }
+
+    // Fills the bytes in the same way as `SmallRng`: eight bytes from each word, and the last few from one more word
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        let mut chunks = dest.chunks_exact_mut(8);
+        for chunk in &mut chunks {
+            chunk.copy_from_slice(&self.next_u64().to_le_bytes());
+        }
+        let rest = chunks.into_remainder();
+        if rest.len() > 4 {
+            let len = rest.len();
+            rest.copy_from_slice(&self.next_u64().to_le_bytes()[..len]);
+        } else if !rest.is_empty() {
+            let len = rest.len();
+            rest.copy_from_slice(&self.next_u32().to_le_bytes()[..len]);
+        }
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use rand::{Rng, RngCore, SeedableRng};
+
+    #[derive(Clone, Debug, PartialEq)]
+    struct TestResult;
+
+    impl RunResult for TestResult {}
+
+    // A counter-based generator. Each value is a pure function of the counter, so it is the same on every platform.
+    #[derive(Clone, Debug, PartialEq)]
+    struct CounterRng {
+        counter: u64,
+    }
+
+    impl RngCore for CounterRng {
+        fn next_u32(&mut self) -> u32 {
+            self.next_u64() as u32
+        }
+
+        fn next_u64(&mut self) -> u64 {
+            // splitmix64
+            self.counter = self.counter.wrapping_add(0x9e3779b97f4a7c15);
+            let mut z = self.counter;
+            z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+            z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+            z ^ (z >> 31)
+        }
+
+        fn fill_bytes(&mut self, dest: &mut [u8]) {
+            for chunk in dest.chunks_mut(8) {
+                let bytes = self.next_u64().to_le_bytes();
+                chunk.copy_from_slice(&bytes[..chunk.len()]);
+            }
+        }
+
+        fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+            self.fill_bytes(dest);
+            Ok(())
+        }
+    }
+
+    impl SeedableRng for CounterRng {
+        type Seed = [u8; 8];
+
+        fn from_seed(seed: Self::Seed) -> Self {
+            CounterRng { counter: u64::from_le_bytes(seed) }
+        }
+    }
+
+    #[test]
+    fn counter_based_rng() {
+        let mut vm = BaseVm::new(None, Configuration::new_simple());
+        add_base_instructions(&mut vm);
+        add_base_literals(&mut vm);
+        vm.engine_mut().set_rng(EngineRng::new(CounterRng { counter: 0 }));
+        let mut other = vm.clone();
+        assert_eq!(vm.engine(), other.engine());
+
+        // The first value of splitmix64 from zero is a well-known constant
+        assert_eq!(0xe220a8397b1dcdaf, vm.get_rng().next_u64());
+        assert_ne!(vm.engine(), other.engine());
+        assert_eq!(0xe220a8397b1dcdaf, other.get_rng().gen::<u64>());
+
+        let code = vm.engine_mut().rand_code(Some(10)).unwrap();
+        assert_eq!(code, other.engine_mut().rand_code(Some(10)).unwrap());
+        assert_eq!(vm.engine(), other.engine());
+
+        // Code generated from a generation seed uses a generator of the same kind
+        let individual: Individual<TestResult> = vm.engine_mut().rand_initial_individual().unwrap();
+        let seed = individual.get_generation_seed().unwrap();
+        other.engine_mut().set_rng(EngineRng::new(CounterRng::seed_from_u64(seed)));
+        assert_eq!(individual.get_code(), &other.engine_mut().rand_initial_code().unwrap());
+        other.engine_mut().set_rng_seed(Some(seed));
+        assert_ne!(individual.get_code(), &other.engine_mut().rand_initial_code().unwrap());
+
+        // A case seed reseeds the same kind of generator, and the generator is restored afterwards
+        let mut expected = CounterRng::seed_from_u64(7);
+        let before = vm.get_rng().clone();
+        vm.with_case_seed(7, |vm| assert_eq!(expected.next_u64(), vm.get_rng().next_u64()));
+        assert_eq!(&before, vm.get_rng());
+
+        // A different kind of generator is never equal
+        vm.engine_mut().set_rng_seed(Some(1));
+        assert_ne!(vm.engine(), other.engine());
+    }
+}
@@ -0,0 +1,24 @@
+use crate::{Individual, RunResult, VirtualMachine};
+
+/// An Evaluator decides how an individual is scored, separately from the rest of the island's lifecycle. When an island
+/// has an evaluator, it is used to produce each individual's RunResult instead of `IslandCallbacks::run_individual`.
+/// This allows local, cached, parallel or remote evaluation to be swapped without changing the island callbacks.
+pub trait Evaluator<R: RunResult, Vm: VirtualMachine> {
+    fn clone(&self) -> Box<dyn Evaluator<R, Vm>>;
+
+    /// Runs the individual on the virtual machine (or elsewhere) and returns its result. Called once for each individual
+    /// on the island every generation.
+    fn evaluate(&self, vm: &mut Vm, individual: &Individual<R>) -> R;
+}
+
+impl<R: RunResult, Vm: VirtualMachine> Clone for Box<dyn Evaluator<R, Vm>> {
+    fn clone(&self) -> Self {
+        self.as_ref().clone()
+    }
+}
+
+impl<R: RunResult, Vm: VirtualMachine> std::fmt::Debug for Box<dyn Evaluator<R, Vm>> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:p}", self.as_ref())
+    }
+}
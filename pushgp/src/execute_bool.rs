@@ -23,6 +23,8 @@ impl BoolLiteralValue {
 }
 
 impl<Vm: VirtualMachine + VirtualMachineMustHaveBool<Vm>> Instruction<Vm> for BoolLiteralValue {
+    const CATEGORY: InstructionCategory = InstructionCategory::Bool;
+
     fn parse<'a>(input: &'a str, opcode: u32) -> nom::IResult<&'a str, Code> {
         let (rest, value) = crate::parse::parse_code_bool(input)?;
         Ok((rest, Code::new(opcode, value.into())))
@@ -83,6 +85,13 @@ fn flush(vm: &mut Vm) {
     vm.bool().clear();
 }
 
+/// Pops the top item of the CODE stack and pushes TRUE if it is a list, or FALSE if it is a single instruction or a
+/// literal. This is the inverse of CODE.ATOM, except that CODE.ATOM leaves the CODE stack unchanged while this pops it.
+#[stack_instruction(Bool)]
+fn from_code(vm: &mut Vm, code: Code) {
+    vm.bool().push(code.is_list())?;
+}
+
 /// Pushes FALSE if the top FLOAT is 0.0, or TRUE otherwise
 #[stack_instruction(Bool)]
 fn from_float(vm: &mut Vm, f: Float) {
@@ -95,6 +104,26 @@ fn from_int(vm: &mut Vm, i: Integer) {
     vm.bool().push(i != 0)?;
 }
 
+/// Pushes the logical implication of the top two BOOLEANs: FALSE if the second item is TRUE and the top item is FALSE,
+/// or TRUE otherwise. That is, "( A B BOOL.IMPLIES )" pushes "A implies B".
+#[stack_instruction(Bool)]
+fn implies(vm: &mut Vm, b: Bool, a: Bool) {
+    vm.bool().push(!a || b)?;
+}
+
+/// Pushes TRUE if the BOOLEAN stack is empty, or FALSE otherwise.
+#[stack_instruction(Bool)]
+fn is_empty(vm: &mut Vm) {
+    let is_empty = vm.bool().len() == 0;
+    vm.bool().push(is_empty)?;
+}
+
+/// Pushes the logical NAND (not and) of the top two BOOLEANs
+#[stack_instruction(Bool)]
+fn nand(vm: &mut Vm, a: Bool, b: Bool) {
+    vm.bool().push(!(a && b))?;
+}
+
 /// Pushes the logical NOT of the top BOOLEAN
 #[stack_instruction(Bool)]
 fn not(vm: &mut Vm, b: Bool) {
@@ -128,6 +157,8 @@ fn rot(vm: &mut Vm) {
 /// Inserts the top BOOLEAN "deep" in the stack, at the position indexed by the top INTEGER
 #[stack_instruction(Bool)]
 fn shove(vm: &mut Vm, position: Integer) {
+    let len = vm.bool().len();
+    let position = vm.engine().deep_index(position, len);
     vm.bool().shove(position)?;
 }
 
@@ -144,10 +175,18 @@ fn swap(vm: &mut Vm) {
     vm.bool().swap()?;
 }
 
+/// Pushes the logical XOR (exclusive-or) of the top two BOOLEANs
+#[stack_instruction(Bool)]
+fn xor(vm: &mut Vm, a: Bool, b: Bool) {
+    vm.bool().push(a ^ b)?;
+}
+
 /// Pushes a copy of an indexed item "deep" in the stack onto the top of the stack, without removing the deep item.
 /// The index is taken from the INTEGER stack
 #[stack_instruction(Bool)]
 fn yank_dup(vm: &mut Vm, position: Integer) {
+    let len = vm.bool().len();
+    let position = vm.engine().deep_index(position, len);
     vm.bool().yank_duplicate(position)?;
 }
 
@@ -155,5 +194,7 @@ fn yank_dup(vm: &mut Vm, position: Integer) {
 /// INTEGER stack
 #[stack_instruction(Bool)]
 fn yank(vm: &mut Vm, position: Integer) {
+    let len = vm.bool().len();
+    let position = vm.engine().deep_index(position, len);
     vm.bool().yank(position)?;
 }
@@ -16,7 +16,8 @@ fn append(vm: &mut Vm, src: Code, dst: Code) {
 }
 
 /// Pushes TRUE onto the BOOLEAN stack if the top piece of code is a single instruction or a literal, and FALSE
-/// otherwise (that is, if it is something surrounded by parentheses). Does not pop the CODE stack
+/// otherwise (that is, if it is something surrounded by parentheses). Does not pop the CODE stack; use BOOL.FROMCODE to
+/// pop and classify the item instead.
 #[stack_instruction(Code)]
 fn atom(vm: &mut Vm) {
     if let Some(code) = vm.code().peek() {
@@ -92,6 +93,15 @@ fn contains(vm: &mut Vm, look_for: Code, look_in: Code) {
     vm.bool().push(look_in.contains(&look_for))?;
 }
 
+/// Pushes onto the INTEGER stack the number of times the first CODE stack item appears anywhere in the second CODE
+/// stack item (including in sub-lists, and including the second item itself). This is the counting version of
+/// CODE.CONTAINS.
+#[stack_instruction(Code)]
+fn count(vm: &mut Vm, look_for: Code, look_in: Code) {
+    let count = *look_in.discrepancy_items().get(&look_for).unwrap_or(&0);
+    vm.integer().push(count)?;
+}
+
 /// Defines the name on top of the NAME stack as an instruction that will push the top item of the CODE stack onto
 /// the EXEC stack.
 #[stack_instruction(Code)]
@@ -247,6 +257,31 @@ fn _do(vm: &mut Vm, code: Code) {
     vm.code().push(code)?;
 }
 
+/// Pushes what remains of the second item on the CODE stack after dropping the longest prefix whose elements all
+/// satisfy the predicate on top of the CODE stack. The predicate is checked against each element in the same way as
+/// CODE.TAKEWHILE, and dropping stops at the first element for which it leaves FALSE on the BOOLEAN stack. An atom or
+/// an empty list is pushed back unchanged.
+#[stack_instruction(Code)]
+fn drop_while(vm: &mut Vm, predicate: Code, list: Code) {
+    let mut elements = list.to_list();
+    if list.is_atom() || elements.is_empty() {
+        vm.code().push(list)?;
+    } else {
+        let first = elements.remove(0);
+        let drop = Code::new_list(vec![
+            CodeQuote::new_code(vm),
+            Code::new_list(elements)?,
+            CodeQuote::new_code(vm),
+            predicate.clone(),
+            CodeDropWhile::new_code(vm),
+        ])?;
+        let stop = Code::new_list(vec![CodeQuote::new_code(vm), list])?;
+        let next = Code::new_list(vec![predicate, ExecIf::new_code(vm), drop, stop])?;
+        vm.exec().push(next)?;
+        vm.code().push(first)?;
+    }
+}
+
 /// Duplicates the top item on the CODE stack. Does not pop its argument (which, if it did, would negate the effect
 /// of the duplication!).
 #[stack_instruction(Code)]
@@ -282,6 +317,20 @@ fn extract(vm: &mut Vm, code: Code, point: Integer) {
     }
 }
 
+/// Pops a name off the NAME stack and pushes a list of the atoms in the top item of the CODE stack whose instructions
+/// primarily work with the stack of that name. I.E. with the name `INTEGER`, the list keeps literals such as `5` and
+/// instructions such as `INTEGER.SUM`. Atoms in sub-lists are included. This is a NOOP if the name is not a stack.
+#[stack_instruction(Code)]
+fn filter_by_stack(vm: &mut Vm, code: Code, stack: Name) {
+    let category = InstructionCategory::from_stack_name(&stack).ok_or(ExecutionError::IllegalOperation)?;
+    let atoms = code
+        .extract_atoms()
+        .into_iter()
+        .filter(|atom| vm.engine().category_for_opcode(atom.get_opcode()) == Some(category))
+        .collect();
+    vm.code().push(Code::new_list(atoms)?)?;
+}
+
 /// Empties the CODE stack.
 #[stack_instruction(Code)]
 fn flush(vm: &mut Vm) {
@@ -316,6 +365,13 @@ fn from_name(vm: &mut Vm, value: Name) {
     vm.code().push(code)?;
 }
 
+/// Pops the top item of the CODE stack and pushes a hash of it onto the INTEGER stack. Equal code always has an equal
+/// hash, so programs can compare large structures cheaply. See `Code::stable_hash` for the algorithm.
+#[stack_instruction(Code)]
+fn hash(vm: &mut Vm, code: Code) {
+    vm.integer().push(code.stable_hash() as i64)?;
+}
+
 /// If the top item of the BOOLEAN stack is TRUE this recursively executes the second item of the CODE stack;
 /// otherwise it recursively executes the first item of the CODE stack. Either way both elements of the CODE stack
 /// (and the BOOLEAN value upon which the decision was made) are popped.
@@ -324,6 +380,15 @@ fn _if(vm: &mut Vm, false_branch: Code, true_branch: Code, switch_on: Bool) {
     vm.exec().push(if switch_on { true_branch } else { false_branch })?;
 }
 
+/// If the third item of the CODE stack is an empty list this recursively executes the second item of the CODE stack;
+/// otherwise it recursively executes the first item of the CODE stack. An atom is not an empty list, as in CODE.NULL.
+/// All three items are popped. This is a shorter form of "CODE.NULL CODE.IF" for guarding against empty lists.
+#[stack_instruction(Code)]
+fn if_empty(vm: &mut Vm, non_empty_branch: Code, empty_branch: Code, list: Code) {
+    // This relies on the behavior that code.len() returns 1 for atoms
+    vm.exec().push(if 0 == list.len() { empty_branch } else { non_empty_branch })?;
+}
+
 /// Pushes the result of inserting the second item of the CODE stack into the first item, at the position indexed by
 /// the top item of the INTEGER stack (and replacing whatever was there formerly). The indexing is computed as in
 /// CODE.EXTRACT.
@@ -334,6 +399,14 @@ fn insert(vm: &mut Vm, search_in: Code, replace_with: Code, point: Integer) {
     vm.code().push(search_in.replace_point(point, &replace_with)?.0)?;
 }
 
+/// Pushes TRUE onto the BOOLEAN stack if the CODE stack is empty, or FALSE otherwise. Unlike CODE.NULL, which tests
+/// whether the top item is an empty list, this tests the stack itself and so never fails for lack of inputs.
+#[stack_instruction(Code)]
+fn is_empty(vm: &mut Vm) {
+    let is_empty = vm.code().len() == 0;
+    vm.bool().push(is_empty)?;
+}
+
 /// Pushes the length of the top item on the CODE stack onto the INTEGER stack. If the top item is not a list then
 /// this pushes a 1. If the top item is a list then this pushes the number of items in the top level of the list;
 /// that is, nested lists contribute only 1 to this count, no matter what they contain.
@@ -413,6 +486,18 @@ fn position(vm: &mut Vm, look_in: Code, look_for: Code) {
     }
 }
 
+/// Pushes onto the CODE stack a list of the positions of every match of the second item on the CODE stack within the
+/// first item (which is coerced to a list if necessary). Each position is an integer literal. Pushes an empty list if
+/// no match is found.
+#[stack_instruction(Code)]
+fn position_all(vm: &mut Vm, look_in: Code, look_for: Code) {
+    let mut positions = vec![];
+    for index in look_in.positions_of(&look_for) {
+        positions.push(IntegerLiteralValue::new_code(vm, index as i64));
+    }
+    vm.code().push(Code::new_list(positions)?)?;
+}
+
 /// Specifies that the next expression submitted for execution will instead be pushed literally onto the CODE stack.
 /// This can be implemented by moving the top item on the EXEC stack onto the CODE stack.
 #[stack_instruction(Code)]
@@ -429,6 +514,17 @@ fn rand(vm: &mut Vm, points: Integer) {
     vm.code().push(code)?;
 }
 
+/// Pushes the result of replacing the point of the second item of the CODE stack that is indexed by the top item of
+/// the INTEGER stack with the first item of the CODE stack. This is the same operation as CODE.INSERT except that the
+/// replacement is taken from the top of the CODE stack rather than the second item. The indexing is computed as in
+/// CODE.EXTRACT.
+#[stack_instruction(Code)]
+fn replace_at_point(vm: &mut Vm, replace_with: Code, search_in: Code, point: Integer) {
+    let total_points = search_in.points();
+    let point = point.saturating_abs() % total_points;
+    vm.code().push(search_in.replace_point(point, &replace_with)?.0)?;
+}
+
 /// Rotates the top three items on the CODE stack, pulling the third item out and pushing it on top. This is
 /// equivalent to "2 CODE.YANK".
 #[stack_instruction(Code)]
@@ -439,6 +535,8 @@ fn rot(vm: &mut Vm) {
 /// Inserts the top piece of CODE "deep" in the stack, at the position indexed by the top INTEGER.
 #[stack_instruction(Code)]
 fn shove(vm: &mut Vm, position: Integer) {
+    let len = vm.code().len();
+    let position = vm.engine().deep_index(position, len);
     vm.code().shove(position)?;
 }
 
@@ -456,6 +554,20 @@ fn stack_depth(vm: &mut Vm) {
     vm.integer().push(len)?;
 }
 
+/// Pushes the portion of the expression on top of the CODE stack (which is coerced to a list first if necessary) that
+/// lies between two indices taken from the INTEGER stack. The top INTEGER is the end index (exclusive) and the second
+/// INTEGER is the start index (inclusive). Both indices are taken modulo one more than the length of the list so that
+/// the end of the list can be reached. If the start is greater than the end the two are swapped.
+#[stack_instruction(Code)]
+fn sublist(vm: &mut Vm, end: Integer, start: Integer, list: Code) {
+    let list = list.to_list();
+    let positions = list.len() + 1;
+    let start = start.saturating_abs() as usize % positions;
+    let end = end.saturating_abs() as usize % positions;
+    let (start, end) = if start > end { (end, start) } else { (start, end) };
+    vm.code().push(Code::new_list(list[start..end].to_vec())?)?;
+}
+
 /// Pushes the result of substituting the third item on the code stack for the second item in the first item.
 #[stack_instruction(Code)]
 fn substitute(vm: &mut Vm, look_in: Code, look_for: Code, replace_with: Code) {
@@ -468,10 +580,68 @@ fn swap(vm: &mut Vm) {
     vm.code().swap()?;
 }
 
+/// Pushes the longest prefix of the second item on the CODE stack whose elements all satisfy the predicate on top of
+/// the CODE stack. Starting at the front of the list, each element is pushed onto the CODE stack and then the predicate
+/// is executed. The element is taken if the predicate leaves TRUE on the BOOLEAN stack, and taking stops at the first
+/// element for which it leaves FALSE. The elements are checked one at a time through the EXEC stack, so each run of
+/// the predicate counts against the step limit. An atom or an empty list is pushed back unchanged.
+#[stack_instruction(Code)]
+fn take_while(vm: &mut Vm, predicate: Code, list: Code) {
+    let mut elements = list.to_list();
+    if list.is_atom() || elements.is_empty() {
+        vm.code().push(list)?;
+    } else {
+        let first = elements.remove(0);
+        // When the first element is taken, it is consed onto whatever is taken from the rest of the list
+        let take = Code::new_list(vec![
+            CodeQuote::new_code(vm),
+            first.clone(),
+            CodeQuote::new_code(vm),
+            Code::new_list(elements)?,
+            CodeQuote::new_code(vm),
+            predicate.clone(),
+            CodeTakeWhile::new_code(vm),
+            CodeCons::new_code(vm),
+        ])?;
+        let stop = Code::new_list(vec![CodeQuote::new_code(vm), Code::new_list(vec![])?])?;
+        let next = Code::new_list(vec![predicate, ExecIf::new_code(vm), take, stop])?;
+        vm.exec().push(next)?;
+        vm.code().push(first)?;
+    }
+}
+
+/// Treats the top item of the CODE stack as a list of rows, each of which is a list of the same length, and pushes its
+/// transpose: the first row of the result holds the first item of every row, and so on. For example, this turns
+/// "( ( 1 2 ) ( 3 4 ) )" into "( ( 1 3 ) ( 2 4 ) )". Acts as a NOOP (and pops the item) if the item is an atom, if any
+/// row is an atom, or if the rows are not all the same length.
+#[stack_instruction(Code)]
+fn transpose(vm: &mut Vm, matrix: Code) {
+    if !matrix.is_list() {
+        return Err(ExecutionError::IllegalOperation);
+    }
+    let rows = matrix.to_list();
+    if rows.iter().any(|row| !row.is_list()) {
+        return Err(ExecutionError::IllegalOperation);
+    }
+    let rows: Vec<Vec<Code>> = rows.iter().map(|row| row.to_list()).collect();
+    let width = rows.first().map(|row| row.len()).unwrap_or(0);
+    if rows.iter().any(|row| row.len() != width) {
+        return Err(ExecutionError::IllegalOperation);
+    }
+
+    let mut columns = Vec::with_capacity(width);
+    for column in 0..width {
+        columns.push(Code::new_list(rows.iter().map(|row| row[column].clone()).collect())?);
+    }
+    vm.code().push(Code::new_list(columns)?)?;
+}
+
 /// Pushes a copy of an indexed item "deep" in the stack onto the top of the stack, without removing the deep item.
 /// The index is taken from the INTEGER stack.
 #[stack_instruction(Code)]
 fn yank_dup(vm: &mut Vm, position: Integer) {
+    let len = vm.code().len();
+    let position = vm.engine().deep_index(position, len);
     vm.code().yank_duplicate(position)?;
 }
 
@@ -479,5 +649,7 @@ fn yank_dup(vm: &mut Vm, position: Integer) {
 /// INTEGER stack.
 #[stack_instruction(Code)]
 fn yank(vm: &mut Vm, position: Integer) {
+    let len = vm.code().len();
+    let position = vm.engine().deep_index(position, len);
     vm.code().yank(position)?;
 }
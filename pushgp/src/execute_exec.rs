@@ -108,6 +108,15 @@ fn dup(vm: &mut Vm) {
     vm.exec().duplicate_top_item()?;
 }
 
+/// Duplicates the top N items on the EXEC stack, preserving their order, where N is taken from the INTEGER stack. N is
+/// limited to the number of items on the EXEC stack. If N is zero or negative this is a NOOP.
+#[stack_instruction(Exec)]
+fn dup_items(vm: &mut Vm, count: Integer) {
+    if count > 0 {
+        vm.exec().duplicate_top_items(count as usize)?;
+    }
+}
+
 /// Pushes TRUE if the top two items on the EXEC stack are equal, or FALSE otherwise.
 #[stack_instruction(Exec)]
 fn equal(vm: &mut Vm, a: Exec, b: Exec) {
@@ -120,6 +129,16 @@ fn flush(vm: &mut Vm) {
     vm.exec().clear();
 }
 
+/// Empties the EXEC stack if the top item of the BOOLEAN stack is TRUE, or does nothing if it is FALSE. Either way the
+/// BOOLEAN is popped. This lets a program halt early when a condition is met. EXEC.FLUSHIF counts as one step, and the
+/// items it removes are never run so they do not count against the step limit.
+#[stack_instruction(Exec)]
+fn flush_if(vm: &mut Vm, condition: Bool) {
+    if condition {
+        vm.exec().clear();
+    }
+}
+
 /// If the top item of the BOOLEAN stack is TRUE then this removes the second item on the EXEC stack, leaving the
 /// first item to be executed. If it is false then it removes the first item, leaving the second to be executed.
 /// This is similar to CODE.IF except that it operates on the EXEC stack. This acts as a NOOP unless there are at
@@ -139,6 +158,18 @@ fn k(vm: &mut Vm, keep: Exec, _discard: Exec) {
 #[stack_instruction(Exec)]
 fn pop(vm: &mut Vm, _popped: Exec) {}
 
+/// Pushes a newly-generated random program onto the EXEC stack so that it is executed immediately. The limit for the
+/// size of the expression is taken from the INTEGER stack in the same way as CODE.RAND.
+///
+/// The program that is generated depends upon the state of the virtual machine's random number generator, so it is
+/// only reproducible when the seed is fixed. Each item in the generated program counts against the instruction limit
+/// as it is executed, and the program is subject to the same memory limits as any other code.
+#[stack_instruction(Exec, Name)]
+fn rand(vm: &mut Vm, points: Integer) {
+    let code = vm.engine_mut().rand_code(Some(points as usize))?;
+    vm.exec().push(code)?;
+}
+
 /// Rotates the top three items on the EXEC stack, pulling the third item out and pushing it on top. This is
 /// equivalent to "2 EXEC.YANK".
 #[stack_instruction(Exec)]
@@ -150,6 +181,8 @@ fn rot(vm: &mut Vm) {
 /// of as a "DO LATER" instruction.
 #[stack_instruction(Exec)]
 fn shove(vm: &mut Vm, position: Integer) {
+    let len = vm.exec().len();
+    let position = vm.engine().deep_index(position, len);
     vm.exec().shove(position)?;
 }
 
@@ -160,6 +193,15 @@ fn stack_depth(vm: &mut Vm) {
     vm.integer().push(len)?;
 }
 
+/// Pushes onto the INTEGER stack the number of steps that will remain in the budget of the current run once this
+/// instruction completes. Programs can use this to stop looping before they run out of steps. This is a NOOP when the
+/// VirtualMachine is not running.
+#[stack_instruction(Exec)]
+fn steps_remaining(vm: &mut Vm) {
+    let remaining = vm.engine().get_steps_remaining().ok_or(ExecutionError::IllegalOperation)?;
+    vm.integer().push(remaining.min(i64::MAX as usize) as i64)?;
+}
+
 /// Swaps the top two items on the EXEC stack.
 #[stack_instruction(Exec)]
 fn swap(vm: &mut Vm) {
@@ -180,6 +222,8 @@ fn s(vm: &mut Vm, a: Exec, b: Exec, c: Exec) {
 /// The index is taken from the INTEGER stack.
 #[stack_instruction(Exec)]
 fn yank_dup(vm: &mut Vm, position: Integer) {
+    let len = vm.exec().len();
+    let position = vm.engine().deep_index(position, len);
     vm.exec().yank_duplicate(position)?;
 }
 
@@ -187,6 +231,8 @@ fn yank_dup(vm: &mut Vm, position: Integer) {
 /// INTEGER stack. This may be thought of as a "DO SOONER" instruction.
 #[stack_instruction(Exec)]
 fn yank(vm: &mut Vm, position: Integer) {
+    let len = vm.exec().len();
+    let position = vm.engine().deep_index(position, len);
     vm.exec().yank(position)?;
 }
 
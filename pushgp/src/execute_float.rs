@@ -1,3 +1,4 @@
+use crate::numeric_instructions::numeric_instructions;
 use crate::*;
 use pushgp_macros::*;
 use rust_decimal::{
@@ -90,13 +91,21 @@ impl FloatLiteralValue {
 }
 
 impl<Vm: VirtualMachine + VirtualMachineMustHaveFloat<Vm>> Instruction<Vm> for FloatLiteralValue {
+    const CATEGORY: InstructionCategory = InstructionCategory::Float;
+
     fn parse<'a>(input: &'a str, opcode: Opcode) -> nom::IResult<&'a str, Code> {
         let (rest, value) = crate::parse::parse_code_float(input)?;
         Ok((rest, Code::new(opcode, Data::from(value))))
     }
 
-    fn fmt(f: &mut std::fmt::Formatter<'_>, code: &Code, _vtable: &InstructionTable<Vm>) -> std::fmt::Result {
-        if let Some(value) = code.get_data().decimal_value() {
+    fn fmt(f: &mut std::fmt::Formatter<'_>, code: &Code, vtable: &InstructionTable<Vm>) -> std::fmt::Result {
+        if let Some(mut value) = code.get_data().decimal_value() {
+            // Round to the configured precision for display only. Normalize to drop any trailing zeros left over from
+            // rounding.
+            if let Some(precision) = vtable.get_float_display_precision() {
+                value = value.round_dp(precision as u32).normalize();
+            }
+
             // Decimals without a fractional part will parse as an integer
             if value.fract().is_zero() {
                 write!(f, "{}.0", value)
@@ -123,12 +132,22 @@ impl<Vm: VirtualMachine + VirtualMachineMustHaveFloat<Vm>> Instruction<Vm> for F
     }
 }
 
+/// Pushes the absolute value of the top item.
+#[stack_instruction(Float)]
+fn abs(vm: &mut Vm, value: Float) {
+    vm.float().push(value.abs().into())?;
+}
+
 /// Pushes the cosine of the top item.F
 #[stack_instruction(Float)]
 fn cos(vm: &mut Vm, value: Float) {
     vm.float().push(Float { inner: Decimal::from_f64(value.to_f64().unwrap().cos()).unwrap() })?;
 }
 
+// DUP, FLUSH, GREATER, ISEMPTY, LESS, MAX, MIN, POP, ROT, SHOVE, STACKDEPTH, SWAP, YANKDUP and YANK are the
+// same for every numeric stack
+numeric_instructions!(Float, float, "FLOAT");
+
 /// Defines the name on top of the NAME stack as an instruction that will push the top item of the FLOAT stack onto
 /// the EXEC stack.
 #[stack_instruction(Float)]
@@ -137,29 +156,40 @@ fn define(vm: &mut Vm, value: Float, name: Name) {
     vm.engine_mut().define_name(name, code);
 }
 
-/// Pushes the difference of the top two items; that is, the second item minus the top item.
+/// Pushes the difference of the top two items; that is, the second item minus the top item. If the difference is too
+/// large to represent this acts as a NOOP.
 #[stack_instruction(Float)]
 fn difference(vm: &mut Vm, right: Float, left: Float) {
-    vm.float().push(left - right)?;
-}
-
-/// Duplicates the top item on the FLOAT stack. Does not pop its argument (which, if it did, would negate the effect
-/// of the duplication!).
-#[stack_instruction(Float)]
-fn dup(vm: &mut Vm) {
-    vm.float().duplicate_top_item()?;
+    match left.checked_sub(*right) {
+        Some(difference) => vm.float().push(difference.into())?,
+        None => return Err(ExecutionError::IllegalOperation),
+    }
 }
 
-/// Pushes TRUE onto the BOOLEAN stack if the top two items are equal, or FALSE otherwise.
+/// Pushes TRUE if the top two items on the FLOAT stack differ by no more than the configured float equality epsilon,
+/// or FALSE otherwise. The epsilon defaults to zero, which requires the items to be exactly equal.
 #[stack_instruction(Float)]
 fn equal(vm: &mut Vm, a: Float, b: Float) {
-    vm.bool().push(a == b)?;
+    let epsilon = vm.engine().get_configuration().get_float_equality_epsilon();
+    let equal = if epsilon > 0.0 {
+        match (a.checked_sub(*b), Decimal::from_f64(epsilon)) {
+            (Some(difference), Some(epsilon)) => difference.abs() <= epsilon,
+            // The difference is only too large to represent when the items are far apart
+            (None, _) => false,
+            // The epsilon is only too large to represent when it is larger than any difference
+            (Some(_), None) => true,
+        }
+    } else {
+        a == b
+    };
+    vm.bool().push(equal)?;
 }
 
-/// Empties the FLOAT stack.
+/// Pushes e raised to the power of the top item. If the result is too large to represent, the largest FLOAT is pushed.
 #[stack_instruction(Float)]
-fn flush(vm: &mut Vm) {
-    vm.float().clear();
+fn exp(vm: &mut Vm, value: Float) {
+    let exp = Decimal::from_f64(value.to_f64().unwrap().exp()).unwrap_or(Decimal::MAX);
+    vm.float().push(exp.into())?;
 }
 
 /// Pushes 1.0 if the top BOOLEAN is TRUE, or 0.0 if the top BOOLEAN is FALSE.
@@ -174,28 +204,14 @@ fn from_integer(vm: &mut Vm, value: Integer) {
     vm.float().push(Decimal::new(value, 0).into())?;
 }
 
-/// Pushes TRUE onto the BOOLEAN stack if the second item is greater than the top item, or FALSE otherwise.
-#[stack_instruction(Float)]
-fn greater(vm: &mut Vm, right: Float, left: Float) {
-    vm.bool().push(left > right)?;
-}
-
-/// Pushes TRUE onto the BOOLEAN stack if the second item is less than the top item, or FALSE otherwise.
-#[stack_instruction(Float)]
-fn less(vm: &mut Vm, right: Float, left: Float) {
-    vm.bool().push(left < right)?;
-}
-
-/// Pushes the maximum of the top two items.
-#[stack_instruction(Float)]
-fn max(vm: &mut Vm, a: Float, b: Float) {
-    vm.float().push(if a > b { a } else { b })?;
-}
-
-/// Pushes the minimum of the top two items.
+/// Pushes the natural logarithm of the top item. If the top item is zero or negative this acts as a NOOP that consumes
+/// the item, the same as FLOAT.QUOTIENT with a zero divisor.
 #[stack_instruction(Float)]
-fn min(vm: &mut Vm, a: Float, b: Float) {
-    vm.float().push(if a < b { a } else { b })?;
+fn log(vm: &mut Vm, value: Float) {
+    if value.is_zero() || value.is_sign_negative() {
+        return Err(ExecutionError::IllegalOperation);
+    }
+    vm.float().push(Decimal::from_f64(value.to_f64().unwrap().ln()).unwrap().into())?;
 }
 
 /// Pushes the second stack item modulo the top stack item. If the top item is zero this acts as a NOOP. The modulus
@@ -211,14 +227,13 @@ fn modulo(vm: &mut Vm, bottom: Float, top: Float) {
     }
 }
 
-/// Pops the FLOAT stack.
-#[stack_instruction(Float)]
-fn pop(vm: &mut Vm, _popped: Float) {}
-
-/// Pushes the product of the top two items.
+/// Pushes the product of the top two items. If the product is too large to represent this acts as a NOOP.
 #[stack_instruction(Float)]
 fn product(vm: &mut Vm, right: Float, left: Float) {
-    vm.float().push(left * right)?;
+    match left.checked_mul(*right) {
+        Some(product) => vm.float().push(product.into())?,
+        None => return Err(ExecutionError::IllegalOperation),
+    }
 }
 
 /// Pushes the quotient of the top two items; that is, the second item divided by the top item. If the top item is
@@ -240,17 +255,17 @@ fn rand(vm: &mut Vm) {
     vm.execute_immediate::<FloatLiteralValue>(random_value)?;
 }
 
-/// Rotates the top three items on the FLOAT stack, pulling the third item out and pushing it on top. This is
-/// equivalent to "2 FLOAT.YANK".
+/// Pushes -1.0 if the top item is negative, 0.0 if it is zero, or 1.0 if it is positive.
 #[stack_instruction(Float)]
-fn rot(vm: &mut Vm) {
-    vm.float().rotate()?;
-}
-
-/// Inserts the top FLOAT "deep" in the stack, at the position indexed by the top INTEGER.
-#[stack_instruction(Float)]
-fn shove(vm: &mut Vm, position: Integer) {
-    vm.float().shove(position)?;
+fn sign(vm: &mut Vm, value: Float) {
+    let sign = if value.is_zero() {
+        Decimal::ZERO
+    } else if value.is_sign_negative() {
+        Decimal::NEGATIVE_ONE
+    } else {
+        Decimal::ONE
+    };
+    vm.float().push(sign.into())?;
 }
 
 /// Pushes the sine of the top item.
@@ -259,23 +274,22 @@ fn sin(vm: &mut Vm, value: Float) {
     vm.float().push(Decimal::from_f64(value.to_f64().unwrap().sin()).unwrap().into())?;
 }
 
-/// Pushes the stack depth onto the INTEGER stack.
+/// Pushes the square root of the top item. If the top item is negative this acts as a NOOP.
 #[stack_instruction(Float)]
-fn stack_depth(vm: &mut Vm) {
-    let len = vm.float().len() as i64;
-    vm.integer().push(len)?;
+fn sqrt(vm: &mut Vm, value: Float) {
+    if value.is_sign_negative() && !value.is_zero() {
+        return Err(ExecutionError::IllegalOperation);
+    }
+    vm.float().push(Decimal::from_f64(value.to_f64().unwrap().sqrt()).unwrap().into())?;
 }
 
-/// Pushes the sum of the top two items.
+/// Pushes the sum of the top two items. If the sum is too large to represent this acts as a NOOP.
 #[stack_instruction(Float)]
 fn sum(vm: &mut Vm, right: Float, left: Float) {
-    vm.float().push(left + right)?;
-}
-
-/// Swaps the top two BOOLEANs.
-#[stack_instruction(Float)]
-fn swap(vm: &mut Vm) {
-    vm.float().swap()?;
+    match left.checked_add(*right) {
+        Some(sum) => vm.float().push(sum.into())?,
+        None => return Err(ExecutionError::IllegalOperation),
+    }
 }
 
 /// Pushes the tangent of the top item.
@@ -284,16 +298,26 @@ fn tan(vm: &mut Vm, value: Float) {
     vm.float().push(Decimal::from_f64(value.to_f64().unwrap().tan()).unwrap().into())?;
 }
 
-/// Pushes a copy of an indexed item "deep" in the stack onto the top of the stack, without removing the deep item.
-/// The index is taken from the INTEGER stack.
-#[stack_instruction(Float)]
-fn yank_dup(vm: &mut Vm, position: Integer) {
-    vm.float().yank_duplicate(position)?;
-}
+#[cfg(test)]
+mod tests {
+    use crate::*;
 
-/// Removes an indexed item from "deep" in the stack and pushes it on top of the stack. The index is taken from the
-/// INTEGER stack.
-#[stack_instruction(Float)]
-fn yank(vm: &mut Vm, position: Integer) {
-    vm.float().yank(position)?;
+    fn float_equal(config: Configuration, src: &str) -> Vec<Bool> {
+        let mut vm = BaseVm::new(Some(1), config);
+        add_base_instructions(&mut vm);
+        add_base_literals(&mut vm);
+        vm.engine_mut().parse_and_set_code(src).unwrap();
+        vm.run(100);
+        vm.bool().to_vec_bottom_first()
+    }
+
+    #[test]
+    fn float_equality_epsilon() {
+        let src = "( 1.0 1.0005 FLOAT.EQUAL 1.0 1.5 FLOAT.EQUAL 2.0 2.0 FLOAT.EQUAL )";
+        assert_eq!(vec![false, false, true], float_equal(Configuration::new_simple(), src));
+
+        let mut config = Configuration::new_simple();
+        config.set_float_equality_epsilon(0.001);
+        assert_eq!(vec![true, false, true], float_equal(config, src));
+    }
 }
@@ -1,3 +1,4 @@
+use crate::numeric_instructions::numeric_instructions;
 use crate::*;
 use pushgp_macros::*;
 use rust_decimal::prelude::ToPrimitive;
@@ -25,6 +26,8 @@ impl IntegerLiteralValue {
 }
 
 impl<Vm: VirtualMachine + VirtualMachineMustHaveInteger<Vm>> Instruction<Vm> for IntegerLiteralValue {
+    const CATEGORY: InstructionCategory = InstructionCategory::Integer;
+
     fn parse<'a>(input: &'a str, opcode: Opcode) -> nom::IResult<&'a str, Code> {
         let (rest, value) = crate::parse::parse_code_integer(input)?;
         Ok((rest, Code::new(opcode, value.into())))
@@ -53,6 +56,38 @@ impl<Vm: VirtualMachine + VirtualMachineMustHaveInteger<Vm>> Instruction<Vm> for
     }
 }
 
+// DUP, FLUSH, GREATER, ISEMPTY, LESS, MAX, MIN, POP, ROT, SHOVE, STACKDEPTH, SWAP, YANKDUP and YANK are the
+// same for every numeric stack
+numeric_instructions!(Integer, integer, "INTEGER");
+
+/// Pushes the absolute value of the top item. The absolute value of the smallest INTEGER saturates to the largest
+/// INTEGER.
+#[stack_instruction(Integer)]
+fn abs(vm: &mut Vm, value: Integer) {
+    vm.integer().push(value.saturating_abs())?;
+}
+
+/// Pushes the depth of every standard stack onto the INTEGER stack, in the order BOOLEAN, CODE, EXEC, FLOAT, INTEGER
+/// and NAME (so the depth of the NAME stack ends up on top). The depths are measured before any of them are pushed.
+/// This is the same as running each stack's STACKDEPTH instruction in turn, except that the INTEGER depth does not
+/// include the depths pushed before it.
+#[stack_instruction(Integer)]
+fn all_stack_depths(vm: &mut Vm) {
+    let depths =
+        [vm.bool().len(), vm.code().len(), vm.exec().len(), vm.float().len(), vm.integer().len(), vm.name().len()];
+    for depth in depths {
+        vm.integer().push(depth as i64)?;
+    }
+}
+
+/// Pushes the id of the context the program is running in, as set by `VirtualMachineEngine::set_context_id`. Islands
+/// can set different ids so that the same program behaves differently on each island.
+#[stack_instruction(Integer)]
+fn context_id(vm: &mut Vm) {
+    let context_id = vm.engine().get_context_id();
+    vm.integer().push(context_id)?;
+}
+
 /// Defines the name on top of the NAME stack as an instruction that will push the top item of the INTEGER stack
 /// onto the EXEC stack.
 #[stack_instruction(Integer)]
@@ -68,59 +103,24 @@ fn difference(vm: &mut Vm, right: Integer, left: Integer) {
     vm.integer().push(left.saturating_sub(right))?;
 }
 
-/// Duplicates the top item on the INTEGER stack. Does not pop its argument (which, if it did, would negate the
-/// effect of the duplication!).
-#[stack_instruction(Integer)]
-fn dup(vm: &mut Vm) {
-    vm.integer().duplicate_top_item()?;
-}
-
 /// Pushes TRUE if the top two items on the INTEGER stack are equal, or FALSE otherwise.
 #[stack_instruction(Integer)]
 fn equal(vm: &mut Vm, a: Integer, b: Integer) {
     vm.bool().push(a == b)?;
 }
 
-/// Empties the INTEGER stack.
-#[stack_instruction(Integer)]
-fn flush(vm: &mut Vm) {
-    vm.integer().clear();
-}
-
 /// Pushes 1 if the top BOOLEAN is TRUE, or 0 if the top BOOLEAN is FALSE.
 #[stack_instruction(Integer)]
 fn from_boolean(vm: &mut Vm, value: Bool) {
     vm.integer().push(if value { 1 } else { 0 })?;
 }
 
-/// Pushes the result of truncating the top FLOAT.
+/// Pushes the result of truncating the top FLOAT. A FLOAT that is too large to represent as an INTEGER pushes i64::MAX
+/// or i64::MIN depending on its sign.
 #[stack_instruction(Integer)]
 fn from_float(vm: &mut Vm, value: Float) {
-    vm.integer().push(value.to_i64().unwrap())?;
-}
-
-/// Pushes TRUE onto the BOOLEAN stack if the second item is greater than the top item, or FALSE otherwise.
-#[stack_instruction(Integer)]
-fn greater(vm: &mut Vm, right: Integer, left: Integer) {
-    vm.bool().push(left > right)?;
-}
-
-/// Pushes TRUE onto the BOOLEAN stack if the second item is less than the top item, or FALSE otherwise.
-#[stack_instruction(Integer)]
-fn less(vm: &mut Vm, right: Integer, left: Integer) {
-    vm.bool().push(left < right)?;
-}
-
-/// Pushes the maximum of the top two items.
-#[stack_instruction(Integer)]
-fn max(vm: &mut Vm, a: Integer, b: Integer) {
-    vm.integer().push(if a > b { a } else { b })?;
-}
-
-/// Pushes the minimum of the top two items.
-#[stack_instruction(Integer)]
-fn min(vm: &mut Vm, a: Integer, b: Integer) {
-    vm.integer().push(if a < b { a } else { b })?;
+    let saturated = if value.is_sign_negative() { i64::MIN } else { i64::MAX };
+    vm.integer().push(value.to_i64().unwrap_or(saturated))?;
 }
 
 /// Pushes the second stack item modulo the top stack item. If the top item is zero this acts as a NOOP. The modulus
@@ -141,9 +141,24 @@ fn modulo(vm: &mut Vm, divisor: Integer, dividend: Integer) {
     }
 }
 
-/// Pops the INTEGER stack.
+/// Pushes the top item with its sign flipped. Negating the smallest INTEGER saturates to the largest INTEGER.
+#[stack_instruction(Integer)]
+fn negate(vm: &mut Vm, value: Integer) {
+    vm.integer().push(value.saturating_neg())?;
+}
+
+/// Pushes the second stack item raised to the power of the top stack item. If the top item is negative this acts as a
+/// NOOP. If the result is too large to represent, i64::MAX or i64::MIN is pushed depending on the sign of the result.
 #[stack_instruction(Integer)]
-fn pop(vm: &mut Vm, _popped: Integer) {}
+fn pow(vm: &mut Vm, exponent: Integer, base: Integer) {
+    if exponent < 0 {
+        return Err(ExecutionError::IllegalOperation);
+    }
+
+    // Any base other than 0, 1 and -1 saturates long before u32::MAX, so a larger exponent only needs to keep its parity
+    let exponent = u32::try_from(exponent).unwrap_or(u32::MAX - (exponent % 2 == 0) as u32);
+    vm.integer().push(base.saturating_pow(exponent))?;
+}
 
 /// Pushes the product of the top two items.
 #[stack_instruction(Integer)]
@@ -170,49 +185,17 @@ fn rand(vm: &mut Vm) {
     vm.execute_immediate::<IntegerLiteralValue>(random_value)?;
 }
 
-/// Rotates the top three items on the INTEGER stack, pulling the third item out and pushing it on top. This is
-/// equivalent to "2 INTEGER.YANK".
-#[stack_instruction(Integer)]
-fn rot(vm: &mut Vm) {
-    vm.integer().rotate()?;
-}
-
-/// Inserts the second INTEGER "deep" in the stack, at the position indexed by the top INTEGER. The index position
-/// is calculated after the index is removed.
-#[stack_instruction(Integer)]
-fn shove(vm: &mut Vm, position: Integer) {
-    vm.integer().shove(position)?;
-}
-
-/// Pushes the stack depth onto the INTEGER stack (thereby increasing it!).
-#[stack_instruction(Integer)]
-fn stack_depth(vm: &mut Vm) {
-    let len = vm.integer().len() as i64;
-    vm.integer().push(len)?;
-}
-
 /// Pushes the sum of the top two items.
 #[stack_instruction(Integer)]
 fn sum(vm: &mut Vm, a: Integer, b: Integer) {
     vm.integer().push(a.saturating_add(b))?;
 }
 
-/// Swaps the top two INTEGERs.
+/// Replaces the entire INTEGER stack with the sum of all of its items. The sum saturates at the largest or smallest
+/// integer instead of overflowing. Pushes 0 if the stack was empty.
 #[stack_instruction(Integer)]
-fn swap(vm: &mut Vm) {
-    vm.integer().swap()?;
-}
-
-/// Pushes a copy of an indexed item "deep" in the stack onto the top of the stack, without removing the deep item.
-/// The index is taken from the INTEGER stack, and the indexing is done after the index is removed.
-#[stack_instruction(Integer)]
-fn yank_dup(vm: &mut Vm, position: Integer) {
-    vm.integer().yank_duplicate(position)?;
-}
-
-/// Removes an indexed item from "deep" in the stack and pushes it on top of the stack. The index is taken from the
-/// INTEGER stack, and the indexing is done after the index is removed.
-#[stack_instruction(Integer)]
-fn yank(vm: &mut Vm, position: Integer) {
-    vm.integer().yank(position)?;
+fn sum_all(vm: &mut Vm) {
+    let sum = vm.integer().iter().fold(0i64, |sum, item| sum.saturating_add(*item));
+    vm.integer().clear();
+    vm.integer().push(sum)?;
 }
@@ -4,7 +4,7 @@ use byte_slice_cast::*;
 use pushgp_macros::*;
 use smartstring::{LazyCompact, SmartString};
 
-#[derive(Clone, Debug, Eq, Hash, PartialEq, PartialOrd)]
+#[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
 pub struct Name {
     inner: SmartString<LazyCompact>,
 }
@@ -95,6 +95,8 @@ impl NameLiteralValue {
 impl<Vm: VirtualMachine + VirtualMachineMustHaveExec<Vm> + VirtualMachineMustHaveName<Vm>> Instruction<Vm>
     for NameLiteralValue
 {
+    const CATEGORY: InstructionCategory = InstructionCategory::Name;
+
     fn parse<'a>(input: &'a str, opcode: Opcode) -> nom::IResult<&'a str, Code> {
         let (rest, value) = crate::parse::parse_code_name(input)?;
         Ok((rest, Code::new(opcode, value.into())))
@@ -113,8 +115,13 @@ impl<Vm: VirtualMachine + VirtualMachineMustHaveExec<Vm> + VirtualMachineMustHav
         let random_value = engine.get_rng().gen_range(0..=u64::MAX);
 
         let slice: [u64; 1] = [random_value];
-        let b64 = encode(slice.as_byte_slice());
-        let name = SmartString::<LazyCompact>::from("RND.") + &b64;
+        let bytes = slice.as_byte_slice();
+        let config = engine.get_configuration();
+        let encoded = match config.get_random_name_encoding() {
+            RandomNameEncoding::Base64 => encode(bytes),
+            RandomNameEncoding::Hex => bytes.iter().map(|b| format!("{:02x}", b)).collect(),
+        };
+        let name = SmartString::<LazyCompact>::from(config.get_random_name_prefix()) + &encoded;
         NameLiteralValue::new_code(engine, Name::from(name))
     }
 
@@ -138,6 +145,19 @@ impl<Vm: VirtualMachine + VirtualMachineMustHaveExec<Vm> + VirtualMachineMustHav
     }
 }
 
+/// Pushes every name that currently has a definition onto the NAME stack, sorted so that the order is the same from
+/// run to run. The last name in sorted order ends up on top. Stops pushing once the NAME stack is full.
+#[stack_instruction(Name)]
+fn all(vm: &mut Vm) {
+    let mut defined_names = vm.engine().all_defined_names();
+    defined_names.sort();
+    for name in defined_names {
+        if vm.name().push(name).is_err() {
+            break;
+        }
+    }
+}
+
 /// Duplicates the top item on the NAME stack. Does not pop its argument (which, if it did, would negate the effect
 /// of the duplication!).
 #[stack_instruction(Name)]
@@ -157,6 +177,13 @@ fn flush(vm: &mut Vm) {
     vm.name().clear();
 }
 
+/// Pushes TRUE onto the BOOLEAN stack if the NAME stack is empty, or FALSE otherwise.
+#[stack_instruction(Name)]
+fn is_empty(vm: &mut Vm) {
+    let is_empty = vm.name().len() == 0;
+    vm.bool().push(is_empty)?;
+}
+
 /// Pops the NAME stack.
 #[stack_instruction(Name)]
 fn pop(vm: &mut Vm, _popped: Name) {}
@@ -183,9 +210,23 @@ fn rand_bound_name(vm: &mut Vm) {
     }
 }
 
-/// Pushes a newly generated random NAME.
+/// Pushes a newly generated random NAME. If the configuration has a name reuse probability and some names already have
+/// definitions, then with that probability this pushes one of the defined names instead (like NAME.RANDBOUNDNAME).
 #[stack_instruction(Name)]
 fn rand(vm: &mut Vm) {
+    use rand::Rng;
+
+    let reuse_probability = vm.engine().get_configuration().get_name_rand_reuse_probability();
+    if reuse_probability > 0.0 {
+        let defined_names = vm.engine().all_defined_names();
+        if !defined_names.is_empty() && vm.get_rng().gen_bool(reuse_probability) {
+            let pick: usize = vm.get_rng().gen_range(0..defined_names.len());
+            let existing_name = defined_names[pick].clone();
+            vm.name().push(existing_name)?;
+            return Ok(());
+        }
+    }
+
     let random_value = vm.random_value::<NameLiteralValue>();
 
     // Executing this random value literal would alter the 'should_quote_next_name' value, so save and restore it
@@ -206,6 +247,8 @@ fn rot(vm: &mut Vm) {
 /// Inserts the top NAME "deep" in the stack, at the position indexed by the top INTEGER.
 #[stack_instruction(Name)]
 fn shove(vm: &mut Vm, position: Integer) {
+    let len = vm.name().len();
+    let position = vm.engine().deep_index(position, len);
     vm.name().shove(position)?;
 }
 
@@ -226,6 +269,8 @@ fn swap(vm: &mut Vm) {
 /// The index is taken from the INTEGER stack.
 #[stack_instruction(Name)]
 fn yank_dup(vm: &mut Vm, position: Integer) {
+    let len = vm.name().len();
+    let position = vm.engine().deep_index(position, len);
     vm.name().yank_duplicate(position)?;
 }
 
@@ -233,5 +278,66 @@ fn yank_dup(vm: &mut Vm, position: Integer) {
 /// INTEGER stack.
 #[stack_instruction(Name)]
 fn yank(vm: &mut Vm, position: Integer) {
+    let len = vm.name().len();
+    let position = vm.engine().deep_index(position, len);
     vm.name().yank(position)?;
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    fn random_names(config: Configuration, seed: u64) -> Vec<Name> {
+        let mut vm = BaseVm::new(Some(seed), config);
+        add_base_instructions(&mut vm);
+        add_base_literals(&mut vm);
+        vm.engine_mut().parse_and_set_code("( NAME.RAND NAME.RAND NAME.RAND )").unwrap();
+        vm.run(100);
+
+        let mut names = vec![];
+        while let Some(name) = vm.name().pop() {
+            names.push(name);
+        }
+        names
+    }
+
+    #[test]
+    fn random_names_are_determined_by_seed() {
+        let first = random_names(Configuration::new_simple(), 7);
+        let second = random_names(Configuration::new_simple(), 7);
+        assert_eq!(3, first.len());
+        assert_eq!(first, second);
+        assert_ne!(first, random_names(Configuration::new_simple(), 8));
+    }
+
+    #[test]
+    fn random_name_format() {
+        let mut config = Configuration::new_simple();
+        config.set_random_name_format("R_", RandomNameEncoding::Hex);
+        let names = random_names(config, 1);
+        for name in names.iter() {
+            assert!(name.starts_with("R_"));
+            assert_eq!(18, name.len());
+            assert!(name[2..].chars().all(|c| c.is_ascii_hexdigit()));
+        }
+    }
+
+    #[test]
+    fn random_names_reuse_defined_names() {
+        let mut config = Configuration::new_simple();
+        config.set_name_rand_reuse_probability(1.0);
+        let mut vm = BaseVm::new(Some(1), config);
+        add_base_instructions(&mut vm);
+        add_base_literals(&mut vm);
+
+        // With no defined names there is nothing to reuse
+        vm.engine_mut().parse_and_set_code("( NAME.RAND )").unwrap();
+        vm.run(100);
+        assert!(vm.name().pop().unwrap().starts_with("RND."));
+
+        vm.clear();
+        vm.engine_mut().parse_and_set_code("( A 1.0 FLOAT.DEFINE NAME.RAND NAME.RAND )").unwrap();
+        vm.run(100);
+        assert_eq!(vec![Name::from("A"), Name::from("A")], vm.name().to_vec_bottom_first());
+    }
+}
@@ -13,6 +13,9 @@ pub enum ExecutionError {
     /// allocate on a heap that is full. Not recoverable
     OutOfMemory,
 
+    /// The code nested lists more deeply than the configured maximum nesting depth. Not recoverable
+    MaxDepthExceeded,
+
     /// An opcode was used that did not map to a valid instruction. Not recoverable
     InvalidOpcode,
 
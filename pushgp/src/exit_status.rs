@@ -7,7 +7,8 @@ pub enum ExitStatus {
     /// The program ran to the max number of instructions allowed and could have run longer.
     ExceededInstructionCount(ExitStats),
 
-    /// The program used more memory than allowed
+    /// The program used more memory than allowed, such as by pushing onto a full stack. The run stops at the step that
+    /// would have exceeded the limit, and the stacks are left in place so that they can be inspected.
     ExceededMemoryLimit(ExitStats),
 
     /// The program encountered an opcode that was not expected
@@ -0,0 +1,12 @@
+/// Determines how many steps are counted against a program's instruction budget when a list is popped from the Exec
+/// stack and its items are pushed back onto the Exec stack.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ExpansionCost {
+    /// Expanding a list always counts as a single step, no matter how many items it contains. This is the default.
+    Flat,
+
+    /// Expanding a list counts as one step for every item pushed onto the Exec stack (with a minimum of one step for an
+    /// empty list). This makes the step budget better reflect the real work performed by programs with large lists.
+    PerItem,
+}
@@ -0,0 +1,54 @@
+use crate::*;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+
+// The number of steps of random code that are run to put random data onto the stacks before each instruction is tried
+const FILL_STEPS: usize = 200;
+
+/// Checks that none of the instructions in the virtual machine panic. Instructions must only ever return cleanly or
+/// with an `ExecutionError`, but many of them take indices, counts or modulos from the data stacks and it is easy to
+/// miss an edge case.
+///
+/// For each iteration, the stacks of a copy of the virtual machine are filled by running random code. Then every
+/// instruction is executed once, each against its own copy of those stacks, using a random value for the instruction's
+/// own data. The iterations are seeded from `seed`, so a failure can be reproduced. This panics with the name of the
+/// instruction if any instruction panics.
+///
+/// This is only available in tests or with the `test-util` feature, so that crates that add their own instructions can
+/// run it against their virtual machine.
+pub fn fuzz_all_instructions<Vm: VirtualMachine>(vm: &Vm, iterations: usize, seed: u64) {
+    let mut vm = vm.clone();
+    for iteration in 0..iterations {
+        let iteration_seed = seed.wrapping_add(iteration as u64);
+        vm.clear();
+        vm.engine_mut().set_rng_seed(Some(iteration_seed));
+        if let Ok(code) = vm.engine_mut().rand_code(None) {
+            vm.engine_mut().exec().push(code).unwrap();
+            vm.run(FILL_STEPS);
+        }
+        vm.engine_mut().exec().clear();
+
+        let mut opcode = 0;
+        while let Some(name) = vm.engine().name_for_opcode(opcode) {
+            let mut target = vm.clone();
+            let code = target.engine_mut().random_value_for_opcode(opcode).unwrap();
+            let (execute_fn, _timer) = target.engine().execute_fn(opcode).unwrap();
+            if catch_unwind(AssertUnwindSafe(|| execute_fn(code, &mut target))).is_err() {
+                panic!("{} panicked during iteration {} using seed {}", name, iteration, iteration_seed);
+            }
+            opcode += 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn base_instructions_do_not_panic() {
+        let mut vm = BaseVm::new(Some(1), Configuration::new_simple());
+        add_base_instructions(&mut vm);
+        add_base_literals(&mut vm);
+        fuzz_all_instructions(&vm, 300, 1);
+    }
+}
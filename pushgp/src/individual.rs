@@ -1,16 +1,39 @@
-use crate::{Code, Name, RunResult};
+use crate::{Code, Name, RunResult, VirtualMachine};
 use fnv::FnvHashMap;
+use rust_decimal::Decimal;
+use std::sync::OnceLock;
 
-#[derive(Debug, PartialEq)]
+/// An individual's code, the names it has defined, and the result of its most recent run. `PartialEq` compares all
+/// three, so two individuals with identical genomes but different run results are not equal. Use `genome_eq` to compare
+/// only the code and defined names.
+///
+/// An individual can also carry free-form metadata (experiment tags, parent ids, notes, etc). The metadata is kept when
+/// the individual is cloned or migrated, but is ignored by both `PartialEq` and `genome_eq`. The same is true of the
+/// generation seed, which records the seed the individual's code was generated from (see `get_generation_seed`).
+///
+/// The score of the individual is cached the first time it is calculated by `cached_score`, so that sorting a population
+/// does not calculate the score of each individual many times. The cached score is discarded whenever the code, defined
+/// names or run result are replaced or mutably borrowed.
+#[derive(Debug)]
 pub struct Individual<R: RunResult> {
     code: Code,
     defined_names: FnvHashMap<Name, Code>,
     run_result: Option<R>,
+    metadata: FnvHashMap<String, String>,
+    generation_seed: Option<u64>,
+    score: OnceLock<u64>,
 }
 
 impl<R: RunResult> Individual<R> {
     pub fn new(code: Code, defined_names: FnvHashMap<Name, Code>, initial_run_result: Option<R>) -> Individual<R> {
-        Individual { code, defined_names, run_result: initial_run_result }
+        Individual {
+            code,
+            defined_names,
+            run_result: initial_run_result,
+            metadata: FnvHashMap::default(),
+            generation_seed: None,
+            score: OnceLock::new(),
+        }
     }
 
     /// Borrows the Individual's code
@@ -20,6 +43,7 @@ impl<R: RunResult> Individual<R> {
 
     /// Sets the Individual's code to a new value
     pub fn set_code(&mut self, code: Code) {
+        self.score = OnceLock::new();
         self.code = code
     }
 
@@ -30,17 +54,20 @@ impl<R: RunResult> Individual<R> {
 
     /// Mutably borrows the HashMap of names that are defined for this Individual, allowing for changes
     pub fn get_defined_names_mut(&mut self) -> &mut FnvHashMap<Name, Code> {
+        self.score = OnceLock::new();
         &mut self.defined_names
     }
 
     /// Replaces the defined names for this Individual with a specific list
     pub fn set_defined_names(&mut self, defined_names: FnvHashMap<Name, Code>) {
+        self.score = OnceLock::new();
         self.defined_names = defined_names;
     }
 
     /// Examines `names_to_set` and if they are defined in `search_map`, sets the same name in this Individual with a
     /// clone of the code from `search_map`.
     pub fn set_specific_defined_names(&mut self, names_to_set: &[Name], search_map: &FnvHashMap<Name, Code>) {
+        self.score = OnceLock::new();
         for name in names_to_set {
             if let Some(code) = search_map.get(name) {
                 self.defined_names.insert(name.clone(), code.clone());
@@ -48,6 +75,44 @@ impl<R: RunResult> Individual<R> {
         }
     }
 
+    /// Returns true if both individuals have the same code and defined names, ignoring their run results
+    pub fn genome_eq(&self, other: &Individual<R>) -> bool {
+        self.code == other.code && self.defined_names == other.defined_names
+    }
+
+    /// Returns true if both individuals have code and definitions that are `Code::approx_eq`, ignoring their run
+    /// results. This can be used in place of `genome_eq` to treat float-heavy individuals that differ only by tiny
+    /// amounts as duplicates.
+    pub fn genome_approx_eq(&self, other: &Individual<R>, float_eps: Decimal) -> bool {
+        self.code.approx_eq(&other.code, float_eps)
+            && self.defined_names.len() == other.defined_names.len()
+            && self.defined_names.iter().all(|(name, code)| {
+                other.defined_names.get(name).map(|other_code| code.approx_eq(other_code, float_eps)).unwrap_or(false)
+            })
+    }
+
+    /// Returns the Individual as source text that can be read back by the parser. The first line is the program, and
+    /// each defined name follows on its own line, sorted by name and indented by two spaces, as the Push code that
+    /// defines it: `  ( name CODE.QUOTE definition CODE.DEFINE )`.
+    pub fn to_source<Vm: VirtualMachine>(&self, vm: &Vm) -> String {
+        let mut source = format!("{}\n", self.code.for_display(vm));
+        let mut names: Vec<&Name> = self.defined_names.keys().collect();
+        names.sort();
+        for name in names {
+            source.push_str(&format!(
+                "  ( {} CODE.QUOTE {} CODE.DEFINE )\n",
+                name,
+                self.defined_names[name].for_display(vm)
+            ));
+        }
+        source
+    }
+
+    /// Returns false if the Individual has a run result that reports itself as invalid. See `RunResult::is_valid`.
+    pub fn is_valid(&self) -> bool {
+        self.run_result.as_ref().map(|result| result.is_valid()).unwrap_or(true)
+    }
+
     /// Borrows the current RunResult for the Individual
     pub fn get_run_result(&self) -> Option<&R> {
         self.run_result.as_ref()
@@ -55,17 +120,159 @@ impl<R: RunResult> Individual<R> {
 
     /// Mutably borrows the current RunResult for the Individual, allowing for changes to results
     pub fn get_run_result_mut(&mut self) -> Option<&mut R> {
+        self.score = OnceLock::new();
         self.run_result.as_mut()
     }
 
     /// Replaces the RunResult for this Individual
     pub fn set_run_result(&mut self, run_result: Option<R>) {
+        self.score = OnceLock::new();
         self.run_result = run_result;
     }
+
+    // Discards the cached score so that the next call to `cached_score` calculates it again, such as when the
+    // individual is moved to an island that scores it differently
+    pub(crate) fn clear_cached_score(&mut self) {
+        self.score = OnceLock::new();
+    }
+
+    /// Returns the score of this Individual. The first call uses `score_fn` to calculate the score, and later calls
+    /// return the same score until the run result changes.
+    pub fn cached_score<F: FnOnce(&Self) -> u64>(&self, score_fn: F) -> u64 {
+        *self.score.get_or_init(|| score_fn(self))
+    }
+
+    /// Returns the seed that this Individual's code was generated from, if it was generated by
+    /// `VirtualMachineEngine::rand_initial_individual` or `VirtualMachineEngine::rand_child`. Passing the seed to
+    /// `rand_initial_individual_with_seed` or `rand_child_with_seed` (with the same parents, configuration and
+    /// instructions) generates the same code again. Individuals created any other way have no generation seed.
+    pub fn get_generation_seed(&self) -> Option<u64> {
+        self.generation_seed
+    }
+
+    /// Sets the seed that this Individual's code was generated from
+    pub fn set_generation_seed(&mut self, generation_seed: Option<u64>) {
+        self.generation_seed = generation_seed;
+    }
+
+    /// Sets a metadata value for this Individual, replacing any previous value for the same key
+    pub fn set_meta<K: ToString, V: ToString>(&mut self, key: K, value: V) {
+        self.metadata.insert(key.to_string(), value.to_string());
+    }
+
+    /// Borrows the metadata value for the specified key, or None if it has not been set
+    pub fn get_meta(&self, key: &str) -> Option<&String> {
+        self.metadata.get(key)
+    }
 }
 
 impl<R: RunResult> Clone for Individual<R> {
     fn clone(&self) -> Self {
-        Self { code: self.code.clone(), defined_names: self.defined_names.clone(), run_result: self.run_result.clone() }
+        Self {
+            code: self.code.clone(),
+            defined_names: self.defined_names.clone(),
+            run_result: self.run_result.clone(),
+            metadata: self.metadata.clone(),
+            generation_seed: self.generation_seed,
+            score: self.score.clone(),
+        }
+    }
+}
+
+impl<R: RunResult> PartialEq for Individual<R> {
+    fn eq(&self, other: &Self) -> bool {
+        self.genome_eq(other) && self.run_result == other.run_result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use fnv::FnvHashMap;
+    use rust_decimal::Decimal;
+
+    #[derive(Clone, Debug, PartialEq)]
+    struct TestResult(u64);
+
+    impl RunResult for TestResult {}
+
+    #[test]
+    fn genome_eq_ignores_run_result() {
+        let mut vm = BaseVm::new(Some(1), Configuration::new_simple());
+        add_base_instructions(&mut vm);
+        add_base_literals(&mut vm);
+        let code = vm.engine().must_parse("( 1 2 INTEGER.SUM )");
+
+        let a = Individual::new(code.clone(), FnvHashMap::default(), Some(TestResult(1)));
+        let b = Individual::new(code.clone(), FnvHashMap::default(), Some(TestResult(2)));
+        assert!(a.genome_eq(&b));
+        assert_ne!(a, b);
+
+        let mut defined_names = FnvHashMap::default();
+        defined_names.insert("A".into(), vm.engine().must_parse("3"));
+        let c = Individual::new(code, defined_names, Some(TestResult(1)));
+        assert!(!a.genome_eq(&c));
+    }
+
+    #[test]
+    fn genome_approx_eq() {
+        let mut vm = BaseVm::new(Some(1), Configuration::new_simple());
+        add_base_instructions(&mut vm);
+        add_base_literals(&mut vm);
+        let individual = |code, definition| {
+            let mut defined_names = FnvHashMap::default();
+            defined_names.insert("A".into(), vm.engine().must_parse(definition));
+            Individual::<TestResult>::new(vm.engine().must_parse(code), defined_names, None)
+        };
+
+        let eps = Decimal::new(1, 6);
+        let a = individual("( 1.0 A )", "2.5");
+        assert!(a.genome_approx_eq(&individual("( 1.0000000001 A )", "2.5000000001"), eps));
+        assert!(!a.genome_eq(&individual("( 1.0000000001 A )", "2.5")));
+        assert!(!a.genome_approx_eq(&individual("( 1.0 A )", "2.6"), eps));
+    }
+
+    #[test]
+    fn metadata_is_cloned_but_ignored_by_equality() {
+        let mut vm = BaseVm::new(Some(1), Configuration::new_simple());
+        add_base_instructions(&mut vm);
+        add_base_literals(&mut vm);
+        let code = vm.engine().must_parse("( 1 2 INTEGER.SUM )");
+
+        let mut a = Individual::new(code.clone(), FnvHashMap::default(), Some(TestResult(1)));
+        a.set_meta("experiment", "baseline");
+        assert_eq!(Some(&"baseline".to_owned()), a.get_meta("experiment"));
+        assert_eq!(None, a.get_meta("parent"));
+
+        let cloned = a.clone();
+        assert_eq!(Some(&"baseline".to_owned()), cloned.get_meta("experiment"));
+
+        let b = Individual::new(code, FnvHashMap::default(), Some(TestResult(1)));
+        assert!(a.genome_eq(&b));
+        assert_eq!(a, b);
+    }
+
+    #[test]
+    fn mutators_discard_cached_score() {
+        let mut vm = BaseVm::new(Some(1), Configuration::new_simple());
+        add_base_instructions(&mut vm);
+        add_base_literals(&mut vm);
+        let mut individual = Individual::new(vm.engine().must_parse("1"), FnvHashMap::default(), Some(TestResult(1)));
+        let points = |i: &Individual<TestResult>| i.get_code().points() as u64 + i.get_defined_names().len() as u64;
+        assert_eq!(1, individual.cached_score(points));
+
+        individual.set_code(vm.engine().must_parse("( 1 2 )"));
+        assert_eq!(3, individual.cached_score(points));
+
+        let mut defined_names = FnvHashMap::default();
+        defined_names.insert("A".into(), vm.engine().must_parse("3"));
+        individual.set_defined_names(defined_names.clone());
+        assert_eq!(4, individual.cached_score(points));
+
+        individual.get_defined_names_mut().clear();
+        assert_eq!(3, individual.cached_score(points));
+
+        individual.set_specific_defined_names(&["A".into()], &defined_names);
+        assert_eq!(4, individual.cached_score(points));
     }
 }
@@ -7,6 +7,10 @@ use crate::*;
 /// instruction may require the VM to implement VirtualMachineHasBoolStack, VirtualMachineHasCodeStack and
 /// VirtualMachineHasGameState. (VirtualMachineHasGameState being a trait defined in the user's code)
 pub trait Instruction<Vm: VirtualMachine + VirtualMachineMustHaveExec<Vm>>: StaticName {
+    /// The stack that this instruction primarily works with. This allows instructions to be grouped by category
+    /// without calling them. Instructions generated by the `stack_instruction` macro set this automatically.
+    const CATEGORY: InstructionCategory = InstructionCategory::Other;
+
     /// Every instruction must be parsable by 'nom' from a string. While the instruction will know what text to look for
     /// and how to create its data, the opcode will vary from one virtual machine to another, and so it is passed as a
     /// parameter.
@@ -28,3 +32,36 @@ pub trait Instruction<Vm: VirtualMachine + VirtualMachineMustHaveExec<Vm>>: Stat
     /// panic and may only update the state of the virtual machine. The 'Code' object is consumed by this call.
     fn execute(code: Code, vm: &mut Vm) -> Result<(), ExecutionError>;
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use pushgp_macros::*;
+
+    /// Pushes the sum of the top two integers under a lower case stack prefix joined with an underscore.
+    #[stack_instruction(Integer, separator = "_", prefix = "integer")]
+    fn add(vm: &mut Vm, a: Integer, b: Integer) {
+        vm.integer().push(a.wrapping_add(b))?;
+    }
+
+    /// Pushes TRUE if the top two booleans are both TRUE.
+    #[stack_instruction(Bool, separator = "::")]
+    fn both(vm: &mut Vm, a: Bool, b: Bool) {
+        vm.bool().push(a && b)?;
+    }
+
+    #[test]
+    fn custom_instruction_names() {
+        assert_eq!("integer_ADD", IntegerAdd::static_name());
+        assert_eq!("BOOL::BOTH", BoolBoth::static_name());
+
+        let mut vm = BaseVm::new(Some(1), Configuration::new_simple());
+        vm.engine_mut().add_instruction::<IntegerAdd>();
+        vm.engine_mut().add_instruction::<BoolBoth>();
+        add_base_literals(&mut vm);
+        vm.engine_mut().parse_and_set_code("( 1 2 integer_ADD TRUE TRUE BOOL::BOTH )").unwrap();
+        vm.run(100);
+        assert_eq!(vec![3], vm.integer().to_vec_bottom_first());
+        assert_eq!(vec![true], vm.bool().to_vec_bottom_first());
+    }
+}
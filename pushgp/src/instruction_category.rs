@@ -0,0 +1,81 @@
+/// A small integer tag for the stack that an instruction primarily works with. The `stack_instruction` macro uses the
+/// first stack named in its attribute, so `#[stack_instruction(Bool)]` instructions are tagged `Bool`. The values are
+/// fixed so that the tags stay the same from one build to the next.
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum InstructionCategory {
+    /// Instructions that work with a stack defined outside of this crate, or that do not declare a stack
+    Other = 0,
+    Bool = 1,
+    Code = 2,
+    Exec = 3,
+    Float = 4,
+    Integer = 5,
+    Name = 6,
+}
+
+impl InstructionCategory {
+    /// Returns the category for a stack name as it appears at the start of instruction names, such as "INTEGER" for
+    /// `INTEGER.SUM`. Returns None for any other name, including the names of stacks defined outside of this crate.
+    pub fn from_stack_name(name: &str) -> Option<InstructionCategory> {
+        match name {
+            "BOOL" => Some(InstructionCategory::Bool),
+            "CODE" => Some(InstructionCategory::Code),
+            "EXEC" => Some(InstructionCategory::Exec),
+            "FLOAT" => Some(InstructionCategory::Float),
+            "INTEGER" => Some(InstructionCategory::Integer),
+            "NAME" => Some(InstructionCategory::Name),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    fn category_of<I: Instruction<BaseVm>>() -> InstructionCategory {
+        I::CATEGORY
+    }
+
+    #[test]
+    fn category_tags_are_stable() {
+        assert_eq!(0, InstructionCategory::Other as u8);
+        assert_eq!(1, InstructionCategory::Bool as u8);
+        assert_eq!(2, InstructionCategory::Code as u8);
+        assert_eq!(3, InstructionCategory::Exec as u8);
+        assert_eq!(4, InstructionCategory::Float as u8);
+        assert_eq!(5, InstructionCategory::Integer as u8);
+        assert_eq!(6, InstructionCategory::Name as u8);
+    }
+
+    #[test]
+    fn category_matches_primary_stack() {
+        assert_eq!(InstructionCategory::Bool, category_of::<BoolAnd>());
+        assert_eq!(InstructionCategory::Bool, category_of::<BoolLiteralValue>());
+        assert_eq!(InstructionCategory::Code, category_of::<CodeAppend>());
+        assert_eq!(InstructionCategory::Exec, category_of::<ExecDup>());
+        assert_eq!(InstructionCategory::Exec, category_of::<PushList>());
+        assert_eq!(InstructionCategory::Float, category_of::<FloatSum>());
+        assert_eq!(InstructionCategory::Integer, category_of::<IntegerSum>());
+        assert_eq!(InstructionCategory::Name, category_of::<NameLiteralValue>());
+
+        // Instructions that use another stack are tagged by the first stack in their attribute
+        assert_eq!(InstructionCategory::Bool, category_of::<BoolFromCode>());
+
+        let mut vm = BaseVm::new(Some(1), Configuration::new_simple());
+        add_base_instructions(&mut vm);
+        add_base_literals(&mut vm);
+        let opcode = vm.opcode_for_name("INTEGER.SUM").unwrap();
+        assert_eq!(Some(InstructionCategory::Integer), vm.engine().category_for_opcode(opcode));
+        assert_eq!(Some(InstructionCategory::Exec), vm.engine().category_for_opcode(0));
+    }
+
+    #[test]
+    fn category_from_stack_name() {
+        assert_eq!(Some(InstructionCategory::Integer), InstructionCategory::from_stack_name("INTEGER"));
+        assert_eq!(Some(InstructionCategory::Bool), InstructionCategory::from_stack_name("BOOL"));
+        assert_eq!(None, InstructionCategory::from_stack_name("integer"));
+        assert_eq!(None, InstructionCategory::from_stack_name("OTHER"));
+    }
+}
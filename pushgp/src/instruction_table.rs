@@ -6,7 +6,10 @@ use prometheus::{
 };
 use quanta::Clock;
 
-use crate::{Code, CodeParser, ExecutionError, Instruction, Opcode, PushList, VirtualMachine, VirtualMachineEngine};
+use crate::{
+    Code, CodeParser, ExecutionError, Instruction, InstructionCategory, Opcode, PushList, VirtualMachine,
+    VirtualMachineEngine,
+};
 
 pub type NameFn = fn() -> &'static str;
 pub type ParseFn = fn(input: &str, opcode: Opcode) -> nom::IResult<&str, Code>;
@@ -45,8 +48,11 @@ pub struct InstructionTable<Vm: VirtualMachine> {
     fmt_functions: Vec<FmtFn<Vm>>,
     random_value_functions: Vec<RandomValueFn<Vm>>,
     execute_functions: Vec<ExecuteEntry<Vm>>,
+    categories: Vec<InstructionCategory>,
     lookup_opcode_by_name: FnvHashMap<&'static str, Opcode>,
     clock: Clock,
+    float_display_precision: Option<usize>,
+    max_name_length: usize,
 }
 
 pub trait OpcodeConvertor {
@@ -62,8 +68,11 @@ impl<Vm: VirtualMachine> InstructionTable<Vm> {
             fmt_functions: vec![],
             random_value_functions: vec![],
             execute_functions: vec![],
+            categories: vec![],
             lookup_opcode_by_name: FnvHashMap::default(),
             clock: Clock::new(),
+            float_display_precision: None,
+            max_name_length: usize::MAX,
         };
 
         instructions.add_instruction::<PushList>();
@@ -87,6 +96,7 @@ impl<Vm: VirtualMachine> InstructionTable<Vm> {
             instruction_count_metric: INSTRUCTION_COUNTER_VEC.get_metric_with_label_values(&[name]).unwrap(),
             instruction_duration: INSTRUCTION_TIME_VEC.get_metric_with_label_values(&[name]).unwrap(),
         });
+        self.categories.push(I::CATEGORY);
         self.lookup_opcode_by_name.insert(name, opcode);
 
         opcode
@@ -102,6 +112,33 @@ impl<Vm: VirtualMachine> InstructionTable<Vm> {
         }
     }
 
+    /// Returns the number of decimal places used when formatting float literals, or None for full precision
+    pub fn get_float_display_precision(&self) -> Option<usize> {
+        self.float_display_precision
+    }
+
+    /// Sets the number of decimal places used when formatting float literals. This only affects formatting; the value
+    /// stored in the Code is unchanged.
+    pub fn set_float_display_precision(&mut self, precision: Option<usize>) {
+        self.float_display_precision = precision;
+    }
+
+    /// Returns the length of the longest name that will be parsed
+    pub fn get_max_name_length(&self) -> usize {
+        self.max_name_length
+    }
+
+    /// Sets the length of the longest name that will be parsed. A longer name fails the parse as soon as it is read,
+    /// without parsing the rest of the input.
+    pub fn set_max_name_length(&mut self, max_name_length: usize) {
+        self.max_name_length = max_name_length;
+    }
+
+    /// Returns the category of the instruction with the specified opcode or None
+    pub fn category_for_opcode(&self, opcode: Opcode) -> Option<InstructionCategory> {
+        self.categories.get(opcode as usize).copied()
+    }
+
     /// Returns the random value fn pointer for the specified opcode or None
     pub fn random_value_fn(&self, opcode: Opcode) -> Option<RandomValueFn<Vm>> {
         self.random_value_functions.get(opcode as usize).map(|f| *f)
@@ -123,6 +160,9 @@ impl<Vm: VirtualMachine> CodeParser for InstructionTable<Vm> {
         for (index, parse_fn) in self.parse_functions.iter().enumerate().skip(1) {
             let opcode = index as Opcode;
             match parse_fn(input, opcode) {
+                Ok((_, code)) if code.get_data().name_value().is_some_and(|name| name.len() > self.max_name_length) => {
+                    return Err(nom::Err::Failure(nom::error::make_error(input, nom::error::ErrorKind::TooLarge)))
+                }
                 Ok((rest, code)) => return Ok((rest, code)),
                 Err(_) => {
                     // Continue searching
@@ -1,27 +1,124 @@
-use crate::{Individual, IslandCallbacks, RunResult, SelectionCurve, VirtualMachine};
+use crate::{Evaluator, Individual, IslandCallbacks, RunResult, SelectionCurve, VirtualMachine};
+use fnv::FnvHashSet;
+use rand::Rng;
 
 #[derive(Clone, Debug)]
 pub struct Island<R: RunResult, Vm: VirtualMachine> {
     functions: Box<dyn IslandCallbacks<R, Vm>>,
+    evaluator: Option<Box<dyn Evaluator<R, Vm>>>,
     individuals: Vec<Individual<R>>,
     individuals_are_sorted: bool,
+    invalid_individuals: usize,
     future: Vec<Individual<R>>,
+    frozen: bool,
+    init_seed: Option<u64>,
+    hall_of_fame: Vec<Individual<R>>,
+    hall_of_fame_size: usize,
+    weight: f64,
+    context_id: Option<i64>,
 }
 
 impl<R: RunResult, Vm: VirtualMachine> Island<R, Vm> {
-    pub(crate) fn new(callbacks: Box<dyn IslandCallbacks<R, Vm>>) -> Island<R, Vm> {
-        Island { functions: callbacks, individuals: vec![], individuals_are_sorted: false, future: vec![] }
+    pub(crate) fn new(callbacks: Box<dyn IslandCallbacks<R, Vm>>, hall_of_fame_size: usize) -> Island<R, Vm> {
+        Island {
+            functions: callbacks,
+            evaluator: None,
+            individuals: vec![],
+            individuals_are_sorted: false,
+            invalid_individuals: 0,
+            future: vec![],
+            frozen: false,
+            init_seed: None,
+            hall_of_fame: vec![],
+            hall_of_fame_size,
+            weight: 1.0,
+            context_id: None,
+        }
     }
 
     /// Resets the island to it's 'new' state.
     pub fn clear(&mut self) {
+        self.reset_preserving_hof();
+        self.hall_of_fame.clear();
+    }
+
+    /// Removes all individuals from the island, but keeps the hall of fame.
+    pub fn reset_preserving_hof(&mut self) {
         self.individuals.clear();
         self.individuals_are_sorted = false;
+        self.invalid_individuals = 0;
         self.future.clear();
     }
 
+    /// Borrows the most fit individuals ever seen on this island, sorted from least fit to most fit in the same way as
+    /// the current generation. Individuals are added after every generation is run and sorted.
+    pub fn hall_of_fame(&self) -> &[Individual<R>] {
+        &self.hall_of_fame[..]
+    }
+
+    /// Sets the maximum number of individuals kept in the hall of fame. Zero disables the hall of fame.
+    pub fn set_hall_of_fame_size(&mut self, hall_of_fame_size: usize) {
+        self.hall_of_fame_size = hall_of_fame_size;
+        self.truncate_hall_of_fame();
+    }
+
+    /// Sets the Evaluator used to produce the RunResult of each individual. When an evaluator is set, it is used in
+    /// place of `IslandCallbacks::run_individual`. Pass None to go back to using the callbacks.
+    pub fn set_evaluator(&mut self, evaluator: Option<Box<dyn Evaluator<R, Vm>>>) {
+        self.evaluator = evaluator;
+    }
+
+    /// Freezes or thaws the island. A frozen island is skipped by the World when running and filling generations and
+    /// when migrating, so its population stays exactly as it was when it was frozen.
+    pub fn set_frozen(&mut self, frozen: bool) {
+        self.frozen = frozen;
+    }
+
+    /// Returns true if the island is currently frozen
+    pub fn is_frozen(&self) -> bool {
+        self.frozen
+    }
+
+    /// Sets how often this island is picked as the destination for migrants by `MigrationAlgorithm::Weighted`, relative to
+    /// the other islands. The default weight is 1.0. Negative weights are treated as zero. Weights that do not add up to
+    /// a finite number stop weighted migration; see `World::validate_migration`.
+    pub fn set_weight(&mut self, weight: f64) {
+        self.weight = weight;
+    }
+
+    /// Returns the weight of this island as a migration destination
+    pub fn get_weight(&self) -> f64 {
+        self.weight
+    }
+
+    /// Sets the context id that this island gives the VM before running its individuals. Programs can read it with
+    /// INTEGER.CONTEXTID, which lets the same program behave differently on each island for coevolution. An island
+    /// without a context id runs its individuals with a context id of zero.
+    pub fn set_context_id(&mut self, context_id: i64) {
+        self.context_id = Some(context_id);
+    }
+
+    /// Returns the context id this island gives the VM, if one was set
+    pub fn get_context_id(&self) -> Option<i64> {
+        self.context_id
+    }
+
+    /// Sets the seed used to generate this island's initial random population. Islands with an init seed generate
+    /// their initial population from their own random number generator, independent of the VM's shared one, so the
+    /// population is reproducible no matter how many other islands there are.
+    pub fn set_init_seed(&mut self, seed: u64) {
+        self.init_seed = Some(seed);
+    }
+
+    /// Returns the seed used to generate this island's initial population, if one was set
+    pub fn get_init_seed(&self) -> Option<u64> {
+        self.init_seed
+    }
+
     /// Returns the most fit of all the individuals (the one sorted to the tail by the sorting algorithm). Returns None
-    /// if there are no Individuals or if the individuals have not been sorted
+    /// if there are no Individuals or if the individuals have not been sorted. The individuals are sorted once after each
+    /// generation is run, so this does not need to scan the population and is cheap to call every generation. Advancing
+    /// or resetting the generation invalidates the sort until the next generation is run.
     pub fn most_fit_individual(&self) -> Option<&Individual<R>> {
         if !self.individuals_are_sorted {
             return None;
@@ -48,10 +145,11 @@ impl<R: RunResult, Vm: VirtualMachine> Island<R, Vm> {
     pub fn run_one_generation(&mut self, vm: &mut Vm) {
         // Allow the island to set up for all runs
         self.functions.pre_generation_run(&self.individuals);
+        self.set_vm_context_id(vm);
 
         // Run each individual
         for individual in self.individuals.iter_mut() {
-            self.functions.run_individual(vm, individual);
+            run_individual(self.functions.as_mut(), self.evaluator.as_deref(), vm, individual);
         }
 
         // Allow the island to before any cleanup or group analysis tasks
@@ -59,16 +157,113 @@ impl<R: RunResult, Vm: VirtualMachine> Island<R, Vm> {
 
         // Sort the individuals
         self.sort_individuals();
+        self.update_hall_of_fame();
+    }
+
+    /// Starts a generation without running any of the individuals, for lazy evaluation. The run results of the previous
+    /// generation are discarded, and each individual is only run when `run_individual_at` is called for it (which
+    /// `select_one_individual_lazily` does). Because the generation is never run as a whole, `post_generation_run` is
+    /// not called, the individuals are not sorted and the hall of fame is not updated.
+    pub fn start_lazy_generation(&mut self, vm: &mut Vm) {
+        self.functions.pre_generation_run(&self.individuals);
+        self.set_vm_context_id(vm);
+        for individual in self.individuals.iter_mut() {
+            individual.set_run_result(None);
+        }
+        self.individuals_are_sorted = false;
+        self.invalid_individuals = 0;
     }
 
-    /// Sorts the individuals by calling the sorter function.
+    /// Runs the individual at the index if it does not have a run result yet. Returns false if the index is out of range.
+    pub fn run_individual_at(&mut self, index: usize, vm: &mut Vm) -> bool {
+        if index >= self.individuals.len() {
+            return false;
+        }
+        if self.individuals[index].get_run_result().is_none() {
+            // Lazy runs happen after every island has started its generation, so the VM may hold another island's
+            // context id
+            self.set_vm_context_id(vm);
+            run_individual(self.functions.as_mut(), self.evaluator.as_deref(), vm, &mut self.individuals[index]);
+        }
+        true
+    }
+
+    // Gives the VM this island's context id, or the default of zero if it does not have one
+    fn set_vm_context_id(&self, vm: &mut Vm) {
+        vm.engine_mut().set_context_id(self.context_id.unwrap_or(0));
+    }
+
+    /// Selects an individual by a tournament between `tournament_size` individuals picked at random, for use with lazy
+    /// evaluation. Only the individuals in the tournament are run (if they have not been run already), and the most fit
+    /// of them wins. Returns the index of the winner, or None if the island has no individuals.
+    pub fn select_one_individual_lazily(&mut self, tournament_size: usize, vm: &mut Vm) -> Option<usize> {
+        if self.individuals.is_empty() {
+            return None;
+        }
+
+        let mut winner: Option<usize> = None;
+        for _ in 0..tournament_size.max(1) {
+            let index = vm.get_rng().gen_range(0..self.individuals.len());
+            self.run_individual_at(index, vm);
+            winner = match winner {
+                Some(best)
+                    if compare_individuals(
+                        self.functions.as_ref(),
+                        &self.individuals[index],
+                        &self.individuals[best],
+                    ) != std::cmp::Ordering::Greater =>
+                {
+                    Some(best)
+                }
+                _ => Some(index),
+            };
+        }
+        winner
+    }
+
+    /// Removes the individual at the index from the current generation, or returns None if the index is out of range
+    pub fn remove_individual(&mut self, index: usize) -> Option<Individual<R>> {
+        if index < self.individuals.len() {
+            if index < self.invalid_individuals {
+                self.invalid_individuals -= 1;
+            }
+            Some(self.individuals.remove(index))
+        } else {
+            None
+        }
+    }
+
+    /// Sorts the individuals by calling the sorter function. Invalid individuals (see `RunResult::is_valid`) are always
+    /// sorted ahead of the valid ones, as if they were less fit.
     pub fn sort_individuals(&mut self) {
         // It is useful to swap the Vec into a local variable to avoid borrow-checking issues during the sort
         let mut local_individuals = vec![];
         std::mem::swap(&mut self.individuals, &mut local_individuals);
-        local_individuals.sort_by(|a, b| self.functions.sort_individuals(a, b));
+        local_individuals.sort_by(|a, b| compare_individuals(self.functions.as_ref(), a, b));
         std::mem::swap(&mut self.individuals, &mut local_individuals);
         self.individuals_are_sorted = true;
+        self.invalid_individuals = self.individuals.iter().take_while(|individual| !individual.is_valid()).count();
+    }
+
+    // Adds the most fit of the current (sorted) individuals to the hall of fame, skipping any whose genome is already
+    // there, and then drops the least fit until the hall of fame is the right size.
+    fn update_hall_of_fame(&mut self) {
+        for individual in self.individuals.iter().rev().take(self.hall_of_fame_size) {
+            if !self.hall_of_fame.iter().any(|famous| famous.genome_eq(individual)) {
+                self.hall_of_fame.push(individual.clone());
+            }
+        }
+
+        let functions = &self.functions;
+        self.hall_of_fame.sort_by(|a, b| compare_individuals(functions.as_ref(), a, b));
+        self.truncate_hall_of_fame();
+    }
+
+    fn truncate_hall_of_fame(&mut self) {
+        if self.hall_of_fame.len() > self.hall_of_fame_size {
+            let excess = self.hall_of_fame.len() - self.hall_of_fame_size;
+            self.hall_of_fame.drain(..excess);
+        }
     }
 
     /// Returns the current number of individuals on the island.
@@ -76,6 +271,36 @@ impl<R: RunResult, Vm: VirtualMachine> Island<R, Vm> {
         self.individuals.len()
     }
 
+    /// Returns how many individuals of the current generation fall into each range of program sizes, which is useful for
+    /// watching the population bloat over time. The individual's size is the number of points in its code, and bucket
+    /// `i` counts the sizes from `i * bucket_width` up to (but not including) `(i + 1) * bucket_width`. The histogram
+    /// ends at the bucket of the largest individual. Panics if the bucket_width is zero.
+    pub fn size_histogram(&self, bucket_width: usize) -> Vec<usize> {
+        assert!(bucket_width > 0, "the bucket_width must be greater than zero");
+        let mut histogram = vec![];
+        for individual in self.individuals.iter() {
+            let bucket = individual.get_code().points() as usize / bucket_width;
+            if bucket >= histogram.len() {
+                histogram.resize(bucket + 1, 0);
+            }
+            histogram[bucket] += 1;
+        }
+        histogram
+    }
+
+    /// Returns the genotypic diversity of the current generation: the number of unique programs divided by the number of
+    /// individuals. A population with no duplicate programs has a diversity of 1.0, and the diversity falls towards zero
+    /// as the population fills with clones. An empty island has a diversity of 1.0. Programs are told apart by their
+    /// `Code::stable_hash`, so the value is the same on every run and can be logged and compared between runs.
+    pub fn genotypic_diversity(&self) -> f64 {
+        if self.individuals.is_empty() {
+            return 1.0;
+        }
+        let unique: FnvHashSet<u64> =
+            self.individuals.iter().map(|individual| individual.get_code().stable_hash()).collect();
+        unique.len() as f64 / self.individuals.len() as f64
+    }
+
     /// Returns the number of individuals in the next generation
     pub fn len_future_generation(&self) -> usize {
         self.future.len()
@@ -85,6 +310,7 @@ impl<R: RunResult, Vm: VirtualMachine> Island<R, Vm> {
     pub fn advance_generation(&mut self) {
         self.individuals.clear();
         self.individuals_are_sorted = false;
+        self.invalid_individuals = 0;
         std::mem::swap(&mut self.individuals, &mut self.future);
     }
 
@@ -99,12 +325,8 @@ impl<R: RunResult, Vm: VirtualMachine> Island<R, Vm> {
             return None;
         }
 
-        let max = self.individuals.len();
-        if max == 0 {
-            None
-        } else {
-            self.individuals.get(curve.pick_one_index(rng, max))
-        }
+        let index = self.pick_selectable_index(curve, rng)?;
+        self.individuals.get(index)
     }
 
     /// Select one individual from the island according to the specified SelectionCurve and remove it permanently.
@@ -118,11 +340,19 @@ impl<R: RunResult, Vm: VirtualMachine> Island<R, Vm> {
             return None;
         }
 
-        let max = self.individuals.len();
+        let index = self.pick_selectable_index(curve, rng)?;
+        Some(self.individuals.remove(index))
+    }
+
+    // Picks the index of an individual according to the curve. Only the valid individuals at the end of the sorted
+    // population can be picked, unless every individual is invalid.
+    fn pick_selectable_index<Rnd: rand::Rng>(&self, curve: SelectionCurve, rng: &mut Rnd) -> Option<usize> {
+        let first = if self.invalid_individuals < self.individuals.len() { self.invalid_individuals } else { 0 };
+        let max = self.individuals.len() - first;
         if max == 0 {
             None
         } else {
-            Some(self.individuals.remove(curve.pick_one_index(rng, max)))
+            Some(first + curve.pick_one_index(rng, max))
         }
     }
 
@@ -131,10 +361,27 @@ impl<R: RunResult, Vm: VirtualMachine> Island<R, Vm> {
         self.future.push(individual);
     }
 
+    // Moves the current and future generations of the other island onto this one. The combined current generation is
+    // sorted by this island's callbacks and the least fit are dropped until there are no more than `capacity` left.
+    pub(crate) fn merge(&mut self, other: Island<R, Vm>, capacity: usize) {
+        for mut individual in other.individuals {
+            individual.clear_cached_score();
+            self.individuals.push(individual);
+        }
+        self.future.extend(other.future);
+
+        self.sort_individuals();
+        if self.individuals.len() > capacity {
+            let excess = self.individuals.len() - capacity;
+            self.individuals.drain(..excess);
+            self.invalid_individuals = self.invalid_individuals.saturating_sub(excess);
+        }
+    }
+
     /// Returns the score for the individual specified by index, or None if the index is out of bounds
     pub fn score_for_individual(&self, index: usize) -> Option<u64> {
         if let Some(individual) = self.get_one_individual(index) {
-            Some(self.functions.score_individual(individual))
+            Some(individual.cached_score(|i| self.functions.score_individual(i)))
         } else {
             None
         }
@@ -144,8 +391,51 @@ impl<R: RunResult, Vm: VirtualMachine> Island<R, Vm> {
 impl<R: RunResult, Vm: VirtualMachine> PartialEq for Island<R, Vm> {
     fn eq(&self, other: &Self) -> bool {
         self.functions.as_ref() as *const _ == other.functions.as_ref() as *const _
+            && same_evaluator(&self.evaluator, &other.evaluator)
             && self.individuals == other.individuals
             && self.individuals_are_sorted == other.individuals_are_sorted
+            && self.invalid_individuals == other.invalid_individuals
             && self.future == other.future
+            && self.frozen == other.frozen
+            && self.init_seed == other.init_seed
+            && self.hall_of_fame == other.hall_of_fame
+            && self.hall_of_fame_size == other.hall_of_fame_size
+            && self.weight == other.weight
+            && self.context_id == other.context_id
+    }
+}
+
+// Runs the individual with the evaluator if there is one, or otherwise the island's callbacks
+fn run_individual<R: RunResult, Vm: VirtualMachine>(
+    functions: &mut dyn IslandCallbacks<R, Vm>,
+    evaluator: Option<&dyn Evaluator<R, Vm>>,
+    vm: &mut Vm,
+    individual: &mut Individual<R>,
+) {
+    if let Some(evaluator) = evaluator {
+        individual.set_run_result(Some(evaluator.evaluate(vm, individual)));
+    } else {
+        functions.run_individual(vm, individual);
+    }
+}
+
+// Compares two individuals using the island's sort, except that an invalid individual is always less fit than a valid one
+fn compare_individuals<R: RunResult, Vm: VirtualMachine>(
+    functions: &dyn IslandCallbacks<R, Vm>,
+    a: &Individual<R>,
+    b: &Individual<R>,
+) -> std::cmp::Ordering {
+    a.is_valid().cmp(&b.is_valid()).then_with(|| functions.sort_individuals(a, b))
+}
+
+// Two islands only have the same evaluator if they share the same evaluator object, or neither has one
+fn same_evaluator<R: RunResult, Vm: VirtualMachine>(
+    a: &Option<Box<dyn Evaluator<R, Vm>>>,
+    b: &Option<Box<dyn Evaluator<R, Vm>>>,
+) -> bool {
+    match (a, b) {
+        (Some(a), Some(b)) => std::ptr::addr_eq(a.as_ref(), b.as_ref()),
+        (None, None) => true,
+        _ => false,
     }
 }
@@ -24,9 +24,7 @@ pub trait IslandCallbacks<R: RunResult, Vm: VirtualMachine> {
     ///
     ///     // Add any functions that this individual defined. This step would be skipped for simulations that do not
     ///     // use the 'Name' stack.
-    ///     for (name, code) in individual.get_defined_names().iter() {
-    ///         vm.name().define_name(name.clone(), code.clone());
-    ///     }
+    ///     vm.engine_mut().load_defined_names(individual.get_defined_names());
     ///     
     ///     // Perform any other simulation or VM setup
     ///     // ...
@@ -50,8 +48,13 @@ pub trait IslandCallbacks<R: RunResult, Vm: VirtualMachine> {
     /// after all individuals have been run. The default implementation sorts based on the score of the two individuals.
     /// You should implement your own sorting function if the order of individual is based upon multiple criteria or a
     /// simple score is impossible to calculate.
+    ///
+    /// The default implementation caches the score in each individual, so score_individual is only called once for
+    /// each individual every time it is run.
     fn sort_individuals(&self, a: &Individual<R>, b: &Individual<R>) -> std::cmp::Ordering {
-        self.score_individual(a).cmp(&self.score_individual(b))
+        let a_score = a.cached_score(|i| self.score_individual(i));
+        let b_score = b.cached_score(|i| self.score_individual(i));
+        a_score.cmp(&b_score)
     }
 
     /// Score the effectiveness of one individual. The default implementation returns zero, indicating the worst
@@ -1,9 +1,13 @@
 extern crate pushgp_macros;
 
+mod clojush;
 mod code;
 mod configuration;
 mod context;
 mod data;
+mod deep_index_policy;
+mod engine_rng;
+mod evaluator;
 mod execute_bool;
 mod execute_code;
 mod execute_exec;
@@ -12,18 +16,26 @@ mod execute_integer;
 mod execute_name;
 mod execution_error;
 mod exit_status;
+mod expansion_cost;
+#[cfg(any(test, feature = "test-util"))]
+mod fuzz;
 mod genetic_operation;
 mod individual;
 mod instruction;
+mod instruction_category;
 mod instruction_table;
 mod instruction_weights;
 mod island;
 mod island_callbacks;
 mod list;
 mod migration_algorithm;
+mod migration_count;
+mod name_inheritance;
 mod name_stack;
+mod numeric_instructions;
 mod parse;
 mod parse_error;
+mod random_name_encoding;
 mod run_result;
 mod selection_curve;
 mod stack;
@@ -32,12 +44,18 @@ mod threading_model;
 mod util;
 mod virtual_machine;
 mod virtual_machine_engine;
+mod with_halt_penalty;
 mod world;
+mod world_error;
 
+pub use clojush::*;
 pub use code::*;
 pub use configuration::*;
 pub use context::*;
 pub use data::*;
+pub use deep_index_policy::*;
+pub use engine_rng::*;
+pub use evaluator::*;
 pub use execute_bool::*;
 pub use execute_code::*;
 pub use execute_exec::*;
@@ -46,23 +64,33 @@ pub use execute_integer::*;
 pub use execute_name::*;
 pub use execution_error::*;
 pub use exit_status::*;
+pub use expansion_cost::*;
+#[cfg(any(test, feature = "test-util"))]
+pub use fuzz::*;
 pub use genetic_operation::GeneticOperation;
 pub use individual::Individual;
 pub use instruction::*;
+pub use instruction_category::*;
 pub use instruction_table::*;
 pub use instruction_weights::*;
 pub use island::*;
 pub use island_callbacks::*;
 pub use list::*;
 pub use migration_algorithm::*;
+pub use migration_count::*;
+pub use name_inheritance::*;
 pub use name_stack::*;
 pub use parse::*;
 pub use parse_error::*;
+pub use random_name_encoding::*;
 pub use run_result::*;
 pub use selection_curve::SelectionCurve;
 pub use stack::*;
 pub use static_name::StaticName;
 pub use threading_model::*;
-pub use virtual_machine::{BaseVm, VirtualMachine};
+pub use util::sorted_by_name;
+pub use virtual_machine::{eval_into, BaseVm, VirtualMachine};
 pub use virtual_machine_engine::*;
+pub use with_halt_penalty::*;
 pub use world::*;
+pub use world_error::*;
@@ -10,6 +10,8 @@ impl StaticName for PushList {
 }
 
 impl<Vm: VirtualMachine + VirtualMachineMustHaveExec<Vm>> Instruction<Vm> for PushList {
+    const CATEGORY: InstructionCategory = InstructionCategory::Exec;
+
     // The PushList cannot be parsed this way because it requires recursive parsing (and thus access to the parser). See
     // parse.rs for the implementation of recursive parsing
     fn parse<'a>(input: &'a str, _opcode: u32) -> nom::IResult<&'a str, Code> {
@@ -36,6 +38,14 @@ impl<Vm: VirtualMachine + VirtualMachineMustHaveExec<Vm>> Instruction<Vm> for Pu
     }
 
     fn execute(mut code: Code, vm: &mut Vm) -> Result<(), ExecutionError> {
+        // A list that does not fit on the Exec stack is put back rather than partially expanded, so that a run that
+        // stops with ExceededMemoryLimit leaves the Exec stack as it was before the step that overflowed it
+        let len = code.len();
+        if code.is_list() && !vm.exec().has_room_for(len) {
+            vm.exec().push(code)?;
+            return Err(ExecutionError::OutOfMemory);
+        }
+
         match code.get_data_mut() {
             Data::CodeList(list) => {
                 while let Some(item) = list.pop() {
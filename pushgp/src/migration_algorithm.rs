@@ -1,5 +1,6 @@
 /// Defines the method by which individuals migrate from island to island when it is time for a migration.
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum MigrationAlgorithm {
     /// The islands are arranged in a circle and individuals always migrate one island clockwise.
     Circular,
@@ -20,4 +21,10 @@ pub enum MigrationAlgorithm {
     /// Every individual selected for migration picks a completely random island that is not its current island and
     /// migrates to that island.
     CompletelyRandom,
+
+    /// Every individual selected for migration picks a random island that is not its current island, with each island
+    /// picked in proportion to its `Island::get_weight`. Islands that are more valuable to the search (for example
+    /// because they run on faster hardware) can be given a higher weight to receive more migrants. An island with a
+    /// weight of zero never receives migrants.
+    Weighted,
 }
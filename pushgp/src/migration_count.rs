@@ -0,0 +1,34 @@
+use rand::Rng;
+
+/// Defines how many individuals migrate from each island when it is time for a migration.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum MigrationCount {
+    /// Exactly this many individuals migrate from each island.
+    Fixed(usize),
+
+    /// A random number of individuals between the two values (inclusive) migrate from each island. The number is drawn
+    /// again for every island in every migration, so the migration pressure varies over the run.
+    Range(usize, usize),
+
+    /// This fraction (0.0 to 1.0) of each island's population migrates, rounded to the nearest individual. Larger
+    /// islands send more migrants.
+    Fraction(f64),
+}
+
+impl MigrationCount {
+    /// Returns the number of individuals that should migrate from an island that has `population` individuals
+    pub fn count<Rnd: Rng>(&self, population: usize, rng: &mut Rnd) -> usize {
+        match *self {
+            MigrationCount::Fixed(count) => count,
+            MigrationCount::Range(first, second) => rng.gen_range(first.min(second)..=first.max(second)),
+            MigrationCount::Fraction(fraction) => (population as f64 * fraction.clamp(0.0, 1.0)).round() as usize,
+        }
+    }
+}
+
+impl From<usize> for MigrationCount {
+    fn from(count: usize) -> Self {
+        MigrationCount::Fixed(count)
+    }
+}
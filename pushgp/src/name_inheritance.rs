@@ -0,0 +1,19 @@
+/// Determines which of the parents' defined names are copied to a child produced by a genetic operation.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum NameInheritance {
+    /// The child receives every name defined by either parent, whether or not the parent's code refers to it. If both
+    /// parents define the same name, the definition from the left parent is used.
+    Union,
+
+    /// The child receives the names that are referenced in each parent's code. If both parents define the same name,
+    /// the definition from the left parent is used. This is the default.
+    LeftPriority,
+
+    /// The child receives the names that are referenced in each parent's code. If both parents define the same name,
+    /// the definition from the right parent is used.
+    RightPriority,
+
+    /// The child does not inherit any defined names.
+    None,
+}
@@ -44,6 +44,16 @@ impl NameStack {
         self.stack.len()
     }
 
+    /// Returns a copy of the names on the stack with the top item first
+    pub fn to_vec_top_first(&self) -> Vec<Name> {
+        self.stack.to_vec_top_first()
+    }
+
+    /// Returns a copy of the names on the stack with the bottom item first
+    pub fn to_vec_bottom_first(&self) -> Vec<Name> {
+        self.stack.to_vec_bottom_first()
+    }
+
     /// Duplicates the top item of the stack. This should not change the Stack or panic if the stack is empty
     pub fn duplicate_top_item(&mut self) -> Result<(), ExecutionError> {
         self.stack.duplicate_top_item()
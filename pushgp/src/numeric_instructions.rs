@@ -0,0 +1,147 @@
+/// Generates the instructions that are identical for every numeric stack: ordering comparisons, MAX, MIN and the stack
+/// manipulation instructions. The parameters are the type of the stack's items (which is also the name of the stack for
+/// `stack_instruction`), the name of the accessor for the stack on the VirtualMachine and the name of the stack as it
+/// appears in the docs. I.E. `numeric_instructions!(Integer, integer, "INTEGER")`.
+///
+/// Arithmetic such as SUM, DIFFERENCE and PRODUCT is not generated, because each numeric type handles overflow in its
+/// own way. EQUAL is not generated either, because FLOAT.EQUAL allows for a configurable epsilon. A new numeric type
+/// needs to implement `Clone`, `PartialEq` and `PartialOrd` for its items and can then use this macro for everything
+/// else.
+macro_rules! numeric_instructions {
+    ($stack:ident, $accessor:ident, $name:literal) => {
+        #[doc = concat!("Duplicates the top item on the ", $name, " stack. Does not pop its argument (which, if it")]
+        /// did, would negate the effect of the duplication!).
+        #[stack_instruction($stack)]
+        fn dup(vm: &mut Vm) {
+            vm.$accessor().duplicate_top_item()?;
+        }
+
+        #[doc = concat!("Empties the ", $name, " stack.")]
+        #[stack_instruction($stack)]
+        fn flush(vm: &mut Vm) {
+            vm.$accessor().clear();
+        }
+
+        /// Pushes TRUE onto the BOOLEAN stack if the second item is greater than the top item, or FALSE otherwise.
+        #[stack_instruction($stack)]
+        fn greater(vm: &mut Vm, right: $stack, left: $stack) {
+            vm.bool().push(left > right)?;
+        }
+
+        #[doc = concat!("Pushes TRUE onto the BOOLEAN stack if the ", $name, " stack is empty, or FALSE otherwise.")]
+        #[stack_instruction($stack)]
+        fn is_empty(vm: &mut Vm) {
+            let is_empty = vm.$accessor().len() == 0;
+            vm.bool().push(is_empty)?;
+        }
+
+        /// Pushes TRUE onto the BOOLEAN stack if the second item is less than the top item, or FALSE otherwise.
+        #[stack_instruction($stack)]
+        fn less(vm: &mut Vm, right: $stack, left: $stack) {
+            vm.bool().push(left < right)?;
+        }
+
+        /// Pushes the maximum of the top two items.
+        #[stack_instruction($stack)]
+        fn max(vm: &mut Vm, a: $stack, b: $stack) {
+            vm.$accessor().push(if a > b { a } else { b })?;
+        }
+
+        /// Pushes the minimum of the top two items.
+        #[stack_instruction($stack)]
+        fn min(vm: &mut Vm, a: $stack, b: $stack) {
+            vm.$accessor().push(if a < b { a } else { b })?;
+        }
+
+        #[doc = concat!("Pops the ", $name, " stack.")]
+        #[stack_instruction($stack)]
+        fn pop(vm: &mut Vm, _popped: $stack) {}
+
+        #[doc = concat!("Rotates the top three items on the ", $name, " stack, pulling the third item out and pushing")]
+        #[doc = concat!("it on top. This is equivalent to \"2 ", $name, ".YANK\".")]
+        #[stack_instruction($stack)]
+        fn rot(vm: &mut Vm) {
+            vm.$accessor().rotate()?;
+        }
+
+        #[doc = concat!("Inserts the top ", $name, " \"deep\" in the stack, at the position indexed by the top")]
+        /// INTEGER. The index position is calculated after the index is removed.
+        #[stack_instruction($stack)]
+        fn shove(vm: &mut Vm, position: Integer) {
+            let len = vm.$accessor().len();
+            let position = vm.engine().deep_index(position, len);
+            vm.$accessor().shove(position)?;
+        }
+
+        #[doc = concat!("Pushes the depth of the ", $name, " stack onto the INTEGER stack.")]
+        #[stack_instruction($stack)]
+        fn stack_depth(vm: &mut Vm) {
+            let len = vm.$accessor().len() as i64;
+            vm.integer().push(len)?;
+        }
+
+        #[doc = concat!("Swaps the top two items on the ", $name, " stack.")]
+        #[stack_instruction($stack)]
+        fn swap(vm: &mut Vm) {
+            vm.$accessor().swap()?;
+        }
+
+        /// Pushes a copy of an indexed item "deep" in the stack onto the top of the stack, without removing the deep
+        /// item. The index is taken from the INTEGER stack, and the indexing is done after the index is removed.
+        #[stack_instruction($stack)]
+        fn yank_dup(vm: &mut Vm, position: Integer) {
+            let len = vm.$accessor().len();
+            let position = vm.engine().deep_index(position, len);
+            vm.$accessor().yank_duplicate(position)?;
+        }
+
+        /// Removes an indexed item from "deep" in the stack and pushes it on top of the stack. The index is taken from
+        /// the INTEGER stack, and the indexing is done after the index is removed.
+        #[stack_instruction($stack)]
+        fn yank(vm: &mut Vm, position: Integer) {
+            let len = vm.$accessor().len();
+            let position = vm.engine().deep_index(position, len);
+            vm.$accessor().yank(position)?;
+        }
+    };
+}
+
+pub(crate) use numeric_instructions;
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use rust_decimal::Decimal;
+
+    fn run(src: &str) -> BaseVm {
+        let mut vm = BaseVm::new(Some(1), Configuration::new_simple());
+        add_base_instructions(&mut vm);
+        add_base_literals(&mut vm);
+        vm.engine_mut().parse_and_set_code(src).unwrap();
+        vm.run(1000);
+        vm
+    }
+
+    #[test]
+    fn integer_and_float_instructions_match() {
+        for instruction in ["DUP", "EQUAL", "FLUSH", "GREATER", "ISEMPTY", "LESS", "MAX", "MIN", "POP", "ROT", "SWAP"] {
+            for values in [[3, 1, 2], [1, 2, 3], [2, 2, 1], [-5, 7, 7]] {
+                let integers = values.map(|v| v.to_string()).join(" ");
+                let floats = values.map(|v| format!("{}.0", v)).join(" ");
+                let mut integer_vm = run(&format!("( {} INTEGER.{} )", integers, instruction));
+                let mut float_vm = run(&format!("( {} FLOAT.{} )", floats, instruction));
+
+                let integer_results: Vec<Float> =
+                    integer_vm.integer().to_vec_bottom_first().into_iter().map(|v| Decimal::from(v).into()).collect();
+                assert_eq!(integer_results, float_vm.float().to_vec_bottom_first(), "{} {:?}", instruction, values);
+                assert_eq!(
+                    integer_vm.bool().to_vec_bottom_first(),
+                    float_vm.bool().to_vec_bottom_first(),
+                    "{} {:?}",
+                    instruction,
+                    values
+                );
+            }
+        }
+    }
+}
@@ -1,10 +1,10 @@
 use crate::*;
 use nom::{
     branch::alt,
-    bytes::complete::tag,
-    character::complete::{char, digit1, none_of, space0, space1},
+    bytes::complete::{is_not, tag},
+    character::complete::{char, digit1, space0, space1},
     combinator::{eof, opt},
-    multi::many1,
+    sequence::preceded,
     IResult,
 };
 use rust_decimal::{prelude::FromPrimitive, Decimal};
@@ -70,6 +70,39 @@ fn end_list(input: &str) -> IResult<&str, ()> {
     Ok((input, ()))
 }
 
+/// Returns the deepest nesting of lists in the first program of the input without parsing it. Names and literals
+/// cannot contain parentheses, so every parenthesis is a list marker. This lets the depth be checked before the
+/// recursive parser runs. The scan stops where the parser would stop: at the end of the first list, or at the
+/// white-space after a leading atom, so that anything after the first program does not count.
+pub fn max_list_depth(input: &str) -> usize {
+    let mut depth: usize = 0;
+    let mut max_depth = 0;
+    let mut started = false;
+    for c in input.chars() {
+        match c {
+            '(' => {
+                depth += 1;
+                max_depth = max_depth.max(depth);
+                started = true;
+            }
+            ')' => {
+                depth = depth.saturating_sub(1);
+                if depth == 0 {
+                    break;
+                }
+            }
+            c if c.is_whitespace() => {
+                if depth == 0 && started {
+                    break;
+                }
+            }
+            _ => started = true,
+        }
+    }
+
+    max_depth
+}
+
 pub fn space_or_end(input: &str) -> IResult<&str, ()> {
     let (input, _) = alt((space1, eof))(input)?;
     Ok((input, ()))
@@ -93,25 +126,30 @@ pub fn parse_code_float(input: &str) -> IResult<&str, Decimal> {
     // A float MAY start with a sign
     let (input, opt_sign) = opt(alt((char('+'), char('-'))))(input)?;
 
-    // It MUST have a decimal point and digits before and after
+    // It MUST have digits, followed by a decimal point and more digits, an exponent (as in `2E10`), or both
     let (input, whole) = digit1(input)?;
-    let (input, _) = char('.')(input)?;
-    let (input, fractional) = digit1(input)?;
-
-    // It MAY have an exponent
+    let (input, opt_fractional) = opt(preceded(char('.'), digit1))(input)?;
     let (input, opt_exponent) = opt(parse_exponent)(input)?;
+    if opt_fractional.is_none() && opt_exponent.is_none() {
+        return Err(nom::Err::Error(nom::error::make_error(input, nom::error::ErrorKind::Verify)));
+    }
 
-    // It MAY have some trailing spaces
-    let (input, _) = space0(input)?;
+    // It MUST be followed by white-space or the end of the input, so that something like `1.5e` is not a float
+    let (input, _) = space_or_end(input)?;
 
     // Put the whole thing back into a string
-    let float_string =
-        format!("{}{}.{}{}", opt_sign.unwrap_or('+'), whole, fractional, opt_exponent.unwrap_or("".to_owned()));
-
-    // Parse it
-    match float_string.parse::<f64>() {
-        Ok(float_value) => Ok((input, Decimal::from_f64(float_value).unwrap())),
-        Err(_) => Err(nom::Err::Error(nom::error::make_error(input, nom::error::ErrorKind::Verify))),
+    let float_string = format!(
+        "{}{}.{}{}",
+        opt_sign.unwrap_or('+'),
+        whole,
+        opt_fractional.unwrap_or("0"),
+        opt_exponent.unwrap_or("".to_owned())
+    );
+
+    // Parse it. Values that are too large for a Decimal are rejected rather than rounded.
+    match float_string.parse::<f64>().ok().and_then(Decimal::from_f64) {
+        Some(value) => Ok((input, value)),
+        None => Err(nom::Err::Error(nom::error::make_error(input, nom::error::ErrorKind::Verify))),
     }
 }
 
@@ -145,9 +183,8 @@ pub fn parse_code_integer(input: &str) -> IResult<&str, i64> {
 
 pub fn parse_code_name(input: &str) -> IResult<&str, Name> {
     // Grab anything that is not a space, tab, line ending or list marker
-    let (input, name_chars) = many1(none_of(" \t\r\n()"))(input)?;
+    let (input, name) = is_not(" \t\r\n()")(input)?;
     let (input, _) = space_or_end(input)?;
-    let name: String = name_chars.iter().collect();
     Ok((input, name.into()))
 }
 
@@ -175,6 +212,36 @@ mod tests {
         assert_eq!(parse_code_float("123.0E-2").unwrap().1, expected);
 
         assert!(parse_code_float("1234").is_err());
+
+        // Scientific notation does not need a decimal point
+        assert_eq!(parse_code_float("1.5e-3").unwrap().1, Decimal::new(15, 4));
+        assert_eq!(parse_code_float("2E10").unwrap().1, Decimal::new(20_000_000_000, 0));
+        assert!(parse_code_float("1.5e").is_err());
+        assert!(parse_code_float("2E").is_err());
+        assert!(parse_code_float("1.5e40").is_err());
+    }
+
+    #[test]
+    fn parse_scientific_notation() {
+        let mut vm = BaseVm::new(Some(1), Configuration::new_simple());
+        add_base_instructions(&mut vm);
+        add_base_literals(&mut vm);
+
+        // The displayed form is a plain decimal that parses back to the same value
+        for (src, expected) in [("1.5e-3", "0.0015"), ("2E10", "20000000000.0"), ("-2.5E+2", "-250.0")] {
+            let code = vm.engine().must_parse(src);
+            assert_eq!(FloatLiteralValue::new_code(&vm, Decimal::from_str_exact(expected).unwrap().into()), code);
+            assert_eq!(expected, format!("{}", code.for_display(&vm)));
+            assert_eq!(code, vm.engine().must_parse(expected));
+        }
+
+        // A malformed exponent is not a float, so it is parsed as a name
+        let expected: Name = "1.5e".into();
+        assert_eq!(NameLiteralValue::new_code(&vm, expected), vm.engine().must_parse("1.5e"));
+        assert_eq!(
+            vm.engine().must_parse("( 1.5e 2 )"),
+            Code::new_list(vec![NameLiteralValue::new_code(&vm, "1.5e".into()), vm.engine().must_parse("2")]).unwrap()
+        );
     }
 
     #[test]
@@ -194,6 +261,66 @@ mod tests {
         assert_eq!(parse_code_name("1234KCMA|AA/AA.AAA=").unwrap().1, expected);
     }
 
+    #[test]
+    fn parse_name_too_long() {
+        let mut config = Configuration::new_simple();
+        config.set_max_name_length(16);
+        let mut vm = BaseVm::new(Some(1), config);
+        add_base_instructions(&mut vm);
+        add_base_literals(&mut vm);
+
+        let long_name = "A".repeat(100_000);
+        assert!(vm.engine().parse(&long_name).is_err());
+        assert!(vm.engine().parse(&format!("( 1 ( {} ) )", long_name)).is_err());
+        assert!(vm.engine_mut().parse_and_set_code(&format!("( {} )", long_name)).is_err());
+
+        // The name is rejected as soon as it is read, rather than after the rest of the input is parsed
+        match vm.engine().parse(&format!("{} 1 2 3", long_name)) {
+            Err(nom::Err::Failure(error)) => assert_eq!(nom::error::ErrorKind::TooLarge, error.code),
+            other => panic!("expected the long name to fail the parse, got {:?}", other),
+        }
+
+        // Names at the limit are still allowed
+        let expected: Name = "A".repeat(16).as_str().into();
+        assert_eq!(vm.engine().must_parse(&expected), NameLiteralValue::new_code(&vm, expected.clone()));
+
+        // Changing the configuration changes the limit
+        let mut config = vm.engine().get_configuration().clone();
+        config.set_max_name_length(100_000);
+        vm.engine_mut().reset_configuration(config);
+        assert!(vm.engine().parse(&long_name).is_ok());
+    }
+
+    #[test]
+    fn parse_too_deep() {
+        let mut config = Configuration::new_simple();
+        config.set_max_nesting_depth(10);
+        let mut vm = BaseVm::new(Some(1), config);
+        add_base_instructions(&mut vm);
+        add_base_literals(&mut vm);
+
+        // A pathologically deep program is rejected before it can overflow the stack
+        let deep = format!("{}{}", "( ".repeat(1_000_000), ") ".repeat(1_000_000));
+        assert!(vm.engine().parse(&deep).is_err());
+        let err = vm.engine_mut().parse_and_set_code(&deep).unwrap_err();
+        assert_eq!(Some(ExecutionError::MaxDepthExceeded), err.get_execution_error());
+
+        // Code at the limit is still allowed
+        let at_limit = format!("{}{}", "( ".repeat(10), ") ".repeat(10));
+        assert_eq!(10, vm.engine().must_parse(&at_limit).depth());
+
+        // Only the first program counts, so a deep program after it does not stop the first from parsing
+        let after_list = format!("( 1 ) {}", deep);
+        let (rest, code) = vm.engine().parse(&after_list).unwrap();
+        assert_eq!(vm.engine().must_parse("( 1 )"), code);
+        assert_eq!(deep, rest);
+        let after_atom = format!("1 {}", deep);
+        let (rest, code) = vm.engine().parse(&after_atom).unwrap();
+        assert_eq!(vm.engine().must_parse("1"), code);
+        assert_eq!(deep, rest);
+        assert!(vm.engine().parse(rest).is_err());
+    }
+
     #[test]
     fn parse_instruction() {
         let mut vtable = InstructionTable::<BaseVm>::new();
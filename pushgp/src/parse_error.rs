@@ -1,15 +1,28 @@
+use crate::ExecutionError;
+
 #[derive(Debug)]
 pub struct ParseError {
     message: String,
+    execution_error: Option<ExecutionError>,
 }
 
 impl ParseError {
     pub fn new<E: std::fmt::Debug>(err: nom::Err<E>) -> ParseError {
-        ParseError { message: err.to_string() }
+        ParseError { message: err.to_string(), execution_error: None }
     }
 
     pub fn new_with_message<S: ToString>(msg: S) -> ParseError {
-        ParseError { message: msg.to_string() }
+        ParseError { message: msg.to_string(), execution_error: None }
+    }
+
+    /// Creates a ParseError for code that was rejected because it would break one of the virtual machine's limits
+    pub fn new_from_execution_error(err: ExecutionError) -> ParseError {
+        ParseError { message: format!("{:?}", err), execution_error: Some(err) }
+    }
+
+    /// Returns the ExecutionError that caused the code to be rejected, if there was one
+    pub fn get_execution_error(&self) -> Option<ExecutionError> {
+        self.execution_error
     }
 }
 
@@ -0,0 +1,10 @@
+/// Defines how the random bits of a name generated by NAME.RAND (or random code generation) are turned into text.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum RandomNameEncoding {
+    /// The eight random bytes are encoded as base64, for example `sN5S8Epgn7Y=`. This is the default.
+    Base64,
+
+    /// The eight random bytes are encoded as sixteen lower-case hexadecimal digits, for example `b0de52f04a609fb6`.
+    Hex,
+}
@@ -1,4 +1,12 @@
 use std::fmt::Debug;
 
 /// This trait is a alias to avoid typing all the restrictions everytime we need to reference them
-pub trait RunResult: Clone + Debug + PartialEq + 'static {}
+pub trait RunResult: Clone + Debug + PartialEq + 'static {
+    /// Returns false if the individual that produced this result should be treated as worse than every valid
+    /// individual, no matter what its fitness is. I.E. a program that crashed the simulation or broke one of the
+    /// problem's constraints. Islands sort invalid individuals ahead of (less fit than) all the valid ones and do not
+    /// select them while any valid individuals remain. The default implementation returns true.
+    fn is_valid(&self) -> bool {
+        true
+    }
+}
@@ -2,6 +2,7 @@
 /// fitness function. The sorting algorithm defines the greatest fitness as being sorted at the end of a vector where
 /// `pool.sort_by(fitness_fn)` has been called.
 #[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum SelectionCurve {
     // All individuals are as likely as any other to be selected
     Fair,
@@ -23,6 +24,16 @@ pub enum SelectionCurve {
 
     // The less fit individuals will appear much more often
     StrongPreferenceForUnfit,
+
+    // The selection pressure toward fit individuals is set by the power. A uniform random value in [0, 1) is raised to
+    // the power and the result is counted back from the most fit individual, so larger powers concentrate selection on
+    // the top ranks. The fixed curves map onto this as:
+    //   Power(1.0) selects the same as Fair
+    //   Power(2.0) selects the same as SlightPreferenceForFit
+    //   Power(3.0) selects the same as PreferenceForFit
+    //   Power(6.0) selects the same as StrongPreferenceForFit
+    // Powers between zero and one favor the less fit individuals. A power that is not positive is treated as 1.0.
+    Power(f64),
 }
 
 impl SelectionCurve {
@@ -40,13 +51,16 @@ impl SelectionCurve {
             SelectionCurve::StrongPreferenceForFit | SelectionCurve::StrongPreferenceForUnfit => {
                 pick * pick * pick * pick * pick * pick
             }
+            SelectionCurve::Power(power) if *power > 0.0 => pick.powf(*power),
+            SelectionCurve::Power(_) => pick,
         };
 
         // Reverse the direction of the 'Fit' selection
         let pick = match &self {
             SelectionCurve::PreferenceForFit
             | SelectionCurve::SlightPreferenceForFit
-            | SelectionCurve::StrongPreferenceForFit => 1.0 - pick,
+            | SelectionCurve::StrongPreferenceForFit
+            | SelectionCurve::Power(_) => 1.0 - pick,
             _ => pick,
         };
 
@@ -221,4 +235,18 @@ mod tests {
             last_bucket_count = bucket;
         }
     }
+
+    #[test]
+    fn power_selection_curve() {
+        // A higher power puts more of the picks on the most fit individuals at the end of the pool
+        let top_ten = |buckets: Vec<usize>| -> usize { buckets[90..].iter().sum() };
+        let mild = top_ten(pick_100_000_times(SelectionCurve::Power(1.0)));
+        let moderate = top_ten(pick_100_000_times(SelectionCurve::Power(3.0)));
+        let strong = top_ten(pick_100_000_times(SelectionCurve::Power(10.0)));
+        assert!(mild < moderate, "power 1.0 picked the top ten {} times, power 3.0 {} times", mild, moderate);
+        assert!(moderate < strong, "power 3.0 picked the top ten {} times, power 10.0 {} times", moderate, strong);
+
+        // Power(1.0) is as fair as the Fair curve
+        assert!((9_000..=11_000).contains(&mild), "power 1.0 picked the top ten {} times", mild);
+    }
 }
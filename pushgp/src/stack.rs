@@ -40,6 +40,21 @@ impl<T: Clone> Stack<T> {
         self.stack.len()
     }
 
+    /// Returns true if `count` more items can be pushed without exceeding the maximum length of the Stack
+    pub fn has_room_for(&self, count: usize) -> bool {
+        self.stack.len() + count <= self.max_len
+    }
+
+    /// Returns the maximum number of items the Stack can hold. Pushing beyond this is an `ExecutionError::OutOfMemory`
+    pub fn max_len(&self) -> usize {
+        self.max_len
+    }
+
+    /// Returns the number of items that can still be pushed before the Stack is full
+    pub fn remaining_capacity(&self) -> usize {
+        self.max_len.saturating_sub(self.stack.len())
+    }
+
     /// Duplicates the top item of the stack. This should not change the Stack or panic if the stack is empty
     pub fn duplicate_top_item(&mut self) -> Result<(), ExecutionError> {
         if self.stack.len() < self.max_len {
@@ -58,6 +73,39 @@ impl<T: Clone> Stack<T> {
         }
     }
 
+    /// Duplicates the top `count` items of the stack, preserving their order, so that `[ 'C', 'B', 'A' ]` becomes
+    /// `[ 'C', 'B', 'A', 'B', 'A' ]` when duplicating two items. The count is limited to the number of items on the
+    /// stack. The stack is not changed if the duplicates would not fit.
+    pub fn duplicate_top_items(&mut self, count: usize) -> Result<(), ExecutionError> {
+        let count = count.min(self.stack.len());
+        if self.stack.len() + count <= self.max_len {
+            let start = self.stack.len() - count;
+            self.stack.extend_from_within(start..);
+            Ok(())
+        } else {
+            Err(ExecutionError::OutOfMemory)
+        }
+    }
+
+    /// Returns a copy of the items on the stack with the top item first. The first item is the one that `pop` would
+    /// return next, so a stack built by pushing 'A', then 'B', then 'C' returns `[ 'C', 'B', 'A' ]`.
+    pub fn to_vec_top_first(&self) -> Vec<T> {
+        self.stack.iter().rev().cloned().collect()
+    }
+
+    /// Returns a copy of the items on the stack with the bottom item first. This is the order in which the items were
+    /// pushed, so a stack built by pushing 'A', then 'B', then 'C' returns `[ 'A', 'B', 'C' ]`.
+    pub fn to_vec_bottom_first(&self) -> Vec<T> {
+        self.stack.clone()
+    }
+
+    /// Returns an iterator over the items on the stack without removing them. The items are visited bottom first, in
+    /// the same order as `to_vec_bottom_first`, so the last item visited is the one that `pop` would return next. Use
+    /// `.rev()` to visit the top item first.
+    pub fn iter(&self) -> std::slice::Iter<'_, T> {
+        self.stack.iter()
+    }
+
     /// Deletes all items from the Stack
     pub fn clear(&mut self) {
         self.stack.clear()
@@ -164,6 +212,29 @@ mod tests {
         assert_eq!(None, stack.pop());
     }
 
+    #[test]
+    fn stack_capacity() {
+        let mut stack = Stack::new(3);
+        assert_eq!(3, stack.max_len());
+        assert_eq!(3, stack.remaining_capacity());
+
+        // Each push uses up some of the capacity
+        assert_eq!(Ok(()), stack.push(1));
+        assert_eq!(Ok(()), stack.push(2));
+        assert_eq!(3, stack.max_len());
+        assert_eq!(1, stack.remaining_capacity());
+
+        // A full stack has no capacity left and refuses more items
+        assert_eq!(Ok(()), stack.push(3));
+        assert_eq!(0, stack.remaining_capacity());
+        assert_eq!(Err(ExecutionError::OutOfMemory), stack.push(4));
+        assert_eq!(0, stack.remaining_capacity());
+
+        // Popping frees up capacity again
+        assert_eq!(Some(3), stack.pop());
+        assert_eq!(1, stack.remaining_capacity());
+    }
+
     #[test]
     fn stack_duplicate_top_item() {
         let mut stack = Stack::new(5);
@@ -185,6 +256,32 @@ mod tests {
         assert_eq!(None, stack.pop());
     }
 
+    #[test]
+    fn stack_duplicate_top_items() {
+        // Duplicating an empty stack has no effect
+        let mut stack = Stack::<char>::new(5);
+        assert_eq!(Ok(()), stack.duplicate_top_items(2));
+        assert_eq!(0, stack.len());
+
+        // The top items are duplicated in order
+        let mut stack = Stack::new_from_vec(vec!['C', 'B', 'A'], 5);
+        assert_eq!(Ok(()), stack.duplicate_top_items(2));
+        let expected = Stack::new_from_vec(vec!['C', 'B', 'A', 'B', 'A'], 5);
+        assert_eq!(expected, stack);
+
+        // The count is limited to the items on the stack
+        let mut stack = Stack::new_from_vec(vec!['B', 'A'], 5);
+        assert_eq!(Ok(()), stack.duplicate_top_items(10));
+        let expected = Stack::new_from_vec(vec!['B', 'A', 'B', 'A'], 5);
+        assert_eq!(expected, stack);
+
+        // Duplicates that would overflow the stack are not added
+        let mut stack = Stack::new_from_vec(vec!['C', 'B', 'A'], 5);
+        assert_eq!(Err(ExecutionError::OutOfMemory), stack.duplicate_top_items(3));
+        let expected = Stack::new_from_vec(vec!['C', 'B', 'A'], 5);
+        assert_eq!(expected, stack);
+    }
+
     #[test]
     fn stack_clear() {
         let mut stack = Stack::new(5);
@@ -430,4 +527,35 @@ mod tests {
         let expected = Stack::new_from_vec(vec!['C', 'B', 'A', 'C'], 5);
         assert_eq!(expected, stack);
     }
+
+    #[test]
+    fn stack_to_vec_orderings() {
+        let mut stack = Stack::new(5);
+        assert_eq!(Ok(()), stack.push('A'));
+        assert_eq!(Ok(()), stack.push('B'));
+        assert_eq!(Ok(()), stack.push('C'));
+
+        assert_eq!(vec!['C', 'B', 'A'], stack.to_vec_top_first());
+        assert_eq!(vec!['A', 'B', 'C'], stack.to_vec_bottom_first());
+
+        // Taking a snapshot does not change the stack
+        assert_eq!(Some('C'), stack.pop());
+        assert_eq!(vec!['B', 'A'], stack.to_vec_top_first());
+    }
+
+    #[test]
+    fn stack_iter() {
+        let mut stack = Stack::new(5);
+        assert_eq!(Ok(()), stack.push(1));
+        assert_eq!(Ok(()), stack.push(2));
+        assert_eq!(Ok(()), stack.push(3));
+
+        assert_eq!(vec![1, 2, 3], stack.iter().copied().collect::<Vec<i64>>());
+        assert_eq!(6, stack.iter().sum::<i64>());
+        assert_eq!(Some(&3), stack.iter().next_back());
+
+        // Iterating does not change the stack
+        assert_eq!(3, stack.len());
+        assert_eq!(vec![1, 2, 3], stack.to_vec_bottom_first());
+    }
 }
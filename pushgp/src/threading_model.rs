@@ -1,14 +1,16 @@
+// TODO: PerIsland and PerIndividual require significant planning and work to safely mutate different parts of a world
+// at the same time. Until then a World returns `WorldError::ThreadingUnsupported` for them.
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum ThreadingModel {
     /// Do not use multi-threading when executing the world
     None,
-    
-    // TODO: The threading models below require significant planning and work to safely mutate different parts of a
-    // world at the same time.
 
-    // Each Island will execute in its own thread. The parameter is the total number of islands to execute at once
-    // PerIsland(usize),
+    /// Each Island will execute in its own thread. The parameter is the total number of islands to execute at once.
+    /// Not yet supported.
+    PerIsland(usize),
 
-    // Each Individual will execute in its own thread. The parameter is the total number of individuals to run at once.
-    // PerIndividual(usize),
+    /// Each Individual will execute in its own thread. The parameter is the total number of individuals to run at once.
+    /// Not yet supported.
+    PerIndividual(usize),
 }
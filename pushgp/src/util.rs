@@ -1,3 +1,5 @@
+use fnv::FnvHashMap;
+
 /// Takes a stack index which is a zero-based index from the end of a Vec and translates it into a Vec index (zero-based
 /// from the front). Additionally, if the stack_index is larger than the vec_len, the modulo is used so that it is
 /// always a valid result
@@ -23,9 +25,19 @@ pub fn stack_to_vec(mut stack_index: i64, vec_len: usize) -> usize {
     (vec_len - 1) - stack_index
 }
 
+/// Returns the entries of the map sorted by key. The iteration order of an FnvHashMap is not stable, so use this
+/// wherever the entries are printed or serialized and the output needs to be reproducible. For maps keyed by
+/// instruction name this yields the instructions in name order.
+pub fn sorted_by_name<K: Ord, V>(map: &FnvHashMap<K, V>) -> Vec<(&K, &V)> {
+    let mut entries: Vec<(&K, &V)> = map.iter().collect();
+    entries.sort_by(|a, b| a.0.cmp(b.0));
+    entries
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::util::stack_to_vec;
+    use crate::util::{sorted_by_name, stack_to_vec};
+    use fnv::FnvHashMap;
 
     #[test]
     fn test_stack_to_vec() {
@@ -66,4 +78,18 @@ mod tests {
         // At one point gave 'attempt to add with overflow'
         assert_eq!(stack_to_vec(i64::MIN + 1, 1), 0);
     }
+
+    #[test]
+    fn test_sorted_by_name() {
+        let mut weights: FnvHashMap<&'static str, u8> = FnvHashMap::default();
+        weights.insert("INTEGER.SUM", 3);
+        weights.insert("BOOL.AND", 1);
+        weights.insert("EXEC.IF", 2);
+        weights.insert("CODE.CAR", 4);
+
+        let sorted = sorted_by_name(&weights);
+        let names: Vec<&str> = sorted.iter().map(|(name, _)| **name).collect();
+        assert_eq!(vec!["BOOL.AND", "CODE.CAR", "EXEC.IF", "INTEGER.SUM"], names);
+        assert_eq!(vec![1, 4, 2, 3], sorted.iter().map(|(_, weight)| **weight).collect::<Vec<u8>>());
+    }
 }
@@ -38,56 +38,115 @@ pub trait VirtualMachine:
     fn run(&mut self, max: usize) -> ExitStatus {
         // trace!("{:?}", self);
         let mut stats = ExitStats { total_instruction_count: 0, total_noop_count: 0 };
-        loop {
+        let status = loop {
+            // Let the instructions know how much of the budget will be left once they complete
+            self.engine_mut().set_steps_remaining(Some(max.saturating_sub(stats.total_instruction_count + 1)));
             match self.next() {
                 Ok(count) => stats.total_instruction_count += count,
                 Err(ExecutionError::ExecStackEmpty) => {
                     PROGRAM_EXIT_COUNTER_VEC.get_metric_with_label_values(&["normal"]).unwrap().inc();
-                    return ExitStatus::Normal(stats);
+                    break ExitStatus::Normal(stats);
                 }
                 Err(ExecutionError::IllegalOperation) => {
                     stats.total_instruction_count += 1;
                     NOOP_ILLEGAL_OPERATION_COUNTER.inc();
+                    self.engine_mut().record_skipped_operation();
                     stats.total_noop_count += 1;
                 }
                 Err(ExecutionError::InsufficientInputs) => {
                     stats.total_instruction_count += 1;
                     NOOP_INSUFFICIENT_INPUTS_COUNTER.inc();
+                    self.engine_mut().record_skipped_operation();
                     stats.total_noop_count += 1;
                 }
                 Err(ExecutionError::OutOfMemory) => {
                     PROGRAM_EXIT_COUNTER_VEC.get_metric_with_label_values(&["exceeded_memory_limit"]).unwrap().inc();
-                    return ExitStatus::ExceededMemoryLimit(stats);
+                    break ExitStatus::ExceededMemoryLimit(stats);
+                }
+                Err(ExecutionError::MaxDepthExceeded) => {
+                    PROGRAM_EXIT_COUNTER_VEC.get_metric_with_label_values(&["exceeded_max_depth"]).unwrap().inc();
+                    break ExitStatus::ExceededMemoryLimit(stats);
                 }
                 Err(ExecutionError::InvalidOpcode) => {
                     PROGRAM_EXIT_COUNTER_VEC.get_metric_with_label_values(&["exceeded_invalid_opcode"]).unwrap().inc();
-                    return ExitStatus::InvalidOpcode(stats);
+                    break ExitStatus::InvalidOpcode(stats);
                 }
             }
 
             if stats.total_instruction_count >= max {
                 PROGRAM_EXIT_COUNTER_VEC.get_metric_with_label_values(&["exceeded_instruction_count"]).unwrap().inc();
-                return ExitStatus::ExceededInstructionCount(stats);
+                break ExitStatus::ExceededInstructionCount(stats);
             }
-        }
+        };
+        self.engine_mut().set_steps_remaining(None);
+        status
     }
 
     /// Processes the next instruction from the Exec stack. The return type allows for some VirtualMachines to indicate
-    /// how expensive an instruction was. Typically returns Ok(1)
+    /// how expensive an instruction was. Typically returns Ok(1), but expanding a list may cost more depending upon the
+    /// configured `ExpansionCost`.
     fn next(&mut self) -> Result<usize, ExecutionError> {
         // Pop the top piece of code from the exec stack and execute it.
         let exec = self.engine_mut().exec().pop().ok_or(ExecutionError::ExecStackEmpty)?;
+        let cost = match self.engine().get_configuration().get_list_expansion_cost() {
+            ExpansionCost::PerItem if exec.is_list() => exec.len().max(1),
+            _ => 1,
+        };
         let (execute_fn, _timer) = self.engine().execute_fn(exec.get_opcode()).ok_or(ExecutionError::InvalidOpcode)?;
         execute_fn(exec, self)?;
 
-        Ok(1)
+        Ok(cost)
+    }
+
+    /// Returns a copy of the top item of the stack with the specified name (such as "INTEGER"), or None if the stack is
+    /// empty or the VirtualMachine has no stack with that name. An item of the CODE or EXEC stack is returned as a
+    /// `Data::CodeList` that holds only that item, and a BOOLEAN is returned as `Data::Integer` of zero or one.
+    fn peek_stack(&mut self, stack: &str) -> Option<Data>;
+
+    /// Returns the names of the stacks that `peek_stack` can read, such as "INTEGER".
+    fn stack_names(&self) -> &'static [&'static str];
+
+    /// Returns the top item of the stack that holds the program's answer, which is the INTEGER stack unless another
+    /// stack is set with `Configuration::set_answer_stack`. Fitness functions can call this after a run to read the
+    /// program's output without knowing which stack holds it. Returns None if the stack is empty.
+    fn answer(&mut self) -> Option<Data> {
+        let stack = self.engine().get_configuration().get_answer_stack().unwrap_or("INTEGER").to_owned();
+        self.peek_stack(&stack)
+    }
+
+    /// Checks that the configured answer stack is one of the stacks of this VirtualMachine, and returns its name if it
+    /// is not. A VirtualMachine without an INTEGER stack must set an answer stack.
+    fn validate_answer_stack(&self) -> Result<(), String> {
+        let stack = self.engine().get_configuration().get_answer_stack().unwrap_or("INTEGER");
+        if self.stack_names().contains(&stack) {
+            Ok(())
+        } else {
+            Err(stack.to_owned())
+        }
     }
 
     /// Returns the random number generator used by the VirtualMachine.
-    fn get_rng(&mut self) -> &mut rand::rngs::SmallRng {
+    fn get_rng(&mut self) -> &mut EngineRng {
         self.engine_mut().get_rng()
     }
 
+    /// Reseeds the random number generator with `seed`, keeping the kind of generator, runs `f` and then restores the
+    /// generator to the state it was in before the call. Fitness functions that evaluate many test cases can use this so
+    /// that every program sees the same random values for a given case, without disturbing the random stream used for
+    /// breeding.
+    fn with_case_seed<F: FnOnce(&mut Self)>(&mut self, seed: u64, f: F) {
+        let seeded = self.get_rng().reseeded(seed);
+        let saved = std::mem::replace(self.get_rng(), seeded);
+        f(self);
+        *self.get_rng() = saved;
+    }
+
+    /// Resets any state that the VirtualMachine keeps for the problem being solved, such as a game in progress, to a
+    /// starting state chosen by the seed. Islands should call this before each test case so that every individual
+    /// starts a case from exactly the same state. This does not clear the stacks; see `clear`. The default
+    /// implementation does nothing, because the base stacks hold no problem state.
+    fn reset_problem_state(&mut self, _seed: u64) {}
+
     /// Formats a code object in the way that std::fmt::Display expects, except with Code as a parameter
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>, code: &Code) -> std::fmt::Result {
         self.engine().fmt(f, code)
@@ -106,6 +165,18 @@ pub trait VirtualMachine:
     }
 }
 
+/// Parses `src` and runs it on the virtual machine without clearing it first, which is useful for a REPL. Unlike
+/// `VirtualMachineEngine::parse_and_set_code`, the stacks and defined names left by earlier calls are kept, so each
+/// call continues from the state left by the last one. The source may contain any number of items, which are run as if
+/// they were wrapped in a list.
+///
+/// The code runs until the Exec stack is empty or the memory limit is exceeded. There is no limit on the number of
+/// steps, so code that never finishes will never return.
+pub fn eval_into<Vm: VirtualMachine>(vm: &mut Vm, src: &str) -> Result<ExitStatus, ParseError> {
+    vm.engine_mut().parse_and_push_code(&format!("( {} )", src))?;
+    Ok(vm.run(usize::MAX))
+}
+
 #[derive(Clone, Debug, PartialEq)]
 pub struct BaseVm {
     engine: VirtualMachineEngine<BaseVm>,
@@ -148,6 +219,22 @@ impl VirtualMachine for BaseVm {
         self.integer_stack.clear();
         self.name_stack.clear();
     }
+
+    fn peek_stack(&mut self, stack: &str) -> Option<Data> {
+        match stack {
+            "BOOL" => self.bool_stack.peek().map(|value| Data::Integer(value as i64)),
+            "CODE" => self.code_stack.peek().map(|code| Data::CodeList(vec![code])),
+            "EXEC" => self.engine.exec().peek().map(|code| Data::CodeList(vec![code])),
+            "FLOAT" => self.float_stack.peek().map(|value| Data::Decimal(*value)),
+            "INTEGER" => self.integer_stack.peek().map(Data::Integer),
+            "NAME" => self.name_stack.peek().map(Data::Name),
+            _ => None,
+        }
+    }
+
+    fn stack_names(&self) -> &'static [&'static str] {
+        &["BOOL", "CODE", "EXEC", "FLOAT", "INTEGER", "NAME"]
+    }
 }
 
 impl VirtualMachineMustHaveBool<BaseVm> for BaseVm {
@@ -201,3 +288,141 @@ impl OpcodeConvertor for BaseVm {
         self.engine().opcode_for_name(name)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use rand::Rng;
+    use rust_decimal::Decimal;
+
+    fn instruction_count_for(src: &str, cost: ExpansionCost) -> usize {
+        let mut config = Configuration::new_simple();
+        config.set_list_expansion_cost(cost);
+        let mut vm = BaseVm::new(Some(1), config);
+        add_base_instructions(&mut vm);
+        add_base_literals(&mut vm);
+        vm.engine_mut().parse_and_set_code(src).unwrap();
+        match vm.run(1000) {
+            ExitStatus::Normal(stats) => stats.total_instruction_count,
+            _ => panic!("program should have exited normally"),
+        }
+    }
+
+    #[test]
+    fn eval_into_keeps_state() {
+        let mut vm = BaseVm::new(Some(1), Configuration::new_simple());
+        add_base_instructions(&mut vm);
+        add_base_literals(&mut vm);
+
+        assert!(matches!(eval_into(&mut vm, "2 3 INTEGER.SUM"), Ok(ExitStatus::Normal(_))));
+        assert_eq!(Some(5), vm.integer().peek());
+        assert!(matches!(eval_into(&mut vm, "4 INTEGER.PRODUCT"), Ok(ExitStatus::Normal(_))));
+        assert_eq!(1, vm.integer().len());
+        assert_eq!(Some(20), vm.integer().pop());
+    }
+
+    #[test]
+    fn answer() {
+        let mut config = Configuration::new_simple();
+        let mut vm = BaseVm::new(Some(1), config.clone());
+        add_base_instructions(&mut vm);
+        add_base_literals(&mut vm);
+        assert_eq!(None, vm.answer());
+        assert_eq!(Ok(()), vm.validate_answer_stack());
+        eval_into(&mut vm, "1.5 2.5 7").unwrap();
+        assert_eq!(Some(Data::Integer(7)), vm.answer());
+
+        config.set_answer_stack(Some("FLOAT"));
+        let mut vm = BaseVm::new(Some(1), config);
+        add_base_instructions(&mut vm);
+        add_base_literals(&mut vm);
+        eval_into(&mut vm, "1.5 2.5 7").unwrap();
+        assert_eq!(Some(Data::Decimal(Decimal::new(25, 1))), vm.answer());
+        assert_eq!(Ok(()), vm.validate_answer_stack());
+        assert_eq!(None, vm.peek_stack("NOT.A.STACK"));
+
+        let mut config = vm.engine().get_configuration().clone();
+        config.set_answer_stack(Some("NOT.A.STACK"));
+        vm.engine_mut().reset_configuration(config);
+        assert_eq!(Err("NOT.A.STACK".to_owned()), vm.validate_answer_stack());
+    }
+
+    #[test]
+    fn exec_overflow_stops_the_run() {
+        let mut vm = BaseVm::new(Some(1), Configuration::new_simple());
+        add_base_instructions(&mut vm);
+        add_base_literals(&mut vm);
+
+        // The Exec stack of a BaseVm holds 20 items, so the inner list cannot be expanded
+        let too_long = (1..=25).map(|i| i.to_string()).collect::<Vec<String>>().join(" ");
+        let src = format!("( 1 2 INTEGER.SUM ( {} ) 4 )", too_long);
+        vm.engine_mut().parse_and_set_code(&src).unwrap();
+        match vm.run(1000) {
+            ExitStatus::ExceededMemoryLimit(stats) => assert_eq!(4, stats.total_instruction_count),
+            _ => panic!("program should have exceeded the memory limit"),
+        }
+
+        // The instructions before the overflow ran, and the list that overflowed is still on the Exec stack
+        assert_eq!(vec![3], vm.integer().to_vec_top_first());
+        let remaining = vm.engine_mut().exec().to_vec_top_first();
+        assert_eq!(vec![vm.engine().must_parse(&format!("( {} )", too_long)), vm.engine().must_parse("4")], remaining);
+    }
+
+    #[test]
+    fn skipped_operation_count() {
+        let mut vm = BaseVm::new(Some(1), Configuration::new_simple());
+        add_base_instructions(&mut vm);
+        add_base_literals(&mut vm);
+
+        vm.engine_mut().parse_and_set_code("( 1 0 INTEGER.QUOTIENT 2.0 0.0 FLOAT.QUOTIENT 3 2 INTEGER.SUM )").unwrap();
+        vm.run(1000);
+        assert_eq!(2, vm.engine().skipped_operation_count());
+
+        // Missing inputs are also counted, and clearing the VM resets the count
+        vm.clear();
+        assert_eq!(0, vm.engine().skipped_operation_count());
+        vm.engine_mut().parse_and_set_code("( INTEGER.SUM )").unwrap();
+        vm.run(1000);
+        assert_eq!(1, vm.engine().skipped_operation_count());
+    }
+
+    #[test]
+    fn list_expansion_cost() {
+        // One step for the list and one for each of the ten integers
+        let src = "( 1 2 3 4 5 6 7 8 9 10 )";
+        assert_eq!(11, instruction_count_for(src, ExpansionCost::Flat));
+
+        // Ten steps for the list and one for each of the ten integers
+        assert_eq!(20, instruction_count_for(src, ExpansionCost::PerItem));
+
+        // An empty list still costs one step
+        assert_eq!(1, instruction_count_for("( )", ExpansionCost::PerItem));
+    }
+
+    fn random_output_for_case(vm: &mut BaseVm, seed: u64) -> Code {
+        let mut output = None;
+        vm.with_case_seed(seed, |vm| {
+            vm.clear();
+            vm.engine_mut().parse_and_set_code("( 20 CODE.RAND )").unwrap();
+            vm.run(100);
+            output = vm.code().pop();
+        });
+        output.unwrap()
+    }
+
+    #[test]
+    fn with_case_seed() {
+        let mut vm = BaseVm::new(Some(1), Configuration::new_simple());
+        add_base_instructions(&mut vm);
+        add_base_literals(&mut vm);
+        let mut untouched = vm.clone();
+
+        // Two cases run under the same seed see the same random values
+        let first = random_output_for_case(&mut vm, 42);
+        let second = random_output_for_case(&mut vm, 42);
+        assert_eq!(first, second);
+
+        // The random stream outside of the cases is unaffected
+        assert_eq!(untouched.get_rng().gen::<u64>(), vm.get_rng().gen::<u64>());
+    }
+}
@@ -1,40 +1,76 @@
-use fnv::FnvHashMap;
+use fnv::{FnvHashMap, FnvHasher};
 use rand::{
     rngs::SmallRng,
     seq::{IteratorRandom, SliceRandom},
-    Rng, SeedableRng,
+    Rng, RngCore, SeedableRng,
 };
+use std::hash::{Hash, Hasher};
 
 use crate::*;
 
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug)]
 pub struct VirtualMachineEngine<Vm: VirtualMachine + VirtualMachineMustHaveExec<Vm>> {
-    rng: SmallRng,
+    rng: EngineRng,
     exec_stack: Stack<Exec>,
     config: Configuration,
     weights: InstructionWeights,
     vtable: InstructionTable<Vm>,
     defined_names: FnvHashMap<Name, Code>,
+    steps_remaining: Option<usize>,
+    skipped_operation_count: usize,
+    context_id: i64,
 }
 
 impl<Vm: VirtualMachine + VirtualMachineMustHaveExec<Vm>> VirtualMachineEngine<Vm> {
     pub fn new(seed: Option<u64>, config: Configuration, max_exec_stack_len: usize) -> VirtualMachineEngine<Vm> {
+        let mut vtable = InstructionTable::new();
+        vtable.set_float_display_precision(config.get_float_display_precision());
+        vtable.set_max_name_length(config.get_max_name_length());
         VirtualMachineEngine {
-            rng: small_rng_from_optional_seed(seed),
+            rng: EngineRng::from_optional_seed(seed),
             exec_stack: Stack::new(max_exec_stack_len),
             config,
             weights: InstructionWeights::new(),
-            vtable: InstructionTable::new(),
+            vtable,
             defined_names: FnvHashMap::default(),
+            steps_remaining: None,
+            skipped_operation_count: 0,
+            context_id: 0,
         }
     }
 
-    pub fn get_rng(&mut self) -> &mut rand::rngs::SmallRng {
+    pub fn get_rng(&mut self) -> &mut EngineRng {
         &mut self.rng
     }
 
+    /// Returns the random number generator for reading only, such as to save its position
+    pub fn rng(&self) -> &EngineRng {
+        &self.rng
+    }
+
+    /// Replaces the random number generator with a `SmallRng` seeded with the seed, or seeded from the operating system
+    /// if there is no seed
     pub fn set_rng_seed(&mut self, seed: Option<u64>) {
-        self.rng = small_rng_from_optional_seed(seed);
+        self.rng = EngineRng::from_optional_seed(seed);
+    }
+
+    /// Replaces the random number generator, such as with a generator that produces the same values on every platform
+    pub fn set_rng(&mut self, rng: EngineRng) {
+        self.rng = rng;
+    }
+
+    /// Returns an independent random number generator for the item with the specified index, such as an individual
+    /// being evaluated on another thread. The generator's seed is a hash of the index and the next value the engine's
+    /// generator would produce, so the result depends only on the engine's current state and the index, never on the
+    /// order in which threads run. The engine's own generator is not advanced.
+    pub fn split_rng(&self, index: u64) -> SmallRng {
+        let base = self.rng.clone().next_u64();
+
+        // The splitmix64 finalizer spreads neighboring indices across the whole range of seeds
+        let mut z = base ^ index.wrapping_add(1).wrapping_mul(0x9e3779b97f4a7c15);
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+        SmallRng::seed_from_u64(z ^ (z >> 31))
     }
 
     pub fn exec(&mut self) -> &mut Stack<Code> {
@@ -45,9 +81,47 @@ impl<Vm: VirtualMachine + VirtualMachineMustHaveExec<Vm>> VirtualMachineEngine<V
         &self.weights
     }
 
+    /// Returns the number of steps that remain in the budget of the current run once the instruction that is executing
+    /// completes, or None when the VirtualMachine is not running.
+    pub fn get_steps_remaining(&self) -> Option<usize> {
+        self.steps_remaining
+    }
+
+    /// Sets the number of steps that remain in the budget of the current run. This is maintained by
+    /// `VirtualMachine::run` and should not need to be called by anything else.
+    pub fn set_steps_remaining(&mut self, steps_remaining: Option<usize>) {
+        self.steps_remaining = steps_remaining;
+    }
+
+    /// Returns the id of the context the VirtualMachine is running in, which programs can read with
+    /// INTEGER.CONTEXTID. The default is zero.
+    pub fn get_context_id(&self) -> i64 {
+        self.context_id
+    }
+
+    /// Sets the id of the context the VirtualMachine is running in. Each island sets it before running its individuals
+    /// (to zero if the island has no context id), so in coevolution the same program can behave differently on each
+    /// island. Clearing the VirtualMachine does not change the context id.
+    pub fn set_context_id(&mut self, context_id: i64) {
+        self.context_id = context_id;
+    }
+
+    /// Returns the number of instructions that were skipped as a NOOP because their inputs were missing or invalid (for
+    /// example dividing by zero) since the engine was last cleared. Fitness functions can use this to penalize fragile
+    /// programs.
+    pub fn skipped_operation_count(&self) -> usize {
+        self.skipped_operation_count
+    }
+
+    /// Counts one instruction that was skipped as a NOOP. This is called by `VirtualMachine::run`.
+    pub fn record_skipped_operation(&mut self) {
+        self.skipped_operation_count += 1;
+    }
+
     pub fn clear(&mut self) {
         self.exec_stack.clear();
         self.defined_names.clear();
+        self.skipped_operation_count = 0;
     }
 
     pub fn add_instruction<I: 'static + Instruction<Vm>>(&mut self) {
@@ -63,6 +137,8 @@ impl<Vm: VirtualMachine + VirtualMachineMustHaveExec<Vm>> VirtualMachineEngine<V
 
     pub fn reset_configuration(&mut self, config: Configuration) {
         self.config = config;
+        self.vtable.set_float_display_precision(self.config.get_float_display_precision());
+        self.vtable.set_max_name_length(self.config.get_max_name_length());
 
         // Iterate through all instruction names and re-assign the weights for the instructions
         self.weights.reset_weights_from_configuration(&self.config);
@@ -89,14 +165,61 @@ impl<Vm: VirtualMachine + VirtualMachineMustHaveExec<Vm>> VirtualMachineEngine<V
         self.vtable.execute_fn(opcode)
     }
 
+    /// Applies the configured `DeepIndexPolicy` to an index into a stack with `len` items
+    pub fn deep_index(&self, position: i64, len: usize) -> i64 {
+        self.config.get_deep_index_policy().apply(position, len)
+    }
+
+    /// Returns a new instruction for the specified opcode with random data, or None if the opcode does not exist
+    pub fn random_value_for_opcode(&mut self, opcode: Opcode) -> Option<Code> {
+        let random_value_fn = self.vtable.random_value_fn(opcode)?;
+        Some(random_value_fn(self))
+    }
+
+    /// Returns the category of the instruction with the specified opcode or None
+    pub fn category_for_opcode(&self, opcode: Opcode) -> Option<InstructionCategory> {
+        self.vtable.category_for_opcode(opcode)
+    }
+
+    /// Checks that every opcode in the code (including the opcodes of lists) is registered with this engine, and
+    /// returns the first one that is not. Code that was parsed by this engine is always valid, but code that was built
+    /// by hand or deserialized should be checked before it is run, because running an unregistered opcode panics.
+    pub fn validate_code(&self, code: &Code) -> Result<(), Opcode> {
+        match code.subtrees().find(|item| self.vtable.name_for_opcode(item.get_opcode()).is_none()) {
+            Some(invalid) => Err(invalid.get_opcode()),
+            None => Ok(()),
+        }
+    }
+
     pub fn must_parse<'a>(&self, input: &'a str) -> Code {
         let (rest, code) = self.parse(input).unwrap();
         assert_eq!(rest.len(), 0);
         code
     }
 
+    /// Parses the leading program in the input and returns it along with the rest of the input, which has not been
+    /// parsed. Unlike `must_parse`, left-over input is not an error, so this can be used to parse a stream one program
+    /// at a time by passing the rest back in. White-space before the program is skipped. Returns an error if the input
+    /// does not start with a program.
+    pub fn try_parse_prefix<'a>(&self, input: &'a str) -> Result<(Code, &'a str), ParseError> {
+        let input = input.trim_start();
+        let (rest, code) = self.parse(input).map_err(ParseError::new)?;
+
+        // The parser also consumes the white-space after the program, but the rest starts right after the program
+        let end = input[..input.len() - rest.len()].trim_end().len();
+        Ok((code, &input[end..]))
+    }
+
     pub fn parse_and_set_code(&mut self, input: &str) -> Result<(), ParseError> {
         self.clear();
+        self.parse_and_push_code(input)
+    }
+
+    /// Parses the input and pushes it onto the Exec stack without clearing the stack or the defined names first
+    pub fn parse_and_push_code(&mut self, input: &str) -> Result<(), ParseError> {
+        if max_list_depth(input) > self.config.get_max_nesting_depth() {
+            return Err(ParseError::new_from_execution_error(ExecutionError::MaxDepthExceeded));
+        }
         let (rest, code) = self.parse(input).map_err(|e| ParseError::new(e))?;
         if rest.len() == 0 {
             self.exec_stack.push(code).map_err(|e| ParseError::new(nom::Err::Error(e.to_owned())))?;
@@ -111,15 +234,41 @@ impl<Vm: VirtualMachine + VirtualMachineMustHaveExec<Vm>> VirtualMachineEngine<V
         self.exec_stack.push(code).unwrap();
     }
 
+    /// Returns a fingerprint of the instruction set. Two engines have the same fingerprint only if they have the same
+    /// instructions with the same opcodes, which means that code created by one can be used by the other.
+    pub fn instruction_fingerprint(&self) -> u64 {
+        let mut hasher = FnvHasher::default();
+        let mut opcode = 0;
+        while let Some(name) = self.vtable.name_for_opcode(opcode) {
+            name.hash(&mut hasher);
+            opcode += 1;
+        }
+        hasher.finish()
+    }
+
     /// Returns the code for the specified name, or None if the name is not defined
     pub fn definition_for_name(&self, name: &Name) -> Option<Code> {
         self.defined_names.get(name).map(|c| c.clone())
     }
 
+    /// Defines the name as the code. This is used by the *.DEFINE instructions while code runs. When the configuration
+    /// limits the number of defined names and that limit has been reached, new names are not defined but existing
+    /// names may still be redefined.
     pub fn define_name(&mut self, name: Name, code: Code) {
+        if let Some(max_defined_names) = self.config.get_max_defined_names() {
+            if self.defined_names.len() >= max_defined_names && !self.defined_names.contains_key(&name) {
+                return;
+            }
+        }
         self.defined_names.insert(name, code);
     }
 
+    /// Defines every name in `defined_names`, such as the names an Individual has stored, before its code is run. The
+    /// configured limit on the number of defined names does not apply, so none of an Individual's names are dropped.
+    pub fn load_defined_names(&mut self, defined_names: &FnvHashMap<Name, Code>) {
+        self.defined_names.extend(defined_names.iter().map(|(name, code)| (name.clone(), code.clone())));
+    }
+
     /// Returns a list of all the names that are defined
     pub fn all_defined_names(&self) -> Vec<Name> {
         self.defined_names.keys().map(|k| k.clone()).collect()
@@ -151,48 +300,223 @@ impl<Vm: VirtualMachine + VirtualMachineMustHaveExec<Vm>> VirtualMachineEngine<V
     /// MAX-POINTS-IN-RANDOM-EXPRESSIONS parameter and the absolute value of the result is used.
     pub fn rand_code(&mut self, points: Option<usize>) -> Result<Code, ExecutionError> {
         let shape = self.generate_random_code_shape(points);
-        self.fill_code_shape(shape)
+        let code = self.fill_code_shape(shape)?;
+        self.check_nesting_depth(code)
+    }
+
+    /// Creates the code for one individual of an initial population. If the configuration has an init template, the
+    /// configured fraction of calls return a mutation of the template. Otherwise this is the same as `rand_code(None)`.
+    ///
+    /// Any of the configured required instructions that the code does not contain are then inserted at random places.
+    /// Returns InvalidOpcode if a required instruction is not registered with this engine.
+    pub fn rand_initial_code(&mut self) -> Result<Code, ExecutionError> {
+        let mut code = match self.config.get_init_template().cloned() {
+            Some(template) if self.rng.gen_bool(self.config.get_init_template_fraction()) => {
+                self.mutate_code(&template)?
+            }
+            _ => self.rand_code(None)?,
+        };
+
+        let required = self.config.get_required_instructions().to_vec();
+        for name in required {
+            let opcode = self.vtable.opcode_for_name(name).ok_or(ExecutionError::InvalidOpcode)?;
+            if !code.extract_atoms().iter().any(|atom| atom.get_opcode() == opcode) {
+                let instruction = self.random_value_for_opcode(opcode).ok_or(ExecutionError::InvalidOpcode)?;
+                code = self.insert_at_random_place(code, instruction)?;
+            }
+        }
+        Ok(code)
+    }
+
+    /// Creates one individual of an initial population, with code from `rand_initial_code`. The code is generated from
+    /// a new seed drawn from the engine's generator, which is saved as the individual's generation seed. Pass that seed
+    /// to `rand_initial_individual_with_seed` to generate the same code again.
+    pub fn rand_initial_individual<R: RunResult>(&mut self) -> Result<Individual<R>, ExecutionError> {
+        let seed = self.rng.next_u64();
+        self.rand_initial_individual_with_seed(seed)
+    }
+
+    /// Creates one individual of an initial population as for `rand_initial_individual`, except the code is generated
+    /// from the specified seed. The engine's own generator is left as it was.
+    pub fn rand_initial_individual_with_seed<R: RunResult>(
+        &mut self,
+        seed: u64,
+    ) -> Result<Individual<R>, ExecutionError> {
+        let code = self.with_generation_seed(seed, |engine| engine.rand_initial_code())?;
+        let mut individual = Individual::new(code, FnvHashMap::default(), None);
+        individual.set_generation_seed(Some(seed));
+        Ok(individual)
+    }
+
+    // Calls the function with the generator replaced by one of the same kind seeded from the seed, so that whatever the
+    // function generates can be reproduced from the seed alone. The engine's generator is restored afterward.
+    fn with_generation_seed<T, F: FnOnce(&mut Self) -> T>(&mut self, seed: u64, f: F) -> T {
+        let seeded = self.rng.reseeded(seed);
+        let saved = std::mem::replace(&mut self.rng, seeded);
+        let result = f(self);
+        self.rng = saved;
+        result
+    }
+
+    /// Checks that every one of the configured required instructions is registered with this engine, and returns the
+    /// name of the first one that is not.
+    pub fn validate_required_instructions(&self) -> Result<(), &'static str> {
+        match self.config.get_required_instructions().iter().find(|name| self.vtable.opcode_for_name(name).is_none()) {
+            Some(invalid) => Err(invalid),
+            None => Ok(()),
+        }
+    }
+
+    // Inserts the atom at a random position of a randomly picked list in the code. An atom has no list to insert into,
+    // so it is combined with the new atom into a list instead.
+    fn insert_at_random_place(&mut self, code: Code, atom: Code) -> Result<Code, ExecutionError> {
+        let lists: Vec<i64> = (0..code.points()).filter(|&point| extract_known_point(&code, point).is_list()).collect();
+        match lists.choose(&mut self.rng) {
+            Some(&point) => {
+                let mut items = extract_known_point(&code, point).to_list();
+                let position = self.rng.gen_range(0..=items.len());
+                items.insert(position, atom);
+                let (code, _) = code.replace_point(point, &Code::new_list(items)?)?;
+                Ok(code)
+            }
+            None => Code::new_list(if self.rng.gen_bool(0.5) { vec![code, atom] } else { vec![atom, code] }),
+        }
     }
 
     /// Produces a random child of the two individuals that is either a mutation of the left individual, or the genetic
     /// crossover of both.
     ///
-    /// The defined_names of the child will only include the code that is specifically named in the child's code. If
-    /// both parents have the same defined_name, the value for that will come from the left individual.
+    /// The defined_names of the child are inherited from the parents according to the configured `NameInheritance`.
+    ///
+    /// The child is generated from a new seed drawn from the engine's generator, which is saved as the child's generation
+    /// seed. Pass that seed and the same parents to `rand_child_with_seed` to produce the same child again.
     pub fn rand_child<R: RunResult>(
         &mut self,
         left: &Individual<R>,
         right: &Individual<R>,
     ) -> Result<Individual<R>, ExecutionError> {
-        match self.select_genetic_operation() {
-            GeneticOperation::Mutation => self.mutate(left),
-            GeneticOperation::Crossover => self.crossover(left, right),
-        }
+        let seed = self.rng.next_u64();
+        self.rand_child_with_seed(seed, left, right)
+    }
+
+    /// Produces a random child of the two individuals as for `rand_child`, except the child is generated from the
+    /// specified seed. The engine's own generator is left as it was.
+    pub fn rand_child_with_seed<R: RunResult>(
+        &mut self,
+        seed: u64,
+        left: &Individual<R>,
+        right: &Individual<R>,
+    ) -> Result<Individual<R>, ExecutionError> {
+        let mut child = self.with_generation_seed(seed, |engine| match engine.select_genetic_operation() {
+            GeneticOperation::Mutation => engine.mutate(left),
+            GeneticOperation::Crossover => engine.crossover(left, right),
+        })?;
+        child.set_generation_seed(Some(seed));
+        Ok(child)
     }
 
     /// Mutates the parent by randomly selecting a point in the code, generating a new random code item of the same
     /// size, and replacing the selected point with the new code.
     ///
-    /// The defined_names of the child will only include the code that is specifically named in the child's code.
+    /// The defined_names of the child are inherited from the parent according to the configured `NameInheritance`.
     pub fn mutate<R: RunResult>(&mut self, parent: &Individual<R>) -> Result<Individual<R>, ExecutionError> {
-        let (selected_point, replace_shape) = self.select_operation_point_and_shape(parent.get_code());
-        let replacement_code = self.fill_code_shape(replace_shape)?;
-        let (child_code, _) = parent.get_code().replace_point(selected_point, &replacement_code)?;
+        let child_code = self.mutate_code(parent.get_code())?;
         let mut child = Individual::new(child_code, FnvHashMap::default(), None);
 
         // Ensure the individuals defined_names are correct
-        let names = parent.get_code().extract_names();
-        child.set_specific_defined_names(&names[..], parent.get_defined_names());
+        match self.config.get_name_inheritance() {
+            NameInheritance::Union => child.set_defined_names(parent.get_defined_names().clone()),
+            NameInheritance::LeftPriority | NameInheritance::RightPriority => {
+                let names = parent.get_code().extract_names();
+                child.set_specific_defined_names(&names[..], parent.get_defined_names());
+            }
+            NameInheritance::None => {}
+        }
 
         Ok(child)
     }
 
+    // Replaces a random point in the code with new random code of the same size
+    fn mutate_code(&mut self, code: &Code) -> Result<Code, ExecutionError> {
+        let (selected_point, replace_shape) = self.select_operation_point_and_shape(code);
+        let replacement_code = self.fill_code_shape(replace_shape)?;
+        let (child_code, _) = code.replace_point(selected_point, &replacement_code)?;
+        self.check_nesting_depth(child_code)
+    }
+
+    // Returns the code unchanged, or MaxDepthExceeded if its lists are nested more deeply than the configuration allows
+    fn check_nesting_depth(&self, code: Code) -> Result<Code, ExecutionError> {
+        if code.depth() > self.config.get_max_nesting_depth() {
+            Err(ExecutionError::MaxDepthExceeded)
+        } else {
+            Ok(code)
+        }
+    }
+
+    /// Produces a child with exactly the same structure and instructions as the parent, but with some of its numeric
+    /// literals perturbed. Each integer and float literal is changed with probability `rate` (0.0 to 1.0; a rate of NaN
+    /// is treated as 0.0). Integers are moved by a uniformly random amount of up to `jitter` in either direction, and
+    /// floats have Gaussian noise added with a standard deviation of `jitter`. This is a cheap way to tune the constants
+    /// of an otherwise good program.
+    ///
+    /// The child has the same defined_names as the parent.
+    pub fn mutate_constants<R: RunResult>(&mut self, parent: &Individual<R>, rate: f64, jitter: f64) -> Individual<R> {
+        let integer_opcode = self.vtable.opcode_for_name(IntegerLiteralValue::static_name());
+        let float_opcode = self.vtable.opcode_for_name(FloatLiteralValue::static_name());
+        let rate = if rate.is_nan() { 0.0 } else { rate.clamp(0.0, 1.0) };
+        let child_code = self.jitter_constants(parent.get_code(), integer_opcode, float_opcode, rate, jitter);
+
+        Individual::new(child_code, parent.get_defined_names().clone(), None)
+    }
+
+    // Copies the code, perturbing the integer and float literals as described by `mutate_constants`
+    fn jitter_constants(
+        &mut self,
+        code: &Code,
+        integer_opcode: Option<Opcode>,
+        float_opcode: Option<Opcode>,
+        rate: f64,
+        jitter: f64,
+    ) -> Code {
+        if let Data::CodeList(list) = code.get_data() {
+            let list = list
+                .iter()
+                .map(|item| self.jitter_constants(item, integer_opcode, float_opcode, rate, jitter))
+                .collect();
+            return Code::new(code.get_opcode(), Data::CodeList(list));
+        }
+
+        let opcode = Some(code.get_opcode());
+        if (opcode != integer_opcode && opcode != float_opcode) || !self.rng.gen_bool(rate) {
+            return code.clone();
+        }
+
+        match code.get_data() {
+            Data::Integer(value) if opcode == integer_opcode => {
+                let max_offset = jitter.abs().round() as i64;
+                let offset = self.rng.gen_range(-max_offset..=max_offset);
+                Code::new(code.get_opcode(), Data::Integer(value.saturating_add(offset)))
+            }
+            Data::Decimal(value) => {
+                // Box-Muller transform of two uniform values into one standard normal value
+                let u1: f64 = 1.0 - self.rng.gen::<f64>();
+                let u2: f64 = self.rng.gen();
+                let noise = (-2.0 * u1.ln()).sqrt() * (2.0 * std::f64::consts::PI * u2).cos() * jitter;
+                match rust_decimal::Decimal::from_f64_retain(noise).and_then(|noise| value.checked_add(noise)) {
+                    Some(jittered) => Code::new(code.get_opcode(), Data::Decimal(jittered)),
+                    None => code.clone(),
+                }
+            }
+            _ => code.clone(),
+        }
+    }
+
     /// Produces a random child that is a crossover of both parents. A random point from the left tree will be selected
     /// and child create that has the selected point from that parent replaced with the code tree of a selected point of
     /// the right parent.
     ///
-    /// The defined_names of the child will only include the code that is specifically named in the child's code. If
-    /// both parents have the same defined_name, the value for that will come from the left individual.
+    /// The defined_names of the child are inherited from both parents according to the configured `NameInheritance`. By
+    /// default, if both parents have the same defined_name, the value for that will come from the left individual.
     pub fn crossover<R: RunResult>(
         &mut self,
         left: &Individual<R>,
@@ -209,14 +533,26 @@ impl<Vm: VirtualMachine + VirtualMachineMustHaveExec<Vm>> VirtualMachineEngine<V
 
         // Put in the left_code at the spot where it fits
         let (child_code, _) = right.get_code().replace_point(right_selected_point, &left_code)?;
+        let child_code = self.check_nesting_depth(child_code)?;
         let mut child = Individual::new(child_code, FnvHashMap::default(), None);
 
-        // Ensure the individuals defined_names are correct. Do the left parent last so that those defined names will
-        // take priority.
-        let names = right.get_code().extract_names();
-        child.set_specific_defined_names(&names[..], right.get_defined_names());
-        let names = left.get_code().extract_names();
-        child.set_specific_defined_names(&names[..], left.get_defined_names());
+        // Ensure the individuals defined_names are correct. The parent that is applied last takes priority.
+        match self.config.get_name_inheritance() {
+            NameInheritance::Union => {
+                let mut defined_names = right.get_defined_names().clone();
+                defined_names.extend(left.get_defined_names().iter().map(|(name, code)| (name.clone(), code.clone())));
+                child.set_defined_names(defined_names);
+            }
+            NameInheritance::LeftPriority => {
+                inherit_referenced_names(&mut child, right);
+                inherit_referenced_names(&mut child, left);
+            }
+            NameInheritance::RightPriority => {
+                inherit_referenced_names(&mut child, left);
+                inherit_referenced_names(&mut child, right);
+            }
+            NameInheritance::None => {}
+        }
 
         Ok(child)
     }
@@ -263,7 +599,11 @@ impl<Vm: VirtualMachine + VirtualMachineMustHaveExec<Vm>> VirtualMachineEngine<V
             CodeShape::Atom => {
                 // Determine how many total possibilities there are. This shifts depending upon how many defined_names we have.
                 let defined_names_total = if Vm::HAS_NAME {
-                    self.defined_names.len() * self.config.get_defined_name_weight() as usize
+                    let total = self.defined_names.len() * self.config.get_defined_name_weight() as usize;
+                    match self.config.get_defined_name_weight_cap() {
+                        Some(cap) => total.min(cap),
+                        None => total,
+                    }
                 } else {
                     0
                 };
@@ -336,6 +676,21 @@ impl<Vm: VirtualMachine + VirtualMachineMustHaveExec<Vm>> VirtualMachineEngine<V
     }
 }
 
+// The skipped operation count is a statistic about how the engine got to its current state, so two engines in the same
+// state are equal even if they skipped a different number of operations to get there.
+impl<Vm: VirtualMachine> PartialEq for VirtualMachineEngine<Vm> {
+    fn eq(&self, other: &Self) -> bool {
+        self.rng == other.rng
+            && self.exec_stack == other.exec_stack
+            && self.config == other.config
+            && self.weights == other.weights
+            && self.vtable == other.vtable
+            && self.defined_names == other.defined_names
+            && self.steps_remaining == other.steps_remaining
+            && self.context_id == other.context_id
+    }
+}
+
 impl<Vm: VirtualMachine> OpcodeConvertor for VirtualMachineEngine<Vm> {
     /// Returns the name for the specified opcode, or None if the opcode does not exist
     fn name_for_opcode(&self, opcode: Opcode) -> Option<&'static str> {
@@ -350,16 +705,12 @@ impl<Vm: VirtualMachine> OpcodeConvertor for VirtualMachineEngine<Vm> {
 
 impl<Vm: VirtualMachine> CodeParser for VirtualMachineEngine<Vm> {
     fn parse<'a>(&self, input: &'a str) -> nom::IResult<&'a str, Code> {
-        let parser = Parser::new(&self.vtable);
-        parser.parse(input)
-    }
-}
+        // Reject code that is nested too deeply before the recursive parser sees it
+        if max_list_depth(input) > self.config.get_max_nesting_depth() {
+            return Err(nom::Err::Failure(nom::error::make_error(input, nom::error::ErrorKind::TooLarge)));
+        }
 
-fn small_rng_from_optional_seed(rng_seed: Option<u64>) -> SmallRng {
-    if let Some(seed) = rng_seed {
-        SmallRng::seed_from_u64(seed)
-    } else {
-        SmallRng::from_entropy()
+        Parser::new(&self.vtable).parse(input)
     }
 }
 
@@ -373,8 +724,283 @@ fn extract_known_point(code: &Code, point: i64) -> Code {
     }
 }
 
+// Copies the defined names that are referenced in the parent's code to the child, replacing any the child already has
+fn inherit_referenced_names<R: RunResult>(child: &mut Individual<R>, parent: &Individual<R>) {
+    let names = parent.get_code().extract_names();
+    child.set_specific_defined_names(&names[..], parent.get_defined_names());
+}
+
 #[derive(Clone, Debug)]
 enum CodeShape {
     Atom,
     List(Vec<CodeShape>),
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use fnv::FnvHashMap;
+
+    #[derive(Clone, Debug, PartialEq)]
+    struct TestResult {}
+
+    impl RunResult for TestResult {}
+
+    fn new_base_vm(name_inheritance: NameInheritance) -> BaseVm {
+        let mut config = Configuration::new_simple();
+        config.set_name_inheritance(name_inheritance);
+        let mut vm = BaseVm::new(Some(1), config);
+        add_base_instructions(&mut vm);
+        add_base_literals(&mut vm);
+        vm
+    }
+
+    #[test]
+    fn split_rng() {
+        use rand::Rng;
+
+        let mut vm = new_base_vm(NameInheritance::LeftPriority);
+        let values = |index: u64, vm: &BaseVm| -> Vec<u64> {
+            let mut rng = vm.engine().split_rng(index);
+            (0..8).map(|_| rng.gen()).collect()
+        };
+
+        // Splitting is deterministic and does not advance the engine's generator
+        let engine_before = vm.engine().clone();
+        assert_eq!(values(3, &vm), values(3, &vm));
+        assert_eq!(&engine_before, vm.engine());
+
+        // Different indices produce different streams
+        assert_ne!(values(3, &vm), values(4, &vm));
+        assert_ne!(values(0, &vm), values(1, &vm));
+
+        // A different engine state produces different streams for the same index
+        let before = values(3, &vm);
+        vm.get_rng().gen::<u64>();
+        assert_ne!(before, values(3, &vm));
+    }
+
+    #[test]
+    fn generation_seed() {
+        let mut vm = new_base_vm(NameInheritance::LeftPriority);
+        let initial: Individual<TestResult> = vm.engine_mut().rand_initial_individual().unwrap();
+        let seed = initial.get_generation_seed().unwrap();
+
+        // Regenerating from the seed produces the same code without advancing the engine's generator
+        let engine_before = vm.engine().clone();
+        let regenerated: Individual<TestResult> = vm.engine_mut().rand_initial_individual_with_seed(seed).unwrap();
+        assert_eq!(&engine_before, vm.engine());
+        assert_eq!(initial, regenerated);
+        assert_eq!(Some(seed), regenerated.get_generation_seed());
+
+        let left = new_individual(&vm, "( 1 2 ( 3 4 ) INTEGER.SUM )", &[]);
+        let right = new_individual(&vm, "( TRUE ( FALSE BOOL.AND ) 5.0 )", &[]);
+        assert_eq!(None, left.get_generation_seed());
+        for _ in 0..10 {
+            let child = vm.engine_mut().rand_child(&left, &right).unwrap();
+            let seed = child.get_generation_seed().unwrap();
+            assert_eq!(child, vm.engine_mut().rand_child_with_seed(seed, &left, &right).unwrap());
+        }
+
+        // Each new individual draws a different seed
+        let next: Individual<TestResult> = vm.engine_mut().rand_initial_individual().unwrap();
+        assert_ne!(Some(seed), next.get_generation_seed());
+    }
+
+    fn new_individual(vm: &BaseVm, src: &str, definitions: &[(&str, &str)]) -> Individual<TestResult> {
+        let mut defined_names = FnvHashMap::default();
+        for (name, definition) in definitions {
+            defined_names.insert((*name).into(), vm.engine().must_parse(definition));
+        }
+        Individual::new(vm.engine().must_parse(src), defined_names, None)
+    }
+
+    fn crossover_names(name_inheritance: NameInheritance) -> FnvHashMap<Name, Code> {
+        let mut vm = new_base_vm(name_inheritance);
+        let left = new_individual(&vm, "( LEFT SHARED )", &[("LEFT", "1"), ("SHARED", "2"), ("UNUSED", "3")]);
+        let right = new_individual(&vm, "( RIGHT SHARED )", &[("RIGHT", "4"), ("SHARED", "5")]);
+        let child = vm.engine_mut().crossover(&left, &right).unwrap();
+        child.get_defined_names().clone()
+    }
+
+    #[test]
+    fn required_instructions() {
+        let mut vm = new_base_vm(NameInheritance::LeftPriority);
+        let mut config = vm.engine().get_configuration().clone();
+        config.set_required_instructions(vec!["INTEGER.SUM", "EXEC.IF"]);
+        vm.engine_mut().reset_configuration(config.clone());
+        assert_eq!(Ok(()), vm.engine().validate_required_instructions());
+
+        let sum = IntegerSum::new_code(vm.engine());
+        let exec_if = ExecIf::new_code(vm.engine());
+        for _ in 0..200 {
+            let atoms = vm.engine_mut().rand_initial_code().unwrap().extract_atoms();
+            assert!(atoms.contains(&sum));
+            assert!(atoms.contains(&exec_if));
+        }
+
+        config.set_required_instructions(vec!["INTEGER.SUM", "NOT.AN.INSTRUCTION"]);
+        vm.engine_mut().reset_configuration(config);
+        assert_eq!(Err("NOT.AN.INSTRUCTION"), vm.engine().validate_required_instructions());
+        assert_eq!(Err(ExecutionError::InvalidOpcode), vm.engine_mut().rand_initial_code());
+    }
+
+    #[test]
+    fn name_inheritance_union() {
+        let vm = new_base_vm(NameInheritance::Union);
+        let names = crossover_names(NameInheritance::Union);
+        assert_eq!(4, names.len());
+        assert_eq!(Some(&vm.engine().must_parse("1")), names.get(&"LEFT".into()));
+        assert_eq!(Some(&vm.engine().must_parse("2")), names.get(&"SHARED".into()));
+        assert_eq!(Some(&vm.engine().must_parse("3")), names.get(&"UNUSED".into()));
+        assert_eq!(Some(&vm.engine().must_parse("4")), names.get(&"RIGHT".into()));
+    }
+
+    #[test]
+    fn name_inheritance_priority() {
+        let vm = new_base_vm(NameInheritance::LeftPriority);
+        let names = crossover_names(NameInheritance::LeftPriority);
+        assert_eq!(3, names.len());
+        assert_eq!(Some(&vm.engine().must_parse("2")), names.get(&"SHARED".into()));
+        assert_eq!(None, names.get(&"UNUSED".into()));
+
+        let names = crossover_names(NameInheritance::RightPriority);
+        assert_eq!(3, names.len());
+        assert_eq!(Some(&vm.engine().must_parse("5")), names.get(&"SHARED".into()));
+    }
+
+    #[test]
+    fn name_inheritance_none() {
+        assert!(crossover_names(NameInheritance::None).is_empty());
+    }
+
+    // Returns the fraction of random atoms that are the definition of a defined name when `count` names are defined
+    fn defined_name_fraction(cap: Option<usize>, count: usize) -> f64 {
+        let mut vm = new_base_vm(NameInheritance::LeftPriority);
+        let mut config = Configuration::new_simple();
+        config.set_defined_name_weight_cap(cap);
+        vm.engine_mut().reset_configuration(config);
+        let definition = vm.engine().must_parse("DEFINITION");
+        for i in 0..count {
+            vm.engine_mut().define_name(format!("NAME{}", i).as_str().into(), definition.clone());
+        }
+
+        let picks = 10_000;
+        let defined = (0..picks).filter(|_| vm.engine_mut().rand_code(Some(1)).unwrap() == definition).count();
+        defined as f64 / picks as f64
+    }
+
+    #[test]
+    fn defined_name_weight_cap() {
+        // Without a cap, names crowd out the instructions as definitions accumulate
+        assert!(defined_name_fraction(None, 1_000) > 0.7);
+
+        // With a cap, a thousand definitions are picked no more often than the cap allows
+        let few = defined_name_fraction(Some(20), 20);
+        let many = defined_name_fraction(Some(20), 1_000);
+        assert!(many < few * 1.25, "20 names picked {} of the time but 1000 names picked {}", few, many);
+    }
+
+    #[test]
+    fn max_defined_names() {
+        let mut vm = new_base_vm(NameInheritance::LeftPriority);
+        let mut config = Configuration::new_simple();
+        config.set_max_defined_names(Some(3));
+        vm.engine_mut().reset_configuration(config);
+
+        let one = vm.engine().must_parse("1");
+        let two = vm.engine().must_parse("2");
+        for i in 0..10 {
+            vm.engine_mut().define_name(format!("NAME{}", i).as_str().into(), one.clone());
+        }
+        assert_eq!(3, vm.engine().all_defined_names().len());
+        assert_eq!(None, vm.engine().definition_for_name(&"NAME3".into()));
+
+        // Names that are already defined can still be redefined at the cap
+        vm.engine_mut().define_name("NAME0".into(), two.clone());
+        assert_eq!(3, vm.engine().all_defined_names().len());
+        assert_eq!(Some(two.clone()), vm.engine().definition_for_name(&"NAME0".into()));
+
+        // The names an individual has stored are all loaded, even past the cap
+        vm.clear();
+        let stored: FnvHashMap<Name, Code> =
+            (0..5).map(|i| (format!("NAME{}", i).as_str().into(), two.clone())).collect();
+        vm.engine_mut().load_defined_names(&stored);
+        assert_eq!(5, vm.engine().all_defined_names().len());
+        vm.engine_mut().define_name("NAME5".into(), one);
+        assert_eq!(5, vm.engine().all_defined_names().len());
+    }
+
+    #[test]
+    fn try_parse_prefix() {
+        let vm = new_base_vm(NameInheritance::LeftPriority);
+        let (code, rest) = vm.engine().try_parse_prefix("( A B ) GARBAGE").unwrap();
+        assert_eq!(vm.engine().must_parse("( A B )"), code);
+        assert_eq!(" GARBAGE", rest);
+
+        let (code, rest) = vm.engine().try_parse_prefix(rest).unwrap();
+        assert_eq!(vm.engine().must_parse("GARBAGE"), code);
+        assert_eq!("", rest);
+        assert!(vm.engine().try_parse_prefix(")").is_err());
+    }
+
+    #[test]
+    fn validate_code() {
+        let vm = new_base_vm(NameInheritance::LeftPriority);
+        let valid = vm.engine().must_parse("( 1 ( TRUE BOOL.NOT ) A INTEGER.SUM )");
+        assert_eq!(Ok(()), vm.engine().validate_code(&valid));
+
+        let bogus = 1_000_000;
+        let invalid =
+            Code::new_list(vec![vm.engine().must_parse("( 1 2 )"), Code::new(bogus, Data::None), valid]).unwrap();
+        assert_eq!(Err(bogus), vm.engine().validate_code(&invalid));
+    }
+
+    #[test]
+    fn expected_random_points() {
+        let mut vm = new_base_vm(NameInheritance::LeftPriority);
+        let samples = 10_000;
+        let total: i64 = (0..samples).map(|_| vm.engine_mut().rand_code(None).unwrap().points()).sum();
+        let sample_mean = total as f64 / samples as f64;
+
+        let expected = vm.engine().get_configuration().expected_random_points();
+        assert_eq!(50.5, expected);
+        assert!((sample_mean - expected).abs() < 1.5, "sample mean {} but expected {}", sample_mean, expected);
+    }
+
+    #[test]
+    fn mutate_constants() {
+        let mut vm = new_base_vm(NameInheritance::LeftPriority);
+        let parent = new_individual(&vm, "( 5 ( 2.5 INTEGER.SUM ) TRUE A 7 )", &[("A", "1")]);
+
+        // A rate of zero changes nothing
+        let child = vm.engine_mut().mutate_constants(&parent, 0.0, 3.0);
+        assert!(child.genome_eq(&parent));
+
+        // A rate of NaN is treated as zero rather than panicking
+        let child = vm.engine_mut().mutate_constants(&parent, f64::NAN, 3.0);
+        assert!(child.genome_eq(&parent));
+
+        // A rate of one changes only the literals, and keeps the structure
+        for _ in 0..20 {
+            let child = vm.engine_mut().mutate_constants(&parent, 1.0, 3.0);
+            assert_eq!(parent.get_defined_names(), child.get_defined_names());
+            assert_eq!(parent.get_code().points(), child.get_code().points());
+            assert_eq!(parent.get_code().depth(), child.get_code().depth());
+            let parent_atoms = parent.get_code().extract_atoms();
+            let child_atoms = child.get_code().extract_atoms();
+            for (index, (before, after)) in parent_atoms.iter().zip(child_atoms.iter()).enumerate() {
+                assert_eq!(before.get_opcode(), after.get_opcode());
+                match index {
+                    0 | 5 => {
+                        let difference =
+                            before.get_data().integer_value().unwrap() - after.get_data().integer_value().unwrap();
+                        assert!(difference.abs() <= 3, "{:?} jittered to {:?}", before, after);
+                    }
+                    1 => assert_ne!(before, after),
+                    _ => assert_eq!(before, after),
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,56 @@
+use crate::{ExitStatus, RunResult};
+
+/// Wraps the RunResult of an individual along with whether its program halted on its own. Programs that are stopped
+/// by the instruction limit can be ranked below programs that halted by passing their score through `penalize`, which
+/// pushes evolution toward programs that terminate. The penalty is configured for each result, so different islands
+/// can weigh halting differently.
+#[derive(Clone, Debug, PartialEq)]
+pub struct WithHaltPenalty<R: RunResult> {
+    result: R,
+    halted: bool,
+    penalty: u64,
+}
+
+impl<R: RunResult> WithHaltPenalty<R> {
+    /// Wraps the result of a run that ended with the exit status. Only a run that exceeded the instruction count is
+    /// considered to not have halted; a run that stopped for any other reason did not run out of time.
+    pub fn new(result: R, exit_status: &ExitStatus, penalty: u64) -> WithHaltPenalty<R> {
+        let halted = !matches!(exit_status, ExitStatus::ExceededInstructionCount(_));
+        WithHaltPenalty { result, halted, penalty }
+    }
+
+    /// Borrows the wrapped RunResult
+    pub fn get_result(&self) -> &R {
+        &self.result
+    }
+
+    /// Returns true if the program halted before reaching the instruction limit
+    pub fn halted(&self) -> bool {
+        self.halted
+    }
+
+    /// Returns the penalty that is subtracted from the score of a program that did not halt
+    pub fn get_penalty(&self) -> u64 {
+        self.penalty
+    }
+
+    /// Returns the score unchanged if the program halted, or else the score less the penalty (but not less than zero).
+    /// Call this from `IslandCallbacks::score_individual` with the score calculated from the wrapped result.
+    ///
+    /// Because the score stops at zero, a program that did not halt and scores no more than the penalty ties with a
+    /// halting program that scores zero. Override `IslandCallbacks::sort_individuals` to compare `halted` before the
+    /// score if every halting program must rank above every program that did not halt.
+    pub fn penalize(&self, score: u64) -> u64 {
+        if self.halted {
+            score
+        } else {
+            score.saturating_sub(self.penalty)
+        }
+    }
+}
+
+impl<R: RunResult> RunResult for WithHaltPenalty<R> {
+    fn is_valid(&self) -> bool {
+        self.result.is_valid()
+    }
+}
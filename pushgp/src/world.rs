@@ -1,5 +1,5 @@
 use crate::*;
-use fnv::FnvHashMap;
+use fnv::{FnvHashMap, FnvHashSet};
 use rand::{prelude::SliceRandom, Rng};
 use std::vec;
 
@@ -8,6 +8,7 @@ pub type IslandId = usize;
 const RETRIES: usize = 5;
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct WorldConfiguration {
     /// The number of individuals on each island. Before running a generation, the island will be filled with the
     /// children of genetic selection if there was a previous generation, or new random individuals if there was no
@@ -22,8 +23,9 @@ pub struct WorldConfiguration {
     /// to disable automatic migrations.
     pub generations_between_migrations: usize,
 
-    /// The number of individuals that will migrate from one island to another.
-    pub number_of_individuals_migrating: usize,
+    /// The number of individuals that will migrate from one island to another. This can be a fixed number, a random
+    /// number drawn from a range, or a fraction of each island's population.
+    pub number_of_individuals_migrating: MigrationCount,
 
     /// When it is time for a migration, a new island will be selected for the individual according to the specified
     /// algorithm.
@@ -33,6 +35,11 @@ pub struct WorldConfiguration {
     /// individuals are cloned and the clone is moved. The default is true
     pub clone_migrated_individuals: bool,
 
+    /// When individuals are not cloned for migration, an island will stop sending emigrants once its current
+    /// generation is down to this many individuals. This keeps every island able to select parents for the next
+    /// generation. The default is 1
+    pub minimum_island_population: usize,
+
     /// The SelectionCurve that will be used when choosing which individual will participate in migration. The default
     /// is PreferenceForFit.
     pub select_for_migration: SelectionCurve,
@@ -45,6 +52,29 @@ pub struct WorldConfiguration {
     /// StrongPreferenceForFit.
     pub select_as_elite: SelectionCurve,
 
+    /// The number of the most fit individuals ever seen that each island keeps in its hall of fame. The hall of fame
+    /// survives `Island::reset_preserving_hof`. Set to zero to disable. The default is 10.
+    pub hall_of_fame_size: usize,
+
+    /// The number of fresh random individuals added to each island every generation. They take the place of the least
+    /// fit children that would otherwise have been bred, which keeps new genetic material flowing into a population that
+    /// has converged. Set to zero to disable. The default is 0.
+    pub random_immigrants_per_generation: usize,
+
+    /// The number of the most fit individuals across all islands whose code will be copied as-is to the next generation
+    /// of the island they live on. Unlike `elite_individuals_per_generation`, this protects the very best individuals
+    /// no matter which island found them. Individuals are compared by the score their own island gives them. Set to
+    /// zero to disable. The default is 0.
+    pub global_elite_individuals: usize,
+
+    /// When set, individuals are evaluated lazily: a generation starts without running anyone, and parents, elites and
+    /// migrants are picked by tournaments between this many random individuals instead of by the selection curves.
+    /// Only the individuals drawn into a tournament are run, so individuals that are never drawn are never evaluated.
+    /// This can greatly reduce the cost of very expensive fitness functions. The islands are not sorted in this mode, so
+    /// the hall of fame, global elites and migration effectiveness are not updated. The default is None, which runs
+    /// every individual every generation.
+    pub lazy_tournament_size: Option<usize>,
+
     /// Determine how the world runs with regards to multi-threading. Placeholder: currently multi-threading is not
     /// implemented
     pub threading_model: ThreadingModel,
@@ -56,29 +86,70 @@ impl Default for WorldConfiguration {
             individuals_per_island: 100,
             elite_individuals_per_generation: 2,
             generations_between_migrations: 10,
-            number_of_individuals_migrating: 10,
+            number_of_individuals_migrating: MigrationCount::Fixed(10),
             migration_algorithm: MigrationAlgorithm::Circular,
             clone_migrated_individuals: true,
+            minimum_island_population: 1,
             select_for_migration: SelectionCurve::PreferenceForFit,
             select_as_parent: SelectionCurve::PreferenceForFit,
             select_as_elite: SelectionCurve::StrongPreferenceForFit,
+            hall_of_fame_size: 10,
+            random_immigrants_per_generation: 0,
+            global_elite_individuals: 0,
+            lazy_tournament_size: None,
             threading_model: ThreadingModel::None,
         }
     }
 }
 
+/// The position of the random number generator used by a World, returned by `World::rng_state`. Restoring it with
+/// `World::set_rng_state` makes the World draw exactly the same random numbers that it drew after the snapshot was taken.
+///
+/// The state is the seed of the VM's generator and the number of words it has drawn since, so it is small and can be
+/// saved with a checkpoint (with the `serde` feature) and restored in another process. Restoring it replays the words
+/// drawn, which takes time in proportion to their number.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RngState {
+    seed: u64,
+    words_drawn: u64,
+}
+
 #[derive(Clone, Debug, PartialEq)]
 pub struct World<R: RunResult, Vm: VirtualMachine> {
     vm: Vm,
     config: WorldConfiguration,
     islands: Vec<Island<R, Vm>>,
+    island_capacity: Option<usize>,
     generations_remaining_before_migration: usize,
+
+    // Each migrant since the last generation was run, along with the island it migrated to
+    recent_migrants: Vec<(IslandId, Individual<R>)>,
+    migrants_evaluated: usize,
+    migrants_became_most_fit: usize,
 }
 
 impl<R: RunResult, Vm: VirtualMachine> World<R, Vm> {
     pub fn new(vm: Vm, config: WorldConfiguration) -> World<R, Vm> {
         let generations_remaining_before_migration = config.generations_between_migrations;
-        World { vm, config, islands: vec![], generations_remaining_before_migration }
+        World {
+            vm,
+            config,
+            islands: vec![],
+            island_capacity: None,
+            generations_remaining_before_migration,
+            recent_migrants: vec![],
+            migrants_evaluated: 0,
+            migrants_became_most_fit: 0,
+        }
+    }
+
+    /// Creates a World that can have no more than `capacity` islands. Creating an island beyond the capacity is an
+    /// error, which guards against accidentally creating islands in a loop.
+    pub fn with_island_capacity(vm: Vm, config: WorldConfiguration, capacity: usize) -> World<R, Vm> {
+        let mut world = World::new(vm, config);
+        world.island_capacity = Some(capacity);
+        world
     }
 
     pub fn get_vm(&self) -> &Vm {
@@ -89,13 +160,49 @@ impl<R: RunResult, Vm: VirtualMachine> World<R, Vm> {
         &mut self.vm
     }
 
+    /// Returns the current state of every random number generator used by the World. Islands with an init seed do not
+    /// keep a generator of their own between generations (they reseed from the init seed whenever they are empty), so
+    /// this is only the state of the VM's generator. Keep it alongside a copy of the population to resume the run later
+    /// with exactly the same random numbers as an uninterrupted run. See `RngState`.
+    ///
+    /// Returns None if the VM's generator was plugged in with `EngineRng::new`, because its seed is not known. Use
+    /// `EngineRng::reseeded` to plug in a generator of that kind whose state can be saved.
+    pub fn rng_state(&self) -> Option<RngState> {
+        let rng = self.vm.engine().rng();
+        rng.get_seed().map(|seed| RngState { seed, words_drawn: rng.get_words_drawn() })
+    }
+
+    /// Restores the random number generator to a state returned by `rng_state`. The VM must use the same kind of
+    /// generator that it used when the state was saved.
+    pub fn set_rng_state(&mut self, state: RngState) {
+        let mut rng = self.vm.get_rng().reseeded(state.seed);
+        rng.skip_words(state.words_drawn);
+        self.vm.engine_mut().set_rng(rng);
+    }
+
     /// Adds a new island to the World that will use the specified callbacks to perform the various individual
-    /// processing tasks required during its lifetime
+    /// processing tasks required during its lifetime. Panics if the World is already at its island capacity; use
+    /// `try_create_island` to handle that case.
     pub fn create_island(&mut self, callbacks: Box<dyn IslandCallbacks<R, Vm>>) -> IslandId {
+        match self.try_create_island(callbacks) {
+            Ok(id) => id,
+            Err(err) => panic!("{}", err),
+        }
+    }
+
+    /// Adds a new island to the World as for `create_island`, or returns an error if the World is already at the
+    /// capacity it was created with
+    pub fn try_create_island(&mut self, callbacks: Box<dyn IslandCallbacks<R, Vm>>) -> Result<IslandId, WorldError> {
+        if let Some(capacity) = self.island_capacity {
+            if self.islands.len() >= capacity {
+                return Err(WorldError::IslandCapacityExceeded(capacity));
+            }
+        }
+
         let id = self.islands.len();
-        self.islands.push(Island::new(callbacks));
+        self.islands.push(Island::new(callbacks, self.config.hall_of_fame_size));
 
-        id
+        Ok(id)
     }
 
     /// Returns the total number of islands
@@ -103,14 +210,14 @@ impl<R: RunResult, Vm: VirtualMachine> World<R, Vm> {
         self.islands.len()
     }
 
-    /// Borrows an island by the specified ID
-    pub fn get_island(&self, id: IslandId) -> Option<&Island<R, Vm>> {
-        self.islands.get(id)
+    /// Borrows an island by the specified ID, or returns IslandNotFound if there is no such island
+    pub fn get_island(&self, id: IslandId) -> Result<&Island<R, Vm>, WorldError> {
+        self.islands.get(id).ok_or(WorldError::IslandNotFound(id))
     }
 
-    /// Mutably borrows an island by the specified ID
-    pub fn get_island_mut(&mut self, id: IslandId) -> Option<&mut Island<R, Vm>> {
-        self.islands.get_mut(id)
+    /// Mutably borrows an island by the specified ID, or returns IslandNotFound if there is no such island
+    pub fn get_island_mut(&mut self, id: IslandId) -> Result<&mut Island<R, Vm>, WorldError> {
+        self.islands.get_mut(id).ok_or(WorldError::IslandNotFound(id))
     }
 
     /// Removes all individuals from all islands
@@ -118,13 +225,19 @@ impl<R: RunResult, Vm: VirtualMachine> World<R, Vm> {
         for island in self.islands.iter_mut() {
             island.clear();
         }
+        self.recent_migrants.clear();
     }
 
-    /// Runs the next generation across all islands.
+    /// Runs the next generation across all islands. Frozen islands are skipped.
     pub fn run_one_generation(&mut self) {
-        for island in self.islands.iter_mut() {
-            island.run_one_generation(&mut self.vm);
+        for island in self.islands.iter_mut().filter(|island| !island.is_frozen()) {
+            if self.config.lazy_tournament_size.is_some() {
+                island.start_lazy_generation(&mut self.vm);
+            } else {
+                island.run_one_generation(&mut self.vm);
+            }
         }
+        self.update_migration_effectiveness();
 
         // See if it is time for a migration
         if self.config.generations_between_migrations > 0 {
@@ -136,55 +249,373 @@ impl<R: RunResult, Vm: VirtualMachine> World<R, Vm> {
         }
     }
 
+    /// Returns the fraction of migrants that became the most fit individual on their destination island in the first
+    /// generation run after they arrived. Returns zero if no migrants have been evaluated yet. This can be used to judge
+    /// whether migration is actually helping the islands.
+    pub fn migration_effectiveness(&self) -> f64 {
+        if self.migrants_evaluated == 0 {
+            0.0
+        } else {
+            self.migrants_became_most_fit as f64 / self.migrants_evaluated as f64
+        }
+    }
+
+    // Checks whether each migrant that has arrived since the last generation is now the most fit on its island. Lazy
+    // generations are not sorted, so there is no most fit individual to compare against and the migrants are not counted.
+    fn update_migration_effectiveness(&mut self) {
+        if self.config.lazy_tournament_size.is_some() {
+            self.recent_migrants.clear();
+            return;
+        }
+        for (island_id, migrant) in self.recent_migrants.drain(..) {
+            self.migrants_evaluated += 1;
+            if let Some(most_fit) = self.islands[island_id].most_fit_individual() {
+                if most_fit.genome_eq(&migrant) {
+                    self.migrants_became_most_fit += 1;
+                }
+            }
+        }
+    }
+
+    /// Returns up to `count` of the most fit individuals across all islands, most fit first, along with the island each
+    /// one lives on. Individuals are compared by the score their own island gives them, so this is only meaningful when
+    /// the islands score on the same scale. Islands whose current generation has not been run are skipped.
+    pub fn global_most_fit(&self, count: usize) -> Vec<(IslandId, &Individual<R>)> {
+        let mut scored = vec![];
+        for (id, island) in self.islands.iter().enumerate() {
+            if island.most_fit_individual().is_some() {
+                for index in 0..island.len() {
+                    scored.push((island.score_for_individual(index).unwrap(), id, index));
+                }
+            }
+        }
+
+        // The islands sort the most fit to the tail, so ties go to the later index
+        scored.sort_by(|a, b| b.0.cmp(&a.0).then(b.2.cmp(&a.2)));
+        scored
+            .into_iter()
+            .take(count)
+            .map(|(_, id, index)| (id, self.islands[id].get_one_individual(index).unwrap()))
+            .collect()
+    }
+
     /// Fills all islands with the children of the genetic algorithm, or with random individuals if there was no
-    /// previous generation from which to draw upon.
+    /// previous generation from which to draw upon. When there was a previous generation, the global elites and then
+    /// the island's elites are added first, then `random_immigrants_per_generation` random individuals, and children
+    /// fill whatever room is left. Frozen islands are skipped. Panics if the islands cannot be filled; use
+    /// `try_fill_all_islands` to handle that case.
     pub fn fill_all_islands(&mut self) {
-        for island in self.islands.iter_mut() {
-            let mut elite_remaining = self.config.elite_individuals_per_generation;
-            while island.len_future_generation() < self.config.individuals_per_island {
-                self.vm.engine_mut().clear();
-
-                let next = if island.len() == 0 {
-                    run_with_retry(|| {
-                        let code = self.vm.engine_mut().rand_code(None)?;
-                        Ok(Individual::new(code, FnvHashMap::default(), None))
-                    }).expect("Unable to generate new code that doesn't use excessive number of Code in list. Check configuration.")
-                } else {
-                    if elite_remaining > 0 {
-                        elite_remaining -= 1;
-                        island.select_one_individual(self.config.select_as_elite, self.vm.get_rng()).unwrap().clone()
-                    } else {
-                        run_with_retry(|| {
-                            let left =
-                                island.select_one_individual(self.config.select_as_parent, self.vm.get_rng()).unwrap();
-                            let right =
-                                island.select_one_individual(self.config.select_as_parent, self.vm.get_rng()).unwrap();
-                            self.vm.engine_mut().rand_child(left, right)
-                        }).expect("Unable to generate child that doesn't use excessive number of Code in list. Check configuration.")
-                    }
-                };
-                island.add_individual_to_future_generation(next);
+        if let Err(err) = self.try_fill_all_islands() {
+            panic!("{}", err);
+        }
+    }
+
+    /// Fills all islands as for `fill_all_islands`, or returns an error if the configured threading model is not
+    /// supported, the VM has no instructions to generate code from, a required instruction is not registered with the
+    /// VM, the answer stack is not one of the VM's stacks, or an island could not be filled with valid code.
+    /// An island that failed may have been partially filled, but the islands before it have been filled completely.
+    pub fn try_fill_all_islands(&mut self) -> Result<(), WorldError> {
+        if self.config.threading_model != ThreadingModel::None {
+            return Err(WorldError::ThreadingUnsupported(self.config.threading_model.clone()));
+        }
+        if self.vm.engine().get_weights().get_sum_of_weights() == 0 {
+            return Err(WorldError::NoInstructions);
+        }
+        self.vm.engine().validate_required_instructions().map_err(WorldError::UnknownRequiredInstruction)?;
+        self.vm.validate_answer_stack().map_err(WorldError::UnknownAnswerStack)?;
+
+        let global_elites: Vec<(IslandId, Individual<R>)> = self
+            .global_most_fit(self.config.global_elite_individuals)
+            .into_iter()
+            .map(|(id, individual)| (id, individual.clone()))
+            .collect();
+
+        for (id, island) in self.islands.iter_mut().enumerate().filter(|(_, island)| !island.is_frozen()) {
+            for (_, elite) in global_elites.iter().filter(|(elite_id, _)| *elite_id == id) {
+                island.add_individual_to_future_generation(elite.clone());
             }
 
+            // An island with an init seed generates its initial population with its own RNG. Swap it in for the VM's
+            // RNG and restore the VM's RNG once the island is full.
+            let saved_rng = match island.get_init_seed() {
+                Some(seed) if island.len() == 0 => {
+                    let seeded = self.vm.get_rng().reseeded(seed);
+                    Some(std::mem::replace(self.vm.get_rng(), seeded))
+                }
+                _ => None,
+            };
+
+            let filled = Self::fill_future_generation(id, island, &mut self.vm, &self.config);
+
+            if let Some(rng) = saved_rng {
+                *self.vm.get_rng() = rng;
+            }
+            filled?;
+
             // Now that the future generation is full, make it the current generation
             island.advance_generation();
         }
+
+        Ok(())
     }
 
-    /// Runs generations until the specified function returns false
-    pub fn run_generations_while<While>(&mut self, mut while_fn: While)
+    // Adds individuals to the island's future generation until it is full. Returns IslandNotRun if elites and parents
+    // are needed but the current generation has not been run, or GenerationFailed if a valid individual could not be
+    // generated.
+    fn fill_future_generation(
+        id: IslandId,
+        island: &mut Island<R, Vm>,
+        vm: &mut Vm,
+        config: &WorldConfiguration,
+    ) -> Result<(), WorldError> {
+        // Selection needs a sorted population, except in lazy mode where the tournaments run individuals as needed
+        let needs_selection = island.len() > 0 && island.len_future_generation() < config.individuals_per_island;
+        if needs_selection && config.lazy_tournament_size.is_none() && island.most_fit_individual().is_none() {
+            return Err(WorldError::IslandNotRun(id));
+        }
+
+        let generation_failed = || WorldError::GenerationFailed(id);
+        let mut elite_remaining = config.elite_individuals_per_generation;
+        let mut immigrants_remaining = config.random_immigrants_per_generation;
+        while island.len_future_generation() < config.individuals_per_island {
+            vm.engine_mut().clear();
+
+            let next = if island.len() == 0 {
+                run_with_retry(|| vm.engine_mut().rand_initial_individual()).ok_or_else(generation_failed)?
+            } else if elite_remaining > 0 {
+                elite_remaining -= 1;
+                if let Some(tournament_size) = config.lazy_tournament_size {
+                    let index = island.select_one_individual_lazily(tournament_size, vm).unwrap();
+                    island.get_one_individual(index).unwrap().clone()
+                } else {
+                    island.select_one_individual(config.select_as_elite, vm.get_rng()).unwrap().clone()
+                }
+            } else if immigrants_remaining > 0 {
+                immigrants_remaining -= 1;
+                run_with_retry(|| vm.engine_mut().rand_initial_individual()).ok_or_else(generation_failed)?
+            } else {
+                run_with_retry(|| {
+                    if let Some(tournament_size) = config.lazy_tournament_size {
+                        let left = island.select_one_individual_lazily(tournament_size, vm).unwrap();
+                        let right = island.select_one_individual_lazily(tournament_size, vm).unwrap();
+                        vm.engine_mut().rand_child(
+                            island.get_one_individual(left).unwrap(),
+                            island.get_one_individual(right).unwrap(),
+                        )
+                    } else {
+                        let left = island.select_one_individual(config.select_as_parent, vm.get_rng()).unwrap();
+                        let right = island.select_one_individual(config.select_as_parent, vm.get_rng()).unwrap();
+                        vm.engine_mut().rand_child(left, right)
+                    }
+                })
+                .ok_or_else(generation_failed)?
+            };
+            island.add_individual_to_future_generation(next);
+        }
+
+        Ok(())
+    }
+
+    /// Runs generations until the specified function returns false. Panics if the islands cannot be filled; use
+    /// `try_run_generations_while` to handle that case.
+    pub fn run_generations_while<While>(&mut self, while_fn: While)
+    where
+        While: FnMut(&World<R, Vm>) -> bool,
+    {
+        if let Err(err) = self.try_run_generations_while(while_fn) {
+            panic!("{}", err);
+        }
+    }
+
+    /// Runs generations as for `run_generations_while`, or stops and returns the error if the islands cannot be filled
+    /// for a generation. See `try_fill_all_islands`.
+    pub fn try_run_generations_while<While>(&mut self, mut while_fn: While) -> Result<(), WorldError>
     where
         While: FnMut(&World<R, Vm>) -> bool,
     {
         // Always run at least one generation
         let mut running = true;
         while running {
-            self.fill_all_islands();
+            self.try_fill_all_islands()?;
             self.run_one_generation();
             running = while_fn(self);
         }
+        Ok(())
+    }
+
+    /// Runs generations until the specified function returns false or the population converges, whichever comes first.
+    /// The population has converged once the genotypic diversity of all the islands together falls below the
+    /// diversity_floor, which means that the population has collapsed into copies of a few programs and further
+    /// generations are unlikely to find anything new. This complements stopping when the fitness stops improving.
+    ///
+    /// Returns true if the run stopped because the population converged.
+    pub fn run_until_converged<While>(&mut self, diversity_floor: f64, mut while_fn: While) -> bool
+    where
+        While: FnMut(&World<R, Vm>) -> bool,
+    {
+        loop {
+            self.fill_all_islands();
+            self.run_one_generation();
+            if self.genotypic_diversity() < diversity_floor {
+                return true;
+            }
+            if !while_fn(self) {
+                return false;
+            }
+        }
+    }
+
+    /// Returns the genotypic diversity of the current generation of all the islands together: the number of unique
+    /// programs divided by the number of individuals. See `Island::genotypic_diversity`.
+    pub fn genotypic_diversity(&self) -> f64 {
+        let mut unique = FnvHashSet::default();
+        let mut total = 0;
+        for island in self.islands.iter() {
+            for index in 0..island.len() {
+                if let Some(individual) = island.get_one_individual(index) {
+                    unique.insert(individual.get_code().stable_hash());
+                    total += 1;
+                }
+            }
+        }
+        if total == 0 {
+            1.0
+        } else {
+            unique.len() as f64 / total as f64
+        }
+    }
+
+    /// Runs generations in phases. Each phase is a tuple of (generations, crossover_rate, mutation_rate): the VM's
+    /// crossover and mutation rates are set for the phase and then that many generations are run. This allows a common
+    /// tuning strategy such as a high mutation rate early in a run, and a lower rate once the population has started to
+    /// converge. The VM's configuration is restored once the schedule is complete.
+    pub fn run_with_schedule(&mut self, schedule: &[(usize, u8, u8)]) {
+        let original_config = self.vm.engine().get_configuration().clone();
+        for &(generations, crossover_rate, mutation_rate) in schedule {
+            let mut config = original_config.clone();
+            config.set_crossover_rate(crossover_rate);
+            config.set_mutation_rate(mutation_rate);
+            self.vm.engine_mut().reset_configuration(config);
+
+            for _ in 0..generations {
+                self.fill_all_islands();
+                self.run_one_generation();
+            }
+        }
+        self.vm.engine_mut().reset_configuration(original_config);
+    }
+
+    /// Reads programs from the reader, one program per line, and adds them to the future generation of the islands in
+    /// round-robin order. The programs are written by instruction name, as produced by `Code::for_display`. The
+    /// `source_fingerprint` is the `instruction_fingerprint` of the VM that produced the programs; the import is
+    /// rejected if it does not match this World's VM. Nothing is imported if any program fails to parse.
+    ///
+    /// Returns the number of programs imported.
+    pub fn import_programs<Rd: std::io::BufRead>(
+        &mut self,
+        reader: Rd,
+        source_fingerprint: u64,
+    ) -> Result<usize, ParseError> {
+        if source_fingerprint != self.vm.engine().instruction_fingerprint() {
+            return Err(ParseError::new_with_message("the programs were created with an incompatible instruction set"));
+        }
+        if self.islands.is_empty() {
+            return Err(ParseError::new_with_message("there are no islands to import the programs to"));
+        }
+
+        let mut programs = vec![];
+        for line in reader.lines() {
+            let line = line.map_err(ParseError::new_with_message)?;
+            let line = line.trim();
+            if !line.is_empty() {
+                let (rest, code) = self.vm.engine().parse(line).map_err(ParseError::new)?;
+                if !rest.is_empty() {
+                    return Err(ParseError::new_with_message("the code did not finish parsing"));
+                }
+                programs.push(code);
+            }
+        }
+
+        let count = programs.len();
+        let island_len = self.islands.len();
+        for (i, code) in programs.into_iter().enumerate() {
+            let individual = Individual::new(code, FnvHashMap::default(), None);
+            self.islands[i % island_len].add_individual_to_future_generation(individual);
+        }
+
+        Ok(count)
+    }
+
+    /// Writes the current generation of every island to the writer as source text, for inspection outside of the
+    /// program. Each island starts with a header line such as `# island 0 (25 individuals)`, followed by each of its
+    /// individuals as written by `Individual::to_source`.
+    pub fn dump_population<W: std::io::Write>(&self, mut writer: W) -> std::io::Result<()> {
+        for (id, island) in self.islands.iter().enumerate() {
+            writeln!(writer, "# island {} ({} individuals)", id, island.len())?;
+            for index in 0..island.len() {
+                if let Some(individual) = island.get_one_individual(index) {
+                    writer.write_all(individual.to_source(&self.vm).as_bytes())?;
+                }
+            }
+        }
+        Ok(())
     }
 
+    /// Combines the populations of islands `a` and `b` into island `a` and removes island `b` from the World, which
+    /// allows the population structure to change during a run, such as collapsing specialized islands late in a run.
+    /// The combined current generation is ranked by island `a`'s callbacks and the least fit are dropped so that no
+    /// more than `individuals_per_island` remain; the future generation of `b` is kept as well. The callbacks, hall of
+    /// fame and settings of island `b` are discarded.
+    ///
+    /// Every island after `b` moves down one ID to fill the gap, and a cyclical or incremental migration distance is
+    /// reduced so that migrants never return to their own island. Returns the new ID of the merged island,
+    /// IslandNotFound if either island does not exist, or IslandNotRun if either island has individuals whose current
+    /// generation has not been run and so cannot be ranked. Merging an island with itself does nothing.
+    pub fn merge_islands(&mut self, a: IslandId, b: IslandId) -> Result<IslandId, WorldError> {
+        for id in [a, b] {
+            let island = self.get_island(id)?;
+            if island.len() > 0 && island.most_fit_individual().is_none() {
+                return Err(WorldError::IslandNotRun(id));
+            }
+        }
+        if a == b {
+            return Ok(a);
+        }
+
+        let removed = self.islands.remove(b);
+        let merged_id = if a > b { a - 1 } else { a };
+        self.islands[merged_id].merge(removed, self.config.individuals_per_island);
+
+        // Migrants that arrived on either island are now on the merged island
+        for (id, _) in self.recent_migrants.iter_mut() {
+            if *id == b {
+                *id = merged_id;
+            } else if *id > b {
+                *id -= 1;
+            }
+        }
+
+        // Keep the migration distance from wrapping around to the island the migrants came from
+        let shorten = |n: usize| std::cmp::max(1, self.island_at_distance(0, n));
+        let next_algorithm = match &self.config.migration_algorithm {
+            MigrationAlgorithm::Cyclical(n) => MigrationAlgorithm::Cyclical(shorten(*n)),
+            MigrationAlgorithm::Incremental(n) => MigrationAlgorithm::Incremental(shorten(*n)),
+            other => other.clone(),
+        };
+        self.config.migration_algorithm = next_algorithm;
+
+        Ok(merged_id)
+    }
+
+    /// Migrates individuals between the islands immediately, using all of the migration settings in the
+    /// WorldConfiguration. This is the same migration that `run_one_generation` performs automatically every
+    /// `generations_between_migrations` generations. Set `generations_between_migrations` to zero and call this from a
+    /// custom loop to decide exactly when migrations happen.
+    pub fn migrate_now(&mut self) {
+        self.migrate_individuals_between_islands();
+    }
+
+    /// Migrates individuals between the islands using the configured migration algorithm. See `migrate_now`.
     pub fn migrate_individuals_between_islands(&mut self) {
         let island_len = self.islands.len();
 
@@ -222,7 +653,7 @@ impl<R: RunResult, Vm: VirtualMachine> World<R, Vm> {
                     // For each migrating individual on each island, pick a random destination that is not the same
                     // island and migrate there.
                     for source_island_id in 0..len {
-                        for _ in 0..self.config.number_of_individuals_migrating {
+                        for _ in 0..self.number_migrating_from(source_island_id) {
                             let mut destination_island_id = source_island_id;
                             while source_island_id != destination_island_id {
                                 destination_island_id = self.vm.get_rng().gen_range(0..len);
@@ -231,10 +662,54 @@ impl<R: RunResult, Vm: VirtualMachine> World<R, Vm> {
                         }
                     }
                 }
+                MigrationAlgorithm::Weighted => {
+                    for source_island_id in 0..self.islands.len() {
+                        for _ in 0..self.number_migrating_from(source_island_id) {
+                            if let Some(destination_island_id) = self.weighted_destination(source_island_id) {
+                                self.migrate_one_individual_from_island_to_island(
+                                    source_island_id,
+                                    destination_island_id,
+                                );
+                            }
+                        }
+                    }
+                }
             }
         }
     }
 
+    /// Checks that the configured migration algorithm makes sense for the current islands. A `Cyclical` or
+    /// `Incremental` distance that is a multiple of the number of islands would send individuals back to their own
+    /// island, and `Weighted` migration needs at least two islands with a positive weight so that every island has
+    /// somewhere to send its migrants, and weights that add up to a finite number. A World with fewer than two islands
+    /// never migrates, so it is always valid.
+    pub fn validate_migration(&self) -> Result<(), WorldError> {
+        let island_len = self.islands.len();
+        if island_len < 2 {
+            return Ok(());
+        }
+
+        match self.config.migration_algorithm {
+            MigrationAlgorithm::Cyclical(n) | MigrationAlgorithm::Incremental(n) if n % island_len == 0 => {
+                Err(WorldError::InvalidMigration(format!(
+                    "a distance of {} would return migrants to their own island when there are {} islands",
+                    n, island_len
+                )))
+            }
+            MigrationAlgorithm::Weighted
+                if self.islands.iter().filter(|island| island.get_weight() > 0.0).count() < 2 =>
+            {
+                Err(WorldError::InvalidMigration("fewer than two islands have a positive weight".to_owned()))
+            }
+            MigrationAlgorithm::Weighted
+                if !self.islands.iter().map(|island| island.get_weight().max(0.0)).sum::<f64>().is_finite() =>
+            {
+                Err(WorldError::InvalidMigration("the island weights do not add up to a finite number".to_owned()))
+            }
+            _ => Ok(()),
+        }
+    }
+
     fn migrate_one_individual_from_island_to_island(
         &mut self,
         source_island_id: IslandId,
@@ -242,9 +717,28 @@ impl<R: RunResult, Vm: VirtualMachine> World<R, Vm> {
     ) {
         let curve = self.config.select_for_migration;
 
-        // Get the migrating individual from the source island
+        // Frozen islands neither send nor receive migrants
+        if self.islands[source_island_id].is_frozen() || self.islands[destination_island_id].is_frozen() {
+            return;
+        }
+
+        // Get the migrating individual from the source island, unless removing it would take the island below its
+        // minimum population
         let source_island = self.islands.get_mut(source_island_id).unwrap();
-        let migrating: Individual<R> = if self.config.clone_migrated_individuals {
+        if !self.config.clone_migrated_individuals && source_island.len() <= self.config.minimum_island_population {
+            return;
+        }
+        let migrating: Individual<R> = if let Some(tournament_size) = self.config.lazy_tournament_size {
+            let index = match source_island.select_one_individual_lazily(tournament_size, &mut self.vm) {
+                Some(index) => index,
+                None => return,
+            };
+            if self.config.clone_migrated_individuals {
+                source_island.get_one_individual(index).unwrap().clone()
+            } else {
+                source_island.remove_individual(index).unwrap()
+            }
+        } else if self.config.clone_migrated_individuals {
             source_island.select_one_individual(curve, self.vm.get_rng()).unwrap().clone()
         } else {
             source_island.select_and_remove_one_individual(curve, self.vm.get_rng()).unwrap()
@@ -252,9 +746,47 @@ impl<R: RunResult, Vm: VirtualMachine> World<R, Vm> {
 
         // Add it to the destination island
         let destination_island = self.islands.get_mut(destination_island_id).unwrap();
+        self.recent_migrants.push((destination_island_id, migrating.clone()));
         destination_island.add_individual_to_future_generation(migrating);
     }
 
+    // Picks a destination island other than the source in proportion to the weight of each island. Returns None if
+    // none of the other islands has a positive weight, or if the weights do not add up to a finite number.
+    fn weighted_destination(&mut self, source_island_id: IslandId) -> Option<IslandId> {
+        let weight_of = |(id, island): (IslandId, &Island<R, Vm>)| {
+            if id == source_island_id {
+                0.0
+            } else {
+                island.get_weight().max(0.0)
+            }
+        };
+        let total: f64 = self.islands.iter().enumerate().map(weight_of).sum();
+        if total <= 0.0 || !total.is_finite() {
+            return None;
+        }
+
+        let mut pick = self.vm.get_rng().gen_range(0.0..total);
+        let mut last_positive = None;
+        for (id, weight) in self.islands.iter().enumerate().map(|entry| (entry.0, weight_of(entry))) {
+            if weight > 0.0 {
+                if pick < weight {
+                    return Some(id);
+                }
+                pick -= weight;
+                last_positive = Some(id);
+            }
+        }
+
+        // Rounding can leave a tiny amount of the pick over after the last island
+        last_positive
+    }
+
+    // Decides how many individuals migrate from the island in this migration
+    fn number_migrating_from(&mut self, source_island_id: IslandId) -> usize {
+        let population = self.islands[source_island_id].len();
+        self.config.number_of_individuals_migrating.count(population, self.vm.get_rng())
+    }
+
     // Calculates the ID of the island at a specific distance from the source. Wraps around when we get to the end of
     // the list.
     fn island_at_distance(&self, source_id: IslandId, distance: usize) -> IslandId {
@@ -269,7 +801,7 @@ impl<R: RunResult, Vm: VirtualMachine> World<R, Vm> {
 
     fn migrate_one_island_circular_n(&mut self, source_island_id: IslandId, n: usize) {
         let destination_island_id = self.island_at_distance(source_island_id, n);
-        for _ in 0..self.config.number_of_individuals_migrating {
+        for _ in 0..self.number_migrating_from(source_island_id) {
             self.migrate_one_individual_from_island_to_island(source_island_id, destination_island_id);
         }
     }
@@ -297,6 +829,67 @@ impl<R: RunResult, Vm: VirtualMachine> World<R, Vm> {
         distances
     }
 
+    /// Counts, across the current generation of every island, how many programs use each instruction at least once.
+    /// Every instruction known to the virtual machine is included so that instructions which evolution never uses
+    /// show up with a count of zero.
+    pub fn instruction_coverage(&self) -> FnvHashMap<&'static str, usize> {
+        let mut coverage: FnvHashMap<&'static str, usize> =
+            self.vm.engine().get_weights().get_instruction_names().into_iter().map(|name| (name, 0)).collect();
+        for island in self.islands.iter() {
+            for individual in (0..island.len()).filter_map(|index| island.get_one_individual(index)) {
+                let used: FnvHashSet<&'static str> = individual
+                    .get_code()
+                    .extract_atoms()
+                    .iter()
+                    .map(|atom| self.vm.name_for_opcode(atom.get_opcode()).unwrap())
+                    .collect();
+                for name in used {
+                    *(coverage.entry(name).or_insert(0)) += 1;
+                }
+            }
+        }
+
+        coverage
+    }
+
+    /// Estimates how much each instruction contributes to the fitness of the most fit individual across all islands.
+    /// Each instruction used by the individual's code is replaced everywhere it appears with an empty list (which does
+    /// nothing when executed) and the fitness of the result is compared with the fitness of the unchanged code. Literals,
+    /// names and the code of defined names are left alone.
+    ///
+    /// The `fitness_fn` runs an individual on the VM and returns its fitness, where higher is better. The returned map
+    /// holds the unchanged fitness minus the fitness without the instruction, so a large positive value marks an
+    /// instruction that the program depends upon. The map is empty if no island has run its current generation.
+    pub fn ablate_instructions<F>(&mut self, mut fitness_fn: F) -> FnvHashMap<&'static str, f64>
+    where
+        F: FnMut(&mut Vm, &Individual<R>) -> f64,
+    {
+        let mut contributions = FnvHashMap::default();
+        let best = match self.global_most_fit(1).first() {
+            Some((_, individual)) => {
+                Individual::new(individual.get_code().clone(), individual.get_defined_names().clone(), None)
+            }
+            None => return contributions,
+        };
+        let baseline = fitness_fn(&mut self.vm, &best);
+
+        let mut instructions = best.get_code().extract_atoms();
+        instructions.retain(|atom| matches!(atom.get_data(), Data::None));
+        instructions.sort_by_key(|atom| atom.get_opcode());
+        instructions.dedup();
+        let nothing = Code::new_list(vec![]).unwrap();
+        for instruction in instructions {
+            // Swapping an atom for an empty list does not change the number of points, so this cannot fail
+            let mut ablated = best.clone();
+            ablated.set_code(best.get_code().replace(&instruction, &nothing).unwrap());
+            let fitness = fitness_fn(&mut self.vm, &ablated);
+            let name = self.vm.name_for_opcode(instruction.get_opcode()).unwrap();
+            contributions.insert(name, baseline - fitness);
+        }
+
+        contributions
+    }
+
     /// Generates 10 random individuals per island per run. The instructions in the most fit and least fit individual
     /// are counted and a determination made as to which instructions most benefit, and which cause the most harm, to
     /// the population as a whole.
@@ -311,12 +904,17 @@ impl<R: RunResult, Vm: VirtualMachine> World<R, Vm> {
             individuals_per_island: 10,
             elite_individuals_per_generation: 0,
             generations_between_migrations: 0,
-            number_of_individuals_migrating: 0,
+            number_of_individuals_migrating: MigrationCount::Fixed(0),
             migration_algorithm: MigrationAlgorithm::Circular,
             clone_migrated_individuals: true,
+            minimum_island_population: 1,
             select_for_migration: SelectionCurve::Fair,
             select_as_parent: SelectionCurve::Fair,
             select_as_elite: SelectionCurve::Fair,
+            hall_of_fame_size: 0,
+            random_immigrants_per_generation: 0,
+            global_elite_individuals: 0,
+            lazy_tournament_size: None,
             threading_model: ThreadingModel::None,
         };
         std::mem::swap(&mut self.config, &mut swap_config);
@@ -402,3 +1000,1222 @@ fn run_with_retry<R: RunResult, F: FnMut() -> Result<Individual<R>, ExecutionErr
 
     code
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use fnv::FnvHashMap;
+
+    #[derive(Clone, Debug, PartialEq)]
+    struct TestResult(u64);
+
+    impl RunResult for TestResult {}
+
+    // Scores each individual by the number of points in its code, so the most fit individuals are the largest
+    #[derive(Clone)]
+    struct TestIsland {}
+
+    impl IslandCallbacks<TestResult, BaseVm> for TestIsland {
+        fn clone(&self) -> Box<dyn IslandCallbacks<TestResult, BaseVm>> {
+            Box::new(TestIsland {})
+        }
+
+        fn run_individual(&mut self, vm: &mut BaseVm, individual: &mut Individual<TestResult>) {
+            vm.clear();
+            vm.engine_mut().set_code(individual.get_code().clone());
+            vm.run(100);
+            individual.set_run_result(Some(TestResult(individual.get_code().points() as u64)));
+        }
+
+        fn score_individual(&self, i: &Individual<TestResult>) -> u64 {
+            i.get_run_result().map(|r| r.0).unwrap_or(0)
+        }
+    }
+
+    // Scores the same way as TestIsland, but counts how many times an individual is scored
+    #[derive(Clone)]
+    struct CountingIsland {
+        scores_calculated: std::sync::Arc<std::sync::atomic::AtomicUsize>,
+    }
+
+    impl IslandCallbacks<TestResult, BaseVm> for CountingIsland {
+        fn clone(&self) -> Box<dyn IslandCallbacks<TestResult, BaseVm>> {
+            Box::new(CountingIsland { scores_calculated: self.scores_calculated.clone() })
+        }
+
+        fn run_individual(&mut self, vm: &mut BaseVm, individual: &mut Individual<TestResult>) {
+            TestIsland {}.run_individual(vm, individual)
+        }
+
+        fn score_individual(&self, i: &Individual<TestResult>) -> u64 {
+            self.scores_calculated.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            TestIsland {}.score_individual(i)
+        }
+    }
+
+    // Scores the same way as TestIsland, but records the VM's (crossover, mutation) rates each time an individual is run
+    #[derive(Clone)]
+    struct RateRecordingIsland {
+        rates: std::sync::Arc<std::sync::Mutex<Vec<(u8, u8)>>>,
+    }
+
+    impl IslandCallbacks<TestResult, BaseVm> for RateRecordingIsland {
+        fn clone(&self) -> Box<dyn IslandCallbacks<TestResult, BaseVm>> {
+            Box::new(RateRecordingIsland { rates: self.rates.clone() })
+        }
+
+        fn run_individual(&mut self, vm: &mut BaseVm, individual: &mut Individual<TestResult>) {
+            let config = vm.engine().get_configuration();
+            self.rates.lock().unwrap().push((config.get_crossover_rate(), config.get_mutation_rate()));
+            TestIsland {}.run_individual(vm, individual)
+        }
+
+        fn score_individual(&self, i: &Individual<TestResult>) -> u64 {
+            TestIsland {}.score_individual(i)
+        }
+    }
+
+    // Runs each individual and records the top INTEGER as its result
+    #[derive(Clone)]
+    struct TopIntegerIsland {}
+
+    impl IslandCallbacks<TestResult, BaseVm> for TopIntegerIsland {
+        fn clone(&self) -> Box<dyn IslandCallbacks<TestResult, BaseVm>> {
+            Box::new(TopIntegerIsland {})
+        }
+
+        fn run_individual(&mut self, vm: &mut BaseVm, individual: &mut Individual<TestResult>) {
+            vm.clear();
+            vm.engine_mut().set_code(individual.get_code().clone());
+            vm.run(100);
+            individual.set_run_result(Some(TestResult(vm.integer().pop().unwrap_or(0) as u64)));
+        }
+
+        fn score_individual(&self, i: &Individual<TestResult>) -> u64 {
+            i.get_run_result().map(|r| r.0).unwrap_or(0)
+        }
+    }
+
+    // Runs each individual the same way as TopIntegerIsland, but programs that do not halt within 100 instructions are
+    // penalized by 5
+    #[derive(Clone)]
+    struct HaltPenaltyIsland {}
+
+    impl IslandCallbacks<WithHaltPenalty<TestResult>, BaseVm> for HaltPenaltyIsland {
+        fn clone(&self) -> Box<dyn IslandCallbacks<WithHaltPenalty<TestResult>, BaseVm>> {
+            Box::new(HaltPenaltyIsland {})
+        }
+
+        fn run_individual(&mut self, vm: &mut BaseVm, individual: &mut Individual<WithHaltPenalty<TestResult>>) {
+            vm.clear();
+            vm.engine_mut().set_code(individual.get_code().clone());
+            let exit_status = vm.run(100);
+            let result = TestResult(vm.integer().pop().unwrap_or(0) as u64);
+            individual.set_run_result(Some(WithHaltPenalty::new(result, &exit_status, 5)));
+        }
+
+        fn score_individual(&self, i: &Individual<WithHaltPenalty<TestResult>>) -> u64 {
+            i.get_run_result().map(|r| r.penalize(r.get_result().0)).unwrap_or(0)
+        }
+    }
+
+    // A result that is only valid for programs with fewer than five points, so the most fit programs are all invalid
+    #[derive(Clone, Debug, PartialEq)]
+    struct ValidatedResult {
+        score: u64,
+        valid: bool,
+    }
+
+    impl RunResult for ValidatedResult {
+        fn is_valid(&self) -> bool {
+            self.valid
+        }
+    }
+
+    #[derive(Clone)]
+    struct ValidatingIsland {}
+
+    impl IslandCallbacks<ValidatedResult, BaseVm> for ValidatingIsland {
+        fn clone(&self) -> Box<dyn IslandCallbacks<ValidatedResult, BaseVm>> {
+            Box::new(ValidatingIsland {})
+        }
+
+        fn run_individual(&mut self, _vm: &mut BaseVm, individual: &mut Individual<ValidatedResult>) {
+            let points = individual.get_code().points() as u64;
+            individual.set_run_result(Some(ValidatedResult { score: points, valid: points < 5 }));
+        }
+
+        fn score_individual(&self, i: &Individual<ValidatedResult>) -> u64 {
+            i.get_run_result().map(|r| r.score).unwrap_or(0)
+        }
+    }
+
+    fn new_world(config: WorldConfiguration) -> World<TestResult, BaseVm> {
+        let mut vm = BaseVm::new(Some(1), Configuration::new_simple());
+        add_base_instructions(&mut vm);
+        add_base_literals(&mut vm);
+        World::new(vm, config)
+    }
+
+    fn population<R: RunResult>(island: &Island<R, BaseVm>) -> Vec<Individual<R>> {
+        (0..island.len()).map(|i| island.get_one_individual(i).unwrap().clone()).collect()
+    }
+
+    #[test]
+    fn rng_state_restores_the_random_stream() {
+        let config = WorldConfiguration { individuals_per_island: 10, ..Default::default() };
+        let mut world = new_world(config);
+        world.create_island(Box::new(TestIsland {}));
+        world.create_island(Box::new(TestIsland {}));
+        world.fill_all_islands();
+        world.run_one_generation();
+
+        let state = world.rng_state().unwrap();
+        #[cfg(feature = "serde")]
+        let state: RngState = serde_json::from_str(&serde_json::to_string(&state).unwrap()).unwrap();
+        let mut resumed = world.clone();
+        resumed.get_vm_mut().engine_mut().set_rng_seed(Some(99));
+        let mut diverged = resumed.clone();
+        resumed.set_rng_state(state);
+
+        let populations = |world: &World<TestResult, BaseVm>| {
+            (0..2).map(|id| population(world.get_island(id).unwrap())).collect::<Vec<_>>()
+        };
+        for _ in 0..3 {
+            for world in [&mut world, &mut resumed, &mut diverged] {
+                world.fill_all_islands();
+                world.run_one_generation();
+            }
+        }
+        assert_eq!(10, populations(&world)[1].len());
+        assert_eq!(populations(&world), populations(&resumed));
+        assert!(world == resumed);
+
+        // Without restoring the state, the children are bred with different random numbers
+        assert_ne!(populations(&world), populations(&diverged));
+
+        // The state of a plugged-in generator can only be saved once it has been seeded
+        use rand::{Rng, SeedableRng};
+        let rng = EngineRng::new(rand::rngs::StdRng::seed_from_u64(1));
+        world.get_vm_mut().engine_mut().set_rng(rng.clone());
+        assert_eq!(None, world.rng_state());
+        world.get_vm_mut().engine_mut().set_rng(rng.reseeded(5));
+        world.get_vm_mut().get_rng().gen::<u32>();
+        let state = world.rng_state().unwrap();
+        let expected = world.get_vm_mut().get_rng().gen::<u64>();
+        resumed.get_vm_mut().engine_mut().set_rng(rng);
+        resumed.set_rng_state(state);
+        assert_eq!(expected, resumed.get_vm_mut().get_rng().gen::<u64>());
+    }
+
+    #[test]
+    fn frozen_island_is_unchanged() {
+        let config =
+            WorldConfiguration { individuals_per_island: 10, generations_between_migrations: 1, ..Default::default() };
+        let mut world = new_world(config);
+        let frozen_id = world.create_island(Box::new(TestIsland {}));
+        let thawed_id = world.create_island(Box::new(TestIsland {}));
+        world.fill_all_islands();
+        world.run_one_generation();
+
+        world.get_island_mut(frozen_id).unwrap().set_frozen(true);
+        let frozen_before = population(world.get_island(frozen_id).unwrap());
+        let thawed_before = population(world.get_island(thawed_id).unwrap());
+        for _ in 0..3 {
+            world.fill_all_islands();
+            world.run_one_generation();
+        }
+
+        assert!(world.get_island(frozen_id).unwrap().is_frozen());
+        assert_eq!(frozen_before, population(world.get_island(frozen_id).unwrap()));
+        assert_ne!(thawed_before, population(world.get_island(thawed_id).unwrap()));
+    }
+
+    #[test]
+    fn init_template() {
+        let config = WorldConfiguration { individuals_per_island: 100, ..Default::default() };
+        let mut world = new_world(config);
+        let template = world.get_vm().engine().must_parse("( 1 2 3 4 5 6 7 8 9 10 INTEGER.SUM )");
+        let mut vm_config = Configuration::new_simple();
+        vm_config.set_init_template(Some(template.clone()));
+        vm_config.set_init_template_fraction(0.5);
+        world.get_vm_mut().engine_mut().reset_configuration(vm_config);
+        let id = world.create_island(Box::new(TestIsland {}));
+        world.fill_all_islands();
+
+        // A mutation of the template replaces one point, so at most one of its top-level items can differ
+        let template_items = template.to_list();
+        let variants = population(world.get_island(id).unwrap())
+            .iter()
+            .filter(|individual| {
+                let items = individual.get_code().to_list();
+                items.len() == template_items.len()
+                    && items.iter().zip(template_items.iter()).filter(|(a, b)| a == b).count() + 1 >= items.len()
+            })
+            .count();
+        assert!(variants > 30 && variants < 70, "{} of 100 individuals were template variants", variants);
+    }
+
+    #[test]
+    fn import_programs() {
+        let mut world = new_world(WorldConfiguration::default());
+        let first_id = world.create_island(Box::new(TestIsland {}));
+        let second_id = world.create_island(Box::new(TestIsland {}));
+        let fingerprint = world.get_vm().engine().instruction_fingerprint();
+
+        let source = "( 1 2 INTEGER.SUM )\n( TRUE BOOL.NOT )\n\n3.5\n";
+        assert_eq!(3, world.import_programs(source.as_bytes(), fingerprint).unwrap());
+        assert_eq!(2, world.get_island(first_id).unwrap().len_future_generation());
+        assert_eq!(1, world.get_island(second_id).unwrap().len_future_generation());
+
+        // Programs from an incompatible instruction set are rejected
+        assert!(world.import_programs(source.as_bytes(), fingerprint + 1).is_err());
+        assert_eq!(2, world.get_island(first_id).unwrap().len_future_generation());
+        assert_eq!(1, world.get_island(second_id).unwrap().len_future_generation());
+    }
+
+    #[test]
+    fn hall_of_fame_survives_reset() {
+        let config = WorldConfiguration { individuals_per_island: 10, hall_of_fame_size: 3, ..Default::default() };
+        let mut world = new_world(config);
+        let id = world.create_island(Box::new(TestIsland {}));
+
+        let mut best_scores = vec![];
+        for _ in 0..5 {
+            world.fill_all_islands();
+            world.run_one_generation();
+            let island = world.get_island(id).unwrap();
+            best_scores.extend((0..island.len()).map(|i| island.score_for_individual(i).unwrap()));
+        }
+        best_scores.sort();
+        best_scores.reverse();
+
+        let island = world.get_island_mut(id).unwrap();
+        island.reset_preserving_hof();
+        assert_eq!(0, island.len());
+        let hall_of_fame = island.hall_of_fame();
+        assert_eq!(3, hall_of_fame.len());
+        assert_eq!(best_scores[0], hall_of_fame[2].get_run_result().unwrap().0);
+        assert!(hall_of_fame[0].get_run_result().unwrap().0 <= hall_of_fame[1].get_run_result().unwrap().0);
+
+        island.clear();
+        assert_eq!(0, island.hall_of_fame().len());
+    }
+
+    #[test]
+    fn init_seed_reproduces_initial_population() {
+        let config = WorldConfiguration { individuals_per_island: 10, ..Default::default() };
+        let mut world = new_world(config.clone());
+        let unseeded_id = world.create_island(Box::new(TestIsland {}));
+        let first_id = world.create_island(Box::new(TestIsland {}));
+        let second_id = world.create_island(Box::new(TestIsland {}));
+        world.get_island_mut(first_id).unwrap().set_init_seed(42);
+        world.get_island_mut(second_id).unwrap().set_init_seed(42);
+        world.fill_all_islands();
+
+        let first = population(world.get_island(first_id).unwrap());
+        assert_eq!(10, first.len());
+        assert_eq!(first, population(world.get_island(second_id).unwrap()));
+        assert_ne!(first, population(world.get_island(unseeded_id).unwrap()));
+
+        // The init seed reseeds the same kind of generator that the VM uses
+        use rand::SeedableRng;
+        let mut world = new_world(config.clone());
+        world.get_vm_mut().engine_mut().set_rng(EngineRng::new(rand::rngs::StdRng::seed_from_u64(1)));
+        let seeded_id = world.create_island(Box::new(TestIsland {}));
+        world.get_island_mut(seeded_id).unwrap().set_init_seed(42);
+        world.fill_all_islands();
+        let mut expected = new_world(config);
+        expected.get_vm_mut().engine_mut().set_rng(EngineRng::new(rand::rngs::StdRng::seed_from_u64(42)));
+        let expected_id = expected.create_island(Box::new(TestIsland {}));
+        expected.fill_all_islands();
+        assert_eq!(
+            population(expected.get_island(expected_id).unwrap()),
+            population(world.get_island(seeded_id).unwrap())
+        );
+    }
+
+    #[test]
+    fn migration_respects_minimum_island_population() {
+        let config = WorldConfiguration {
+            individuals_per_island: 5,
+            number_of_individuals_migrating: MigrationCount::Fixed(10),
+            clone_migrated_individuals: false,
+            minimum_island_population: 2,
+            ..Default::default()
+        };
+        let mut world = new_world(config);
+        let first_id = world.create_island(Box::new(TestIsland {}));
+        let second_id = world.create_island(Box::new(TestIsland {}));
+        world.fill_all_islands();
+        world.run_one_generation();
+
+        world.migrate_individuals_between_islands();
+        assert_eq!(2, world.get_island(first_id).unwrap().len());
+        assert_eq!(2, world.get_island(second_id).unwrap().len());
+        assert_eq!(3, world.get_island(first_id).unwrap().len_future_generation());
+        assert_eq!(3, world.get_island(second_id).unwrap().len_future_generation());
+    }
+
+    #[test]
+    fn most_fit_individual_matches_a_scan() {
+        let config = WorldConfiguration { individuals_per_island: 20, ..Default::default() };
+        let mut world = new_world(config);
+        let id = world.create_island(Box::new(TestIsland {}));
+
+        for _ in 0..5 {
+            world.fill_all_islands();
+            assert!(world.get_island(id).unwrap().most_fit_individual().is_none());
+            world.run_one_generation();
+
+            let island = world.get_island(id).unwrap();
+            let best_scanned = (0..island.len()).map(|i| island.score_for_individual(i).unwrap()).max().unwrap();
+            let most_fit = island.most_fit_individual().unwrap();
+            assert_eq!(best_scanned, most_fit.get_run_result().unwrap().0);
+        }
+    }
+
+    #[test]
+    fn manual_migration() {
+        let config = WorldConfiguration {
+            individuals_per_island: 5,
+            generations_between_migrations: 0,
+            number_of_individuals_migrating: MigrationCount::Fixed(2),
+            ..Default::default()
+        };
+        let mut world = new_world(config);
+        let first_id = world.create_island(Box::new(TestIsland {}));
+        let second_id = world.create_island(Box::new(TestIsland {}));
+        world.fill_all_islands();
+
+        // Automatic migration is disabled, so running generations never migrates anyone
+        for _ in 0..3 {
+            world.run_one_generation();
+            assert_eq!(0, world.get_island(first_id).unwrap().len_future_generation());
+            assert_eq!(0, world.get_island(second_id).unwrap().len_future_generation());
+        }
+
+        // Each manual migration moves the configured number of individuals exactly once
+        for migrations in 1..=3 {
+            world.migrate_now();
+            assert_eq!(2 * migrations, world.get_island(first_id).unwrap().len_future_generation());
+            assert_eq!(2 * migrations, world.get_island(second_id).unwrap().len_future_generation());
+        }
+    }
+
+    #[test]
+    fn weighted_migration() {
+        let config = WorldConfiguration {
+            individuals_per_island: 5,
+            number_of_individuals_migrating: MigrationCount::Fixed(10),
+            clone_migrated_individuals: true,
+            migration_algorithm: MigrationAlgorithm::Weighted,
+            ..Default::default()
+        };
+        let mut world = new_world(config);
+        let light_id = world.create_island(Box::new(TestIsland {}));
+        let other_light_id = world.create_island(Box::new(TestIsland {}));
+        let heavy_id = world.create_island(Box::new(TestIsland {}));
+        world.get_island_mut(heavy_id).unwrap().set_weight(4.0);
+        world.fill_all_islands();
+        world.run_one_generation();
+
+        // Each cycle, the light islands each send 8 of their 10 migrants to the heavy island and the heavy island splits
+        // its migrants between the two light islands. So the heavy island expects 16 migrants and the others expect 7.
+        let cycles = 100;
+        for _ in 0..cycles {
+            world.migrate_individuals_between_islands();
+        }
+        let heavy = world.get_island(heavy_id).unwrap().len_future_generation() as f64;
+        let light = world.get_island(light_id).unwrap().len_future_generation() as f64;
+        let other_light = world.get_island(other_light_id).unwrap().len_future_generation() as f64;
+        assert_eq!(30.0 * cycles as f64, heavy + light + other_light);
+        assert!((heavy / (16.0 * cycles as f64) - 1.0).abs() < 0.05, "heavy island received {} migrants", heavy);
+        assert!((light / (7.0 * cycles as f64) - 1.0).abs() < 0.1, "light island received {} migrants", light);
+
+        // The light islands have nowhere to send migrants when the weights do not add up to a finite number, but the
+        // heavy island still sends its migrants to them
+        world.get_island_mut(heavy_id).unwrap().set_weight(f64::INFINITY);
+        world.migrate_individuals_between_islands();
+        assert_eq!(heavy as usize, world.get_island(heavy_id).unwrap().len_future_generation());
+        let light_after = world.get_island(light_id).unwrap().len_future_generation()
+            + world.get_island(other_light_id).unwrap().len_future_generation();
+        assert_eq!((light + other_light) as usize + 10, light_after);
+    }
+
+    #[test]
+    fn fractional_migration_count() {
+        use rand::SeedableRng;
+
+        for individuals_per_island in [8, 40] {
+            let config = WorldConfiguration {
+                individuals_per_island,
+                generations_between_migrations: 0,
+                number_of_individuals_migrating: MigrationCount::Fraction(0.25),
+                ..Default::default()
+            };
+            let mut world = new_world(config);
+            world.create_island(Box::new(TestIsland {}));
+            world.create_island(Box::new(TestIsland {}));
+            world.fill_all_islands();
+            world.run_one_generation();
+            world.migrate_now();
+            assert_eq!(2 * individuals_per_island / 4, world.recent_migrants.len());
+        }
+
+        let mut rng = rand::rngs::SmallRng::seed_from_u64(1);
+        assert_eq!(3, MigrationCount::Fixed(3).count(100, &mut rng));
+        assert_eq!(3, MigrationCount::Fraction(0.25).count(10, &mut rng));
+        assert!((0..20)
+            .map(|_| MigrationCount::Range(2, 4).count(100, &mut rng))
+            .all(|count| (2..=4).contains(&count)));
+    }
+
+    #[test]
+    fn instruction_coverage() {
+        let config = WorldConfiguration { individuals_per_island: 1, ..Default::default() };
+        let mut world = new_world(config);
+        let first_id = world.create_island(Box::new(TestIsland {}));
+        let second_id = world.create_island(Box::new(TestIsland {}));
+        let fingerprint = world.get_vm().engine().instruction_fingerprint();
+        let source = "( 1 2 INTEGER.SUM INTEGER.SUM )\n( TRUE BOOL.NOT )\n( 3 INTEGER.SUM )\n";
+        assert_eq!(3, world.import_programs(source.as_bytes(), fingerprint).unwrap());
+        world.fill_all_islands();
+        assert_eq!(2, world.get_island(first_id).unwrap().len());
+        assert_eq!(1, world.get_island(second_id).unwrap().len());
+
+        let coverage = world.instruction_coverage();
+        assert_eq!(Some(&2), coverage.get("INTEGER.SUM"));
+        assert_eq!(Some(&1), coverage.get("BOOL.NOT"));
+        assert_eq!(Some(&0), coverage.get("INTEGER.DIFFERENCE"));
+    }
+
+    #[test]
+    fn ablate_instructions() {
+        let config =
+            WorldConfiguration { individuals_per_island: 1, generations_between_migrations: 0, ..Default::default() };
+        let mut world = new_world(config);
+        world.create_island(Box::new(TopIntegerIsland {}));
+        let top_integer = |vm: &mut BaseVm, individual: &Individual<TestResult>| {
+            vm.clear();
+            vm.engine_mut().set_code(individual.get_code().clone());
+            vm.run(100);
+            vm.integer().pop().unwrap_or(0) as f64
+        };
+        assert!(world.ablate_instructions(top_integer).is_empty());
+
+        let fingerprint = world.get_vm().engine().instruction_fingerprint();
+        let source = "( 5 3 INTEGER.PRODUCT ( 1 INTEGER.SUM ) INTEGER.DUP INTEGER.POP )\n";
+        assert_eq!(1, world.import_programs(source.as_bytes(), fingerprint).unwrap());
+        world.fill_all_islands();
+        world.run_one_generation();
+
+        // The program leaves 16 on the INTEGER stack. Without the SUM it leaves 1, without the PRODUCT 4 and without the
+        // DUP nothing at all. The POP only removes the duplicate, so it contributes nothing.
+        let contributions = world.ablate_instructions(top_integer);
+        assert_eq!(4, contributions.len());
+        assert_eq!(Some(&15.0), contributions.get("INTEGER.SUM"));
+        assert_eq!(Some(&12.0), contributions.get("INTEGER.PRODUCT"));
+        assert_eq!(Some(&16.0), contributions.get("INTEGER.DUP"));
+        assert_eq!(Some(&0.0), contributions.get("INTEGER.POP"));
+    }
+
+    // Scores every individual by the number of atoms in its code rather than the number of points
+    #[derive(Clone)]
+    struct AtomCountEvaluator {}
+
+    impl Evaluator<TestResult, BaseVm> for AtomCountEvaluator {
+        fn clone(&self) -> Box<dyn Evaluator<TestResult, BaseVm>> {
+            Box::new(AtomCountEvaluator {})
+        }
+
+        fn evaluate(&self, _vm: &mut BaseVm, individual: &Individual<TestResult>) -> TestResult {
+            TestResult(individual.get_code().extract_atoms().len() as u64)
+        }
+    }
+
+    #[test]
+    fn island_uses_evaluator() {
+        let config = WorldConfiguration { individuals_per_island: 10, ..Default::default() };
+        let mut world = new_world(config);
+        let id = world.create_island(Box::new(TestIsland {}));
+        world.get_island_mut(id).unwrap().set_evaluator(Some(Box::new(AtomCountEvaluator {})));
+        world.fill_all_islands();
+        world.run_one_generation();
+
+        let island = world.get_island(id).unwrap();
+        for individual in population(island) {
+            let atoms = individual.get_code().extract_atoms().len() as u64;
+            assert_eq!(Some(&TestResult(atoms)), individual.get_run_result());
+        }
+        assert_eq!(
+            island.most_fit_individual().unwrap().get_code().extract_atoms().len(),
+            population(island).iter().map(|i| i.get_code().extract_atoms().len()).max().unwrap()
+        );
+    }
+
+    #[test]
+    fn migration_effectiveness() {
+        let config = WorldConfiguration {
+            individuals_per_island: 3,
+            generations_between_migrations: 0,
+            number_of_individuals_migrating: MigrationCount::Fixed(1),
+            select_for_migration: SelectionCurve::StrongPreferenceForFit,
+            ..Default::default()
+        };
+        let mut world = new_world(config);
+        world.create_island(Box::new(TestIsland {}));
+        world.create_island(Box::new(TestIsland {}));
+        assert_eq!(0.0, world.migration_effectiveness());
+
+        // The first island gets large programs and the second gets tiny ones
+        let fingerprint = world.get_vm().engine().instruction_fingerprint();
+        let large = "( 1 2 3 4 5 6 7 8 9 10 11 12 13 14 15 16 17 18 19 20 )";
+        let source = [large, "( 1 )", large, "( 2 )", large, "( 3 )"].join("\n");
+        assert_eq!(6, world.import_programs(source.as_bytes(), fingerprint).unwrap());
+        world.fill_all_islands();
+        world.run_one_generation();
+
+        // A large program migrating to the second island dominates it, but a tiny one does not dominate the first
+        world.migrate_individuals_between_islands();
+        world.fill_all_islands();
+        world.run_one_generation();
+        assert_eq!(0.5, world.migration_effectiveness());
+    }
+
+    #[test]
+    fn metadata_survives_migration() {
+        let config = WorldConfiguration {
+            individuals_per_island: 3,
+            generations_between_migrations: 0,
+            number_of_individuals_migrating: MigrationCount::Fixed(1),
+            ..Default::default()
+        };
+        let mut world = new_world(config);
+        let first_id = world.create_island(Box::new(TestIsland {}));
+        let second_id = world.create_island(Box::new(TestIsland {}));
+        for src in ["( 1 )", "( 2 )", "( 3 )"] {
+            let mut individual = Individual::new(world.get_vm().engine().must_parse(src), FnvHashMap::default(), None);
+            individual.set_meta("origin", "first");
+            world.get_island_mut(first_id).unwrap().add_individual_to_future_generation(individual);
+        }
+        world.fill_all_islands();
+        world.run_one_generation();
+
+        world.migrate_individuals_between_islands();
+        world.fill_all_islands();
+        let migrants = population(world.get_island(second_id).unwrap())
+            .iter()
+            .filter(|individual| individual.get_meta("origin").map(|origin| origin.as_str()) == Some("first"))
+            .count();
+        assert_eq!(1, migrants);
+    }
+
+    #[test]
+    fn random_immigrants_replace_children() {
+        let config = WorldConfiguration {
+            individuals_per_island: 20,
+            elite_individuals_per_generation: 2,
+            random_immigrants_per_generation: 5,
+            ..Default::default()
+        };
+        let mut world = new_world(config);
+        // Only crossover, so every child of a population of "( 1 )" is made of nothing but the literal 1
+        let vm_config = Configuration::new(65536, 100, 100, 0, 1, FnvHashMap::default());
+        world.get_vm_mut().engine_mut().reset_configuration(vm_config);
+        let id = world.create_island(Box::new(TestIsland {}));
+        let fingerprint = world.get_vm().engine().instruction_fingerprint();
+        let source = vec!["( 1 )"; 20].join("\n");
+        assert_eq!(20, world.import_programs(source.as_bytes(), fingerprint).unwrap());
+        world.fill_all_islands();
+        world.run_one_generation();
+
+        world.fill_all_islands();
+        let one = world.get_vm().engine().must_parse("1");
+        let population = population(world.get_island(id).unwrap());
+        let immigrants = population
+            .iter()
+            .filter(|individual| individual.get_code().extract_atoms().iter().any(|a| a != &one))
+            .count();
+        assert_eq!(20, population.len());
+        assert_eq!(5, immigrants);
+    }
+
+    #[test]
+    fn run_with_schedule() {
+        let config = WorldConfiguration { individuals_per_island: 4, ..Default::default() };
+        let mut world = new_world(config);
+        let rates = std::sync::Arc::new(std::sync::Mutex::new(vec![]));
+        world.create_island(Box::new(RateRecordingIsland { rates: rates.clone() }));
+
+        world.run_with_schedule(&[(3, 50, 50), (2, 90, 10)]);
+        let mut expected = vec![(50, 50); 3 * 4];
+        expected.extend(vec![(90, 10); 2 * 4]);
+        assert_eq!(expected, *rates.lock().unwrap());
+
+        // The original rates are restored once the schedule is complete
+        assert_eq!(99, world.get_vm().engine().get_configuration().get_crossover_rate());
+        assert_eq!(1, world.get_vm().engine().get_configuration().get_mutation_rate());
+    }
+
+    // Runs one generation and returns whether the global best individual survived into the next generation
+    fn global_best_survives(global_elite_individuals: usize) -> bool {
+        let config = WorldConfiguration {
+            individuals_per_island: 6,
+            elite_individuals_per_generation: 0,
+            global_elite_individuals,
+            ..Default::default()
+        };
+        let mut world = new_world(config);
+        world.create_island(Box::new(TestIsland {}));
+        world.create_island(Box::new(TestIsland {}));
+        world.fill_all_islands();
+        world.run_one_generation();
+
+        let (best_id, best) = world.global_most_fit(1)[0];
+        let best = best.clone();
+        let best_scanned = (0..2)
+            .flat_map(|id| population(world.get_island(id).unwrap()))
+            .map(|individual| individual.get_run_result().unwrap().0)
+            .max()
+            .unwrap();
+        assert_eq!(best_scanned, best.get_run_result().unwrap().0);
+
+        world.fill_all_islands();
+        population(world.get_island(best_id).unwrap()).iter().any(|individual| individual.genome_eq(&best))
+    }
+
+    #[test]
+    fn global_elite_individuals() {
+        // Without elitism the best individual is replaced by children, but a global elite keeps it on its home island
+        assert!(!global_best_survives(0));
+        assert!(global_best_survives(1));
+    }
+
+    #[test]
+    fn island_capacity() {
+        let vm = new_world(WorldConfiguration::default()).get_vm().clone();
+        let mut world: World<TestResult, BaseVm> = World::with_island_capacity(vm, WorldConfiguration::default(), 2);
+        assert_eq!(Ok(0), world.try_create_island(Box::new(TestIsland {})));
+        assert_eq!(1, world.create_island(Box::new(TestIsland {})));
+        assert_eq!(Err(WorldError::IslandCapacityExceeded(2)), world.try_create_island(Box::new(TestIsland {})));
+        assert_eq!(2, world.get_number_of_islands());
+    }
+
+    #[test]
+    fn validate_migration() {
+        let config = WorldConfiguration { migration_algorithm: MigrationAlgorithm::Cyclical(3), ..Default::default() };
+        let mut world = new_world(config);
+        world.create_island(Box::new(TestIsland {}));
+        assert_eq!(Ok(()), world.validate_migration());
+        for _ in 0..2 {
+            world.create_island(Box::new(TestIsland {}));
+        }
+        assert!(matches!(world.validate_migration(), Err(WorldError::InvalidMigration(_))));
+        world.create_island(Box::new(TestIsland {}));
+        assert_eq!(Ok(()), world.validate_migration());
+
+        let config = WorldConfiguration { migration_algorithm: MigrationAlgorithm::Weighted, ..Default::default() };
+        let mut world = new_world(config);
+        for _ in 0..3 {
+            world.create_island(Box::new(TestIsland {}));
+        }
+        assert_eq!(Ok(()), world.validate_migration());
+        world.get_island_mut(0).unwrap().set_weight(f64::INFINITY);
+        assert!(matches!(world.validate_migration(), Err(WorldError::InvalidMigration(_))));
+        world.get_island_mut(0).unwrap().set_weight(f64::MAX);
+        world.get_island_mut(1).unwrap().set_weight(f64::MAX);
+        assert!(matches!(world.validate_migration(), Err(WorldError::InvalidMigration(_))));
+        world.get_island_mut(0).unwrap().set_weight(0.0);
+        world.get_island_mut(1).unwrap().set_weight(0.0);
+        assert!(matches!(world.validate_migration(), Err(WorldError::InvalidMigration(_))));
+    }
+
+    #[test]
+    fn world_errors() {
+        let config = WorldConfiguration { individuals_per_island: 2, ..Default::default() };
+        let mut world = new_world(config.clone());
+        let id = world.create_island(Box::new(TestIsland {}));
+        assert!(world.get_island(id).is_ok());
+        assert_eq!(Some(WorldError::IslandNotFound(1)), world.get_island(1).err());
+        assert_eq!(Some(WorldError::IslandNotFound(1)), world.get_island_mut(1).err());
+
+        // A VM without any instructions cannot generate code
+        let mut world: World<TestResult, BaseVm> =
+            World::new(BaseVm::new(Some(1), Configuration::new_simple()), config.clone());
+        world.create_island(Box::new(TestIsland {}));
+        assert_eq!(Err(WorldError::NoInstructions), world.try_fill_all_islands());
+        assert_eq!(Err(WorldError::NoInstructions), world.try_run_generations_while(|_| false));
+
+        // No code can be generated when a required instruction does not exist
+        let mut world = new_world(config.clone());
+        world.get_vm_mut().engine_mut().reset_configuration({
+            let mut vm_config = Configuration::new_simple();
+            vm_config.set_required_instructions(vec!["NOT.AN.INSTRUCTION"]);
+            vm_config
+        });
+        world.create_island(Box::new(TestIsland {}));
+        let second_id = world.create_island(Box::new(TestIsland {}));
+        assert_eq!(Err(WorldError::UnknownRequiredInstruction("NOT.AN.INSTRUCTION")), world.try_fill_all_islands());
+        assert_eq!(0, world.get_island(0).unwrap().len());
+        assert_eq!(0, world.get_island(second_id).unwrap().len());
+
+        // An answer stack that the VM does not have could never be read
+        let mut world = new_world(config.clone());
+        let mut vm_config = world.get_vm().engine().get_configuration().clone();
+        vm_config.set_answer_stack(Some("NOT.A.STACK"));
+        world.get_vm_mut().engine_mut().reset_configuration(vm_config);
+        world.create_island(Box::new(TestIsland {}));
+        assert_eq!(Err(WorldError::UnknownAnswerStack("NOT.A.STACK".to_owned())), world.try_fill_all_islands());
+
+        // Elites and parents cannot be selected until the filled generation has been run
+        let mut world = new_world(config.clone());
+        world.create_island(Box::new(TestIsland {}));
+        assert_eq!(Ok(()), world.try_fill_all_islands());
+        let before = population(world.get_island(0).unwrap());
+        assert_eq!(Err(WorldError::IslandNotRun(0)), world.try_fill_all_islands());
+        assert_eq!(before, population(world.get_island(0).unwrap()));
+        world.run_one_generation();
+        assert_eq!(Ok(()), world.try_fill_all_islands());
+
+        let config = WorldConfiguration { threading_model: ThreadingModel::PerIsland(2), ..config };
+        let mut world = new_world(config);
+        world.create_island(Box::new(TestIsland {}));
+        assert_eq!(Err(WorldError::ThreadingUnsupported(ThreadingModel::PerIsland(2))), world.try_fill_all_islands());
+        assert_eq!(0, world.get_island(0).unwrap().len());
+    }
+
+    #[test]
+    fn island_context_id() {
+        let config = WorldConfiguration { individuals_per_island: 1, ..Default::default() };
+        let mut world = new_world(config);
+        let first_id = world.create_island(Box::new(TopIntegerIsland {}));
+        let second_id = world.create_island(Box::new(TopIntegerIsland {}));
+        let unset_id = world.create_island(Box::new(TopIntegerIsland {}));
+        world.get_island_mut(first_id).unwrap().set_context_id(3);
+        world.get_island_mut(second_id).unwrap().set_context_id(7);
+        let fingerprint = world.get_vm().engine().instruction_fingerprint();
+        let source = ["( INTEGER.CONTEXTID 10 INTEGER.PRODUCT 1 INTEGER.SUM )"; 3].join("\n");
+        assert_eq!(3, world.import_programs(source.as_bytes(), fingerprint).unwrap());
+        world.fill_all_islands();
+        world.run_one_generation();
+
+        // An island without a context id does not inherit the id of the island that ran before it
+        let result_on = |id| world.get_island(id).unwrap().most_fit_individual().unwrap().get_run_result().unwrap().0;
+        assert_eq!(31, result_on(first_id));
+        assert_eq!(71, result_on(second_id));
+        assert_eq!(1, result_on(unset_id));
+    }
+
+    #[test]
+    fn lazy_island_context_id() {
+        let config = WorldConfiguration {
+            individuals_per_island: 2,
+            elite_individuals_per_generation: 2,
+            generations_between_migrations: 2,
+            number_of_individuals_migrating: MigrationCount::Fixed(1),
+            lazy_tournament_size: Some(2),
+            ..Default::default()
+        };
+        let mut world = new_world(config);
+        let first_id = world.create_island(Box::new(TopIntegerIsland {}));
+        let second_id = world.create_island(Box::new(TopIntegerIsland {}));
+        world.get_island_mut(first_id).unwrap().set_context_id(3);
+        world.get_island_mut(second_id).unwrap().set_context_id(7);
+        let fingerprint = world.get_vm().engine().instruction_fingerprint();
+        let source = ["( INTEGER.CONTEXTID 10 INTEGER.PRODUCT )"; 4].join("\n");
+        assert_eq!(4, world.import_programs(source.as_bytes(), fingerprint).unwrap());
+        world.fill_all_islands();
+
+        // The individuals are only run when the next generation is filled, after both islands started the generation
+        world.run_one_generation();
+        world.fill_all_islands();
+        for (id, expected) in [(first_id, 30), (second_id, 70)] {
+            for individual in population(world.get_island(id).unwrap()) {
+                assert_eq!(expected, individual.get_run_result().unwrap().0);
+            }
+        }
+
+        // The migrants cannot be compared against an unsorted island, so they do not count as evaluated
+        world.run_one_generation();
+        world.fill_all_islands();
+        world.run_one_generation();
+        assert_eq!(0.0, world.migration_effectiveness());
+        assert_eq!(0, world.migrants_evaluated);
+    }
+
+    #[test]
+    fn merge_islands() {
+        let config = WorldConfiguration {
+            individuals_per_island: 3,
+            migration_algorithm: MigrationAlgorithm::Incremental(2),
+            ..Default::default()
+        };
+        let mut world = new_world(config);
+        let first_id = world.create_island(Box::new(TopIntegerIsland {}));
+        let second_id = world.create_island(Box::new(TopIntegerIsland {}));
+        let third_id = world.create_island(Box::new(TopIntegerIsland {}));
+
+        // The programs are handed out in turn, so the islands hold ( 1 5 3 ), ( 6 2 4 ) and ( 7 8 9 )
+        let fingerprint = world.get_vm().engine().instruction_fingerprint();
+        let source = "( 1 )\n( 6 )\n( 7 )\n( 5 )\n( 2 )\n( 8 )\n( 3 )\n( 4 )\n( 9 )";
+        assert_eq!(9, world.import_programs(source.as_bytes(), fingerprint).unwrap());
+        world.fill_all_islands();
+        world.run_one_generation();
+        let third = population(world.get_island(third_id).unwrap());
+
+        assert_eq!(Err(WorldError::IslandNotFound(3)), world.merge_islands(first_id, 3));
+        assert_eq!(Ok(second_id), world.merge_islands(second_id, second_id));
+        assert_eq!(3, world.get_number_of_islands());
+
+        // The first island is removed, so the merged island and the third island each move down one ID
+        let merged_id = world.merge_islands(second_id, first_id).unwrap();
+        assert_eq!(0, merged_id);
+        assert_eq!(2, world.get_number_of_islands());
+        assert_eq!(third, population(world.get_island(1).unwrap()));
+
+        // The survivors are the fittest three of both islands, most fit last
+        let merged = world.get_island(merged_id).unwrap();
+        let survivors: Vec<u64> = population(merged).iter().map(|i| i.get_run_result().unwrap().0).collect();
+        assert_eq!(vec![4, 5, 6], survivors);
+        assert_eq!(6, merged.most_fit_individual().unwrap().get_run_result().unwrap().0);
+
+        // An incremental distance of two would now send migrants back to their own island
+        assert_eq!(MigrationAlgorithm::Incremental(1), world.config.migration_algorithm);
+        assert_eq!(Ok(()), world.validate_migration());
+        world.migrate_now();
+    }
+
+    #[test]
+    fn merge_islands_cyclical() {
+        let config = WorldConfiguration {
+            individuals_per_island: 3,
+            migration_algorithm: MigrationAlgorithm::Cyclical(2),
+            ..Default::default()
+        };
+        let mut world = new_world(config);
+        for _ in 0..3 {
+            world.create_island(Box::new(TopIntegerIsland {}));
+        }
+        world.fill_all_islands();
+
+        // Islands that have not been run cannot be ranked, so the merge is refused and the World is unchanged
+        assert_eq!(Err(WorldError::IslandNotRun(0)), world.merge_islands(0, 1));
+        assert_eq!(Err(WorldError::IslandNotRun(2)), world.merge_islands(2, 1));
+        assert_eq!(3, world.get_number_of_islands());
+
+        // A cyclical distance of two would now send migrants back to their own island
+        world.run_one_generation();
+        assert_eq!(Ok(0), world.merge_islands(0, 2));
+        assert_eq!(MigrationAlgorithm::Cyclical(1), world.config.migration_algorithm);
+        assert_eq!(Ok(()), world.validate_migration());
+        world.migrate_now();
+    }
+
+    #[test]
+    fn halt_penalty() {
+        let mut vm = BaseVm::new(Some(1), Configuration::new_simple());
+        add_base_instructions(&mut vm);
+        add_base_literals(&mut vm);
+        let island = HaltPenaltyIsland {};
+        let mut run = |code: &str| {
+            let mut individual = Individual::new(vm.engine().must_parse(code), FnvHashMap::default(), None);
+            HaltPenaltyIsland {}.run_individual(&mut vm, &mut individual);
+            individual
+        };
+
+        // Both programs leave 10 on the INTEGER stack, but the second one is stopped before it can halt
+        let halting = run("( 3 7 INTEGER.SUM )");
+        let looping = run("( 3 7 INTEGER.SUM EXEC.Y ( INTEGER.DUP INTEGER.POP ) )");
+        assert!(halting.get_run_result().unwrap().halted());
+        assert!(!looping.get_run_result().unwrap().halted());
+        assert_eq!(10, looping.get_run_result().unwrap().get_result().0);
+        assert_eq!(10, island.score_individual(&halting));
+        assert_eq!(5, island.score_individual(&looping));
+        assert_eq!(std::cmp::Ordering::Less, island.sort_individuals(&looping, &halting));
+
+        // A program that does not halt and scores no more than the penalty ties with a halting program that scores zero
+        let halting_zero = run("( 0 )");
+        let looping_low = run("( 3 EXEC.Y ( INTEGER.DUP INTEGER.POP ) )");
+        assert_eq!(0, island.score_individual(&looping_low));
+        assert_eq!(std::cmp::Ordering::Equal, island.sort_individuals(&looping_low, &halting_zero));
+    }
+
+    #[test]
+    fn size_histogram() {
+        let mut world = new_world(WorldConfiguration::default());
+        let id = world.create_island(Box::new(TestIsland {}));
+        assert!(world.get_island(id).unwrap().size_histogram(3).is_empty());
+
+        // The programs have 2, 4, 4 and 7 points
+        for src in ["( 1 )", "( 1 2 3 )", "( 4 ( 5 ) )", "( 1 2 3 4 5 6 )"] {
+            let individual = Individual::new(world.get_vm().engine().must_parse(src), FnvHashMap::default(), None);
+            world.get_island_mut(id).unwrap().add_individual_to_future_generation(individual);
+        }
+        let island = world.get_island_mut(id).unwrap();
+        island.advance_generation();
+        assert_eq!(vec![1, 2, 1], island.size_histogram(3));
+        assert_eq!(vec![0, 0, 1, 0, 2, 0, 0, 1], island.size_histogram(1));
+        assert_eq!(vec![4], island.size_histogram(10));
+    }
+
+    #[test]
+    fn dump_population() {
+        let mut world = new_world(WorldConfiguration::default());
+        let id = world.create_island(Box::new(TestIsland {}));
+        let mut defined_names = FnvHashMap::default();
+        defined_names.insert(Name::from("b_name"), world.get_vm().engine().must_parse("( 2 3 )"));
+        defined_names.insert(Name::from("a_name"), world.get_vm().engine().must_parse("TRUE"));
+        let programs = [
+            Individual::new(world.get_vm().engine().must_parse("( 1 ( b_name ) INTEGER.SUM )"), defined_names, None),
+            Individual::new(world.get_vm().engine().must_parse("( 4.5 FLOAT.SIN )"), FnvHashMap::default(), None),
+        ];
+        for individual in programs.iter() {
+            world.get_island_mut(id).unwrap().add_individual_to_future_generation(individual.clone());
+        }
+        world.get_island_mut(id).unwrap().advance_generation();
+
+        let mut dump = vec![];
+        world.dump_population(&mut dump).unwrap();
+        let dump = String::from_utf8(dump).unwrap();
+        assert_eq!(
+            "# island 0 (2 individuals)\n( 1 ( b_name ) INTEGER.SUM )\n  ( a_name CODE.QUOTE TRUE CODE.DEFINE )\n  \
+             ( b_name CODE.QUOTE ( 2 3 ) CODE.DEFINE )\n( 4.5 FLOAT.SIN )\n",
+            dump
+        );
+
+        let mut reparsed: Vec<(Code, Vec<Code>)> = vec![];
+        for line in dump.lines().filter(|line| !line.starts_with('#')) {
+            let code = world.get_vm().engine().must_parse(line.trim());
+            if line.starts_with(' ') {
+                reparsed.last_mut().unwrap().1.push(code);
+            } else {
+                reparsed.push((code, vec![]));
+            }
+        }
+        assert_eq!(2, reparsed.len());
+        for (individual, (code, definitions)) in programs.iter().zip(reparsed.iter()) {
+            assert_eq!(individual.get_code(), code);
+            assert_eq!(individual.get_defined_names().len(), definitions.len());
+            for definition in definitions.iter() {
+                let items = definition.to_list();
+                assert_eq!(4, items.len());
+                let name = items[0].extract_names().pop().unwrap();
+                assert_eq!(individual.get_defined_names().get(&name), Some(&items[2]));
+            }
+        }
+    }
+
+    #[test]
+    fn genotypic_diversity() {
+        let mut world = new_world(WorldConfiguration::default());
+        let first_id = world.create_island(Box::new(TestIsland {}));
+        let second_id = world.create_island(Box::new(TestIsland {}));
+        assert_eq!(1.0, world.genotypic_diversity());
+
+        // 1.5 and 1.50 are the same float, so there are three unique programs on the first island
+        let first_programs = ["( 1 )", "( 1 )", "( 1.5 )", "( 1.50 )", "( 2 )"];
+        let second_programs = ["( 2 )", "( 3 )", "( 4 )"];
+        for (id, programs) in [(first_id, &first_programs[..]), (second_id, &second_programs[..])] {
+            for src in programs {
+                let individual = Individual::new(world.get_vm().engine().must_parse(src), FnvHashMap::default(), None);
+                world.get_island_mut(id).unwrap().add_individual_to_future_generation(individual);
+            }
+        }
+        world.get_island_mut(first_id).unwrap().advance_generation();
+        world.get_island_mut(second_id).unwrap().advance_generation();
+
+        assert_eq!(0.6, world.get_island(first_id).unwrap().genotypic_diversity());
+        assert_eq!(1.0, world.get_island(second_id).unwrap().genotypic_diversity());
+        // "( 2 )" is on both islands, so it is only counted once overall
+        assert_eq!(5.0 / 8.0, world.genotypic_diversity());
+    }
+
+    #[test]
+    fn run_until_converged() {
+        // Every individual is a copy of a strongly preferred elite, so the population quickly fills with clones
+        let config = WorldConfiguration {
+            individuals_per_island: 20,
+            elite_individuals_per_generation: 20,
+            select_as_elite: SelectionCurve::StrongPreferenceForFit,
+            ..Default::default()
+        };
+        let mut world = new_world(config);
+        world.create_island(Box::new(TestIsland {}));
+        world.create_island(Box::new(TestIsland {}));
+
+        let mut generations = 0;
+        let converged = world.run_until_converged(0.25, |world| {
+            generations += 1;
+            assert!(world.genotypic_diversity() >= 0.25);
+            generations < 100
+        });
+        assert!(converged);
+        assert!(generations < 100);
+        assert!(world.genotypic_diversity() < 0.25);
+        assert!(world.get_island(0).unwrap().genotypic_diversity() < 0.5);
+
+        // A floor of zero can never be reached, so only the function stops the run
+        assert!(!world.run_until_converged(0.0, |_| false));
+    }
+
+    #[test]
+    fn invalid_individuals_are_never_selected() {
+        use rand::SeedableRng;
+
+        let mut vm = BaseVm::new(Some(1), Configuration::new_simple());
+        add_base_instructions(&mut vm);
+        add_base_literals(&mut vm);
+        let mut world: World<ValidatedResult, BaseVm> = World::new(vm, WorldConfiguration::default());
+        let id = world.create_island(Box::new(ValidatingIsland {}));
+        for src in ["( 1 2 3 4 5 6 7 8 )", "( 1 )", "( 9 9 9 9 9 9 9 )", "( 2 3 )", "( 4 ( 5 ) ( 6 ) )"] {
+            let individual = Individual::new(world.get_vm().engine().must_parse(src), FnvHashMap::default(), None);
+            world.get_island_mut(id).unwrap().add_individual_to_future_generation(individual);
+        }
+        world.get_island_mut(id).unwrap().advance_generation();
+        world.run_one_generation();
+
+        // The invalid individuals have the highest scores, but are sorted as the least fit
+        let island = world.get_island(id).unwrap();
+        let scores: Vec<u64> = population(island).iter().map(|i| i.get_run_result().unwrap().score).collect();
+        assert_eq!(vec![6, 8, 9, 2, 3], scores);
+        assert!(island.most_fit_individual().unwrap().is_valid());
+        assert!(!island.least_fit_individual().unwrap().is_valid());
+        assert!(island.hall_of_fame().last().unwrap().is_valid());
+
+        let mut rng = rand::rngs::SmallRng::seed_from_u64(1);
+        for curve in [SelectionCurve::Fair, SelectionCurve::StrongPreferenceForUnfit, SelectionCurve::PreferenceForFit]
+        {
+            for _ in 0..200 {
+                assert!(island.select_one_individual(curve, &mut rng).unwrap().is_valid());
+            }
+        }
+    }
+
+    // Scores the same way as TestIsland, but counts how many individuals it has evaluated
+    struct CountingEvaluator {
+        evaluations: std::sync::Arc<std::sync::atomic::AtomicUsize>,
+    }
+
+    impl Evaluator<TestResult, BaseVm> for CountingEvaluator {
+        fn clone(&self) -> Box<dyn Evaluator<TestResult, BaseVm>> {
+            Box::new(CountingEvaluator { evaluations: self.evaluations.clone() })
+        }
+
+        fn evaluate(&self, _vm: &mut BaseVm, individual: &Individual<TestResult>) -> TestResult {
+            self.evaluations.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            TestResult(individual.get_code().points() as u64)
+        }
+    }
+
+    #[test]
+    fn lazy_evaluation() {
+        let config = WorldConfiguration {
+            individuals_per_island: 20,
+            generations_between_migrations: 2,
+            number_of_individuals_migrating: MigrationCount::Fixed(2),
+            clone_migrated_individuals: false,
+            lazy_tournament_size: Some(2),
+            ..Default::default()
+        };
+        let mut world = new_world(config);
+        let evaluations = std::sync::Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let evaluation_count = || evaluations.load(std::sync::atomic::Ordering::SeqCst);
+        for _ in 0..2 {
+            let id = world.create_island(Box::new(TestIsland {}));
+            let evaluator = CountingEvaluator { evaluations: evaluations.clone() };
+            world.get_island_mut(id).unwrap().set_evaluator(Some(Box::new(evaluator)));
+        }
+        world.fill_all_islands();
+        world.run_one_generation();
+        assert_eq!(0, evaluation_count());
+
+        // Three tournaments of two individuals run at most six of them, and each is only run once
+        let mut vm = world.get_vm().clone();
+        let island = world.get_island_mut(0).unwrap();
+        for _ in 0..3 {
+            island.select_one_individual_lazily(2, &mut vm).unwrap();
+        }
+        let evaluated = population(island).iter().filter(|individual| individual.get_run_result().is_some()).count();
+        assert_eq!(evaluated, evaluation_count());
+        assert!(evaluated <= 6);
+        for _ in 0..3 {
+            island.select_one_individual_lazily(1, &mut vm).unwrap();
+        }
+        let evaluated = population(island).iter().filter(|individual| individual.get_run_result().is_some()).count();
+        assert_eq!(evaluated, evaluation_count());
+
+        // Breeding, elitism and migration all work without running the whole population. The last generation has only
+        // been drawn from by the migration, so most of its individuals have never been run.
+        let mut generations = 0;
+        world.run_generations_while(|_| {
+            generations += 1;
+            generations < 5
+        });
+        for id in 0..2 {
+            let island = world.get_island(id).unwrap();
+            let evaluated =
+                population(island).iter().filter(|individual| individual.get_run_result().is_some()).count();
+            assert!(evaluated <= 4);
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn configurations_round_trip_through_serde() {
+        let world_config = WorldConfiguration {
+            individuals_per_island: 7,
+            elite_individuals_per_generation: 1,
+            generations_between_migrations: 3,
+            number_of_individuals_migrating: MigrationCount::Range(1, 3),
+            migration_algorithm: MigrationAlgorithm::Incremental(2),
+            clone_migrated_individuals: false,
+            minimum_island_population: 4,
+            select_for_migration: SelectionCurve::Power(2.5),
+            select_as_parent: SelectionCurve::Fair,
+            select_as_elite: SelectionCurve::StrongPreferenceForUnfit,
+            hall_of_fame_size: 5,
+            random_immigrants_per_generation: 6,
+            global_elite_individuals: 8,
+            lazy_tournament_size: Some(3),
+            threading_model: ThreadingModel::None,
+        };
+        let json = serde_json::to_string(&world_config).unwrap();
+        assert_eq!(world_config, serde_json::from_str(&json).unwrap());
+
+        let mut weights = FnvHashMap::default();
+        weights.insert("INTEGER.SUM", 5);
+        weights.insert("BOOL.AND", 0);
+        let mut config = Configuration::new(1000, 50, 80, 20, 3, weights);
+        config.set_list_expansion_cost(ExpansionCost::PerItem);
+        config.set_deep_index_policy(DeepIndexPolicy::Clamp);
+        config.set_name_inheritance(NameInheritance::Union);
+        config.set_random_name_format("GEN.", RandomNameEncoding::Hex);
+        config.set_name_rand_reuse_probability(0.5);
+        config.set_max_name_length(64);
+        config.set_max_nesting_depth(10);
+        config.set_max_defined_names(Some(12));
+        config.set_float_display_precision(Some(3));
+        config.set_float_equality_epsilon(0.01);
+        config.set_init_template_fraction(0.25);
+        config.set_defined_name_weight_cap(Some(9));
+        config.set_required_instructions(vec!["INTEGER.SUM", "EXEC.IF"]);
+        config.set_answer_stack(Some("FLOAT"));
+        let json = serde_json::to_string(&config).unwrap();
+        assert_eq!(config, serde_json::from_str(&json).unwrap());
+    }
+
+    #[test]
+    fn score_is_calculated_once_per_individual_per_generation() {
+        let config = WorldConfiguration { individuals_per_island: 50, ..Default::default() };
+        let mut world = new_world(config);
+        let scores_calculated = std::sync::Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        world.create_island(Box::new(CountingIsland { scores_calculated: scores_calculated.clone() }));
+
+        for generation in 1..=3 {
+            world.fill_all_islands();
+            world.run_one_generation();
+            assert_eq!(50 * generation, scores_calculated.load(std::sync::atomic::Ordering::SeqCst));
+        }
+    }
+}
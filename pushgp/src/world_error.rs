@@ -0,0 +1,65 @@
+use crate::*;
+
+/// The reasons that a World can reject a change to its islands or configuration, or fail to run a generation
+#[derive(Clone, Debug, PartialEq)]
+pub enum WorldError {
+    /// The World already has the maximum number of islands it was created with. The parameter is the capacity.
+    IslandCapacityExceeded(usize),
+
+    /// The migration algorithm cannot work with the current islands. The parameter describes the problem.
+    InvalidMigration(String),
+
+    /// The virtual machine has no instructions with a positive weight, so no random code can be generated.
+    NoInstructions,
+
+    /// One of the configured required instructions is not registered with the virtual machine, so no random code can
+    /// be generated. The parameter is the name of the instruction.
+    UnknownRequiredInstruction(&'static str),
+
+    /// The configured answer stack is not one of the stacks of the virtual machine, so `VirtualMachine::answer` could
+    /// never return anything. The parameter is the name of the stack.
+    UnknownAnswerStack(String),
+
+    /// No valid individual could be generated for an island within the retry limit, usually because the configuration
+    /// does not allow enough points or nesting for the code being generated. The parameter is the island.
+    GenerationFailed(IslandId),
+
+    /// Elites and parents cannot be selected from the island because its current generation has not been run since it
+    /// was filled, such as when the islands are filled twice in a row. The parameter is the island.
+    IslandNotRun(IslandId),
+
+    /// There is no island with the ID. The parameter is the ID.
+    IslandNotFound(IslandId),
+
+    /// The World cannot run with the configured threading model yet. The parameter is the model.
+    ThreadingUnsupported(ThreadingModel),
+}
+
+impl std::fmt::Display for WorldError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            WorldError::IslandCapacityExceeded(capacity) => {
+                write!(f, "the world cannot have more than {} islands", capacity)
+            }
+            WorldError::InvalidMigration(message) => write!(f, "invalid migration: {}", message),
+            WorldError::NoInstructions => write!(f, "the virtual machine has no instructions to generate code from"),
+            WorldError::UnknownRequiredInstruction(name) => {
+                write!(f, "the required instruction {} is not registered with the virtual machine", name)
+            }
+            WorldError::UnknownAnswerStack(name) => {
+                write!(f, "the virtual machine has no {} stack to answer from", name)
+            }
+            WorldError::GenerationFailed(id) => write!(
+                f,
+                "unable to generate code for island {} that doesn't use excessive number of Code in list. Check \
+                 configuration.",
+                id
+            ),
+            WorldError::IslandNotRun(id) => write!(f, "the current generation of island {} has not been run", id),
+            WorldError::IslandNotFound(id) => write!(f, "there is no island {}", id),
+            WorldError::ThreadingUnsupported(model) => write!(f, "the threading model {:?} is not supported", model),
+        }
+    }
+}
+
+impl std::error::Error for WorldError {}
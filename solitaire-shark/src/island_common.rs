@@ -1,7 +1,7 @@
 use pushgp::VirtualMachine;
 use rand::{rngs::SmallRng, Rng, SeedableRng};
 
-use crate::{solitaire_result::SolitaireResults, GameState, SolitareVm};
+use crate::{solitaire_result::SolitaireResults, SolitareVm, VirtualMachineMustHaveGame};
 
 const GAMES_PER_RUN: usize = 100;
 
@@ -43,25 +43,16 @@ impl IslandCommon {
 
             // Setup this individuals' code and functions
             vm.engine_mut().set_code(individual.get_code().clone());
-            for (name, code) in individual.get_defined_names().iter() {
-                vm.engine_mut().define_name(name.clone(), code.clone());
-            }
+            vm.engine_mut().load_defined_names(individual.get_defined_names());
 
-            // Setup a new GameState. If this is not the first game, we also need to save the previous game's state.
-            let previous_game =
-                vm.swap_game_state(GameState::new(*self.game_seeds.get(game_index).unwrap()));
-            if game_index != 0 {
-                result.save_game(previous_game);
-            }
+            // Deal this game from its seed
+            vm.reset_problem_state(*self.game_seeds.get(game_index).unwrap());
 
-            // Run the vm for up to 10_000 instructions
+            // Run the vm for up to 10_000 instructions, then save the state the game ended in
             vm.run(10_000);
+            result.save_game(vm.game().clone());
         }
 
-        // Save the GameState from the last game
-        let last_game = vm.swap_game_state(GameState::new(1));
-        result.save_game(last_game);
-
         // Save the output of all games in the SolitaireResults for the Individual
         individual.set_run_result(Some(result));
     }
@@ -26,11 +26,6 @@ impl SolitareVm {
 
         vm
     }
-
-    pub fn swap_game_state(&mut self, mut to_swap: GameState) -> GameState {
-        std::mem::swap(&mut self.game, &mut to_swap);
-        to_swap
-    }
 }
 
 impl VirtualMachine for SolitareVm {
@@ -50,6 +45,27 @@ impl VirtualMachine for SolitareVm {
         self.integer_stack.clear();
         self.name_stack.clear();
     }
+
+    fn peek_stack(&mut self, stack: &str) -> Option<Data> {
+        match stack {
+            "BOOL" => self.bool_stack.peek().map(|value| Data::Integer(value as i64)),
+            "CARD" => self.card_stack.peek().map(|card| card.into()),
+            "CODE" => self.code_stack.peek().map(|code| Data::CodeList(vec![code])),
+            "EXEC" => self.engine.exec().peek().map(|code| Data::CodeList(vec![code])),
+            "INTEGER" => self.integer_stack.peek().map(Data::Integer),
+            "NAME" => self.name_stack.peek().map(Data::Name),
+            _ => None,
+        }
+    }
+
+    fn stack_names(&self) -> &'static [&'static str] {
+        &["BOOL", "CARD", "CODE", "EXEC", "INTEGER", "NAME"]
+    }
+
+    /// Deals a new game shuffled with the seed
+    fn reset_problem_state(&mut self, seed: u64) {
+        self.game = GameState::new(seed);
+    }
 }
 
 impl VirtualMachineMustHaveBool<SolitareVm> for SolitareVm {
@@ -273,3 +289,36 @@ pub fn add_instructions(vm: &mut SolitareVm) {
     vm.engine_mut()
         .add_instruction::<pushgp::NameLiteralValue>();
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reset_problem_state_is_reproducible() {
+        let mut vm = SolitareVm::new(1, Configuration::new_simple());
+        add_instructions(&mut vm);
+        let play = |vm: &mut SolitareVm, seed: u64| {
+            vm.clear();
+            vm.engine_mut().parse_and_set_code("( CARD.DRAWNEXTTHREE CARD.DRAWNEXTTHREE )").unwrap();
+            vm.reset_problem_state(seed);
+            vm.run(100);
+            vm.game().clone()
+        };
+
+        let first = play(&mut vm, 42);
+        play(&mut vm, 7);
+        assert_eq!(first, play(&mut vm, 42));
+        assert_ne!(first, play(&mut vm, 7));
+    }
+
+    #[test]
+    fn answer() {
+        let mut vm = SolitareVm::new(1, Configuration::new_simple());
+        add_instructions(&mut vm);
+        assert_eq!(Ok(()), vm.validate_answer_stack());
+        vm.engine_mut().parse_and_set_code("( 3 4 INTEGER.SUM )").unwrap();
+        vm.run(100);
+        assert_eq!(Some(Data::Integer(7)), vm.answer());
+    }
+}